@@ -1,39 +1,255 @@
+use core::fmt;
+
 use pinocchio::program_error::ProgramError;
 
+/// Errors returned by the program, as `ProgramError::Custom(code)`.
+///
+/// Codes are stable. New errors are appended, and removed errors leave their code unused.
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PimeError {
     IncorrectPDA = 0,
     UnsupportedTokenProgram = 1,
     InvalidMintTokenProgram = 2,
     Unserializeable = 3,
     Undeserializeable = 4,
-    WithdrawLimitReachedTransactions,
-    WithdrawLimitReachedAmount,
-    AuthorityError,
-    UnsupporedTokenProgram,
-    VaultWarmupViolation,
-    DestinationMismatch,
-    TransferWarmingUp,
-    TransferExpired,
-    VaultHasOpenTransfers,
-    VaultIsNotEmpty,
+    WithdrawLimitReachedTransactions = 5,
+    WithdrawLimitReachedAmount = 6,
+    AuthorityError = 7,
+    // 8 was a misspelled duplicate of UnsupportedTokenProgram.
+    VaultWarmupViolation = 9,
+    DestinationMismatch = 10,
+    TransferWarmingUp = 11,
+    TransferExpired = 12,
+    VaultHasOpenTransfers = 13,
+    VaultIsNotEmpty = 14,
+    VaultWarmingUp = 15,
+    WithdrawCoolingDown = 16,
+    FundsLocked = 17,
+    LotNotMatured = 18,
+    LotPayerMismatch = 19,
+    DepositorNotAllowed = 20,
+    DepositBelowMinimum = 21,
+    ReturnExceedsDeposits = 22,
+    InvalidMetadata = 23,
+    AccountNotInitialized = 24,
+    InvalidAccountOwner = 25,
+    InvalidAccountLength = 26,
+    InvalidDiscriminator = 27,
+    OutdatedAccountVersion = 28,
+    UnsupportedAccountVersion = 29,
+    UnknownInstruction = 30,
+    InvalidInstructionDataLength = 31,
+    InstructionMismatch = 32,
+    InvalidTimeframe = 33,
+    InvalidWithdrawCooldown = 34,
+    InvalidWithdrawWarmup = 35,
+    InvalidTransferWarmup = 36,
+    InvalidTransferValidity = 37,
+    InvalidLotMaturity = 38,
+    InvalidLotAmount = 39,
+    InvalidDepositPolicy = 40,
+    AccountNotMigratable = 41,
+    InvalidTokenAccount = 42,
+    UnsupportedAssociatedTokenProgram = 43,
+    CrankNotAllowed = 44,
+    InvalidCrankTip = 45,
+    InvalidTransferStream = 46,
+    PartialDrawNotAllowed = 47,
+    DrawExceedsReleased = 48,
+    InvalidAmendment = 49,
+    // 50 rejected reused transfer indices, which are now assigned by the vault.
+    TransferRegistryFull = 51,
+    InvalidMemo = 52,
+    NotClaimLink = 53,
+    TransferIsClaimLink = 54,
+    InvalidHashLock = 55,
+    HashLocked = 56,
+    InvalidPreimage = 57,
+    InvalidSwap = 58,
+    SwapMismatch = 59,
+    TransferIsSwap = 60,
+    InvalidCampaign = 61,
+    CampaignEnded = 62,
+    CampaignActive = 63,
+    CampaignTargetNotReached = 64,
+    CampaignNotFinalized = 65,
+    PledgeWithdrawNotAllowed = 66,
+    // 67 rejected skipped transfer indices, which are now assigned by the vault.
+    NoOpenTransfers = 68,
+    LotScheduleFull = 69,
+    TransfersNotAllowed = 70,
 
+    Unknown = u8::MAX,
+}
 
+impl PimeError {
 
+    /// A human readable description of the error.
+    pub fn description(&self) -> &'static str {
+        match self {
+            PimeError::IncorrectPDA => "Account address does not match its expected PDA.",
+            PimeError::UnsupportedTokenProgram => "Token program is not supported.",
+            PimeError::InvalidMintTokenProgram => "Mint is not owned by the token program.",
+            PimeError::Unserializeable => "Data could not be serialized.",
+            PimeError::Undeserializeable => "Data could not be deserialized.",
+            PimeError::WithdrawLimitReachedTransactions => "Withdraw limit reached: too many withdraws within the vault's timeframe.",
+            PimeError::WithdrawLimitReachedAmount => "Withdraw limit reached: amount exceeds the vault's allowance within its timeframe.",
+            PimeError::AuthorityError => "Signer is not the vault's authority.",
+            PimeError::VaultWarmupViolation => "Transfer warm-up is shorter than the vault's minimum.",
+            PimeError::DestinationMismatch => "Destination does not match the transfer's destination.",
+            PimeError::TransferWarmingUp => "Transfer is still warming up.",
+            PimeError::TransferExpired => "Transfer has expired.",
+            PimeError::VaultHasOpenTransfers => "Vault has open transfers.",
+            PimeError::VaultIsNotEmpty => "Vault is not empty.",
+            PimeError::VaultWarmingUp => "Vault is warming up after being created or funded.",
+            PimeError::WithdrawCoolingDown => "Vault is cooling down after the previous withdraw.",
+            PimeError::FundsLocked => "Vault funds are locked until their lots mature.",
+            PimeError::LotNotMatured => "Lot has not matured.",
+            PimeError::LotPayerMismatch => "Account is not the lot's rent payer.",
+            PimeError::DepositorNotAllowed => "Depositor is not allowed to deposit into the vault.",
+            PimeError::DepositBelowMinimum => "Deposit is below the vault's minimum deposit.",
+            PimeError::ReturnExceedsDeposits => "Return exceeds what the depositor has deposited.",
+            PimeError::InvalidMetadata => "Metadata is too long or not valid UTF-8.",
+            PimeError::AccountNotInitialized => "Account is not initialized.",
+            PimeError::InvalidAccountOwner => "Account has an incorrect owner.",
+            PimeError::InvalidAccountLength => "Account data has an incorrect length.",
+            PimeError::InvalidDiscriminator => "Account is of another type.",
+            PimeError::OutdatedAccountVersion => "Account has to be migrated.",
+            PimeError::UnsupportedAccountVersion => "Account version is not supported.",
+            PimeError::UnknownInstruction => "Instruction is unknown.",
+            PimeError::InvalidInstructionDataLength => "Instruction data has an incorrect length.",
+            PimeError::InstructionMismatch => "Instruction data belongs to another instruction.",
+            PimeError::InvalidTimeframe => "Timeframe must be positive.",
+            PimeError::InvalidWithdrawCooldown => "Withdraw cool-down must be positive.",
+            PimeError::InvalidWithdrawWarmup => "Withdraw warm-up must be positive.",
+            PimeError::InvalidTransferWarmup => "Transfer warm-up must be positive.",
+            PimeError::InvalidTransferValidity => "Transfer validity must be positive.",
+            PimeError::InvalidLotMaturity => "Lot maturity must be in the future.",
+            PimeError::InvalidLotAmount => "Lot amount must be above zero.",
+            PimeError::InvalidDepositPolicy => "Deposit policy is unknown.",
+            PimeError::AccountNotMigratable => "Account version can not be migrated.",
+            PimeError::InvalidTokenAccount => "Account is not a token account.",
+            PimeError::UnsupportedAssociatedTokenProgram => "Associated token program is not supported.",
+            PimeError::CrankNotAllowed => "Transfer does not allow being cranked.",
+            PimeError::InvalidCrankTip => "Crank tip requires the transfer to allow being cranked.",
+            PimeError::InvalidTransferStream => "Transfer stream must be positive and end within the transfer's validity.",
+            PimeError::PartialDrawNotAllowed => "Transfer does not allow partial draws.",
+            PimeError::DrawExceedsReleased => "Draw exceeds what the transfer has released.",
+            PimeError::InvalidAmendment => "Amended amount must exceed what has been drawn.",
+            PimeError::TransferRegistryFull => "Vault has too many open transfers.",
+            PimeError::InvalidMemo => "Memo is too long or not valid UTF-8.",
+            PimeError::NotClaimLink => "Transfer is not a claim link.",
+            PimeError::TransferIsClaimLink => "Claim-link transfers can only be claimed by their claim key.",
+            PimeError::InvalidHashLock => "Hash lock is unknown, or combined with partial draws or a claim link.",
+            PimeError::HashLocked => "Hash-locked transfers can only be executed with their preimage, or refunded once expired.",
+            PimeError::InvalidPreimage => "Preimage does not match the transfer's hash lock.",
+            PimeError::InvalidSwap => "Swap lacks its counterpart amount, or is combined with cranks, partial draws, claim links or hash locks.",
+            PimeError::SwapMismatch => "Swap does not match its counterpart's booking.",
+            PimeError::TransferIsSwap => "Swaps can only be settled together with their counterpart.",
+            PimeError::InvalidCampaign => "Campaign needs a target, a future deadline, and a recipient of its mint.",
+            PimeError::CampaignEnded => "Campaign has passed its deadline or been finalized.",
+            PimeError::CampaignActive => "Campaign has not yet passed its deadline.",
+            PimeError::CampaignTargetNotReached => "Campaign pledges did not reach its target.",
+            PimeError::CampaignNotFinalized => "Campaign reached its target, and has to be finalized first.",
+            PimeError::PledgeWithdrawNotAllowed => "Campaign does not allow withdrawing pledges.",
+            PimeError::NoOpenTransfers => "Vault has no open transfers.",
+            PimeError::LotScheduleFull => "Vault has too many unmatured lots.",
+            PimeError::TransfersNotAllowed => "Vault does not allow transfers.",
+            PimeError::Unknown => "Unknown error.",
+        }
+    }
+}
 
-    Unknown = u8::MAX,
+impl fmt::Display for PimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
 }
 
 impl From<u8> for PimeError {
     fn from(value: u8) -> Self {
-        match value { 
-            // SAFETY value is guaranteed to be in range of PimeError enum
-            0..=0 => unsafe { core::mem::transmute::<u8, PimeError>(value) },
+        match value {
+            0 => PimeError::IncorrectPDA,
+            1 => PimeError::UnsupportedTokenProgram,
+            2 => PimeError::InvalidMintTokenProgram,
+            3 => PimeError::Unserializeable,
+            4 => PimeError::Undeserializeable,
+            5 => PimeError::WithdrawLimitReachedTransactions,
+            6 => PimeError::WithdrawLimitReachedAmount,
+            7 => PimeError::AuthorityError,
+            9 => PimeError::VaultWarmupViolation,
+            10 => PimeError::DestinationMismatch,
+            11 => PimeError::TransferWarmingUp,
+            12 => PimeError::TransferExpired,
+            13 => PimeError::VaultHasOpenTransfers,
+            14 => PimeError::VaultIsNotEmpty,
+            15 => PimeError::VaultWarmingUp,
+            16 => PimeError::WithdrawCoolingDown,
+            17 => PimeError::FundsLocked,
+            18 => PimeError::LotNotMatured,
+            19 => PimeError::LotPayerMismatch,
+            20 => PimeError::DepositorNotAllowed,
+            21 => PimeError::DepositBelowMinimum,
+            22 => PimeError::ReturnExceedsDeposits,
+            23 => PimeError::InvalidMetadata,
+            24 => PimeError::AccountNotInitialized,
+            25 => PimeError::InvalidAccountOwner,
+            26 => PimeError::InvalidAccountLength,
+            27 => PimeError::InvalidDiscriminator,
+            28 => PimeError::OutdatedAccountVersion,
+            29 => PimeError::UnsupportedAccountVersion,
+            30 => PimeError::UnknownInstruction,
+            31 => PimeError::InvalidInstructionDataLength,
+            32 => PimeError::InstructionMismatch,
+            33 => PimeError::InvalidTimeframe,
+            34 => PimeError::InvalidWithdrawCooldown,
+            35 => PimeError::InvalidWithdrawWarmup,
+            36 => PimeError::InvalidTransferWarmup,
+            37 => PimeError::InvalidTransferValidity,
+            38 => PimeError::InvalidLotMaturity,
+            39 => PimeError::InvalidLotAmount,
+            40 => PimeError::InvalidDepositPolicy,
+            41 => PimeError::AccountNotMigratable,
+            42 => PimeError::InvalidTokenAccount,
+            43 => PimeError::UnsupportedAssociatedTokenProgram,
+            44 => PimeError::CrankNotAllowed,
+            45 => PimeError::InvalidCrankTip,
+            46 => PimeError::InvalidTransferStream,
+            47 => PimeError::PartialDrawNotAllowed,
+            48 => PimeError::DrawExceedsReleased,
+            49 => PimeError::InvalidAmendment,
+            51 => PimeError::TransferRegistryFull,
+            52 => PimeError::InvalidMemo,
+            53 => PimeError::NotClaimLink,
+            54 => PimeError::TransferIsClaimLink,
+            55 => PimeError::InvalidHashLock,
+            56 => PimeError::HashLocked,
+            57 => PimeError::InvalidPreimage,
+            58 => PimeError::InvalidSwap,
+            59 => PimeError::SwapMismatch,
+            60 => PimeError::TransferIsSwap,
+            61 => PimeError::InvalidCampaign,
+            62 => PimeError::CampaignEnded,
+            63 => PimeError::CampaignActive,
+            64 => PimeError::CampaignTargetNotReached,
+            65 => PimeError::CampaignNotFinalized,
+            66 => PimeError::PledgeWithdrawNotAllowed,
+            68 => PimeError::NoOpenTransfers,
+            69 => PimeError::LotScheduleFull,
+            70 => PimeError::TransfersNotAllowed,
             _ => PimeError::Unknown
         }
     }
 }
 
+/// Reads the code of a `ProgramError::Custom`. Codes outside of the `u8` range are `Unknown`.
+impl From<u32> for PimeError {
+    fn from(value: u32) -> Self {
+        u8::try_from(value).map_or(PimeError::Unknown, PimeError::from)
+    }
+}
+
 impl From<PimeError> for ProgramError {
     fn from(value: PimeError) -> Self {
         ProgramError::Custom(value as u32)
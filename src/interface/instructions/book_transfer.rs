@@ -1,36 +1,111 @@
 use pinocchio::{pubkey::Pubkey, sysvars::clock::UnixTimestamp};
 
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::{Transmutable, hash_lock::{HASH_LEN, HashLock}, memo::Memo}};
 
     ///   - `u64`       The amount to transfer (without decimals).
     ///   - `u64`       The vault index.
-    ///   - `u64`       The transfer index.
     ///   - `UnixTimestamp` Warmup period
     ///   - `UnixTimestamp` Validity period
+    ///   - `u8`        If anyone may crank the transfer.
+    ///   - `u64`       The crank tip, in lamports.
+    ///   - `u8`        If the transfer may be drawn partially.
+    ///   - `UnixTimestamp` Stream period after the warm-up
+    ///   - `Memo`      Memo attached when the transfer is executed.
+    ///   - `u8`        If the destination is an ephemeral claim key.
+    ///   - `u8`        The hash lock's hash function.
+    ///   - `[u8; 32]`  The hash lock's hash.
+    ///   - `Pubkey`    The counterpart transfer of a swap.
+    ///   - `u64`       The amount the counterpart pays in return.
 #[repr(C)]
 pub struct BookTransferInstructionData {
     pub discriminator: u8,
     amount: [u8; size_of::<u64>()],
     pub destination: Pubkey,
     vault_index: [u8; size_of::<u64>()],
-    transfer_index: [u8; size_of::<u64>()],
     warmup: [u8; size_of::<UnixTimestamp>()],
     validity: [u8; size_of::<UnixTimestamp>()],
+    allows_crank: u8,
+    crank_tip: [u8; size_of::<u64>()],
+    allows_partial: u8,
+    stream: [u8; size_of::<UnixTimestamp>()],
+    memo: Memo,
+    claim_link: u8,
+    hash_lock: u8,
+    hash: [u8; HASH_LEN],
+    swap: Pubkey,
+    swap_amount: [u8; size_of::<u64>()],
 }
 
 impl BookTransferInstructionData {
-    pub fn new(amount: u64, destination: Pubkey, vault_index: u64, transfer_index: u64, warmup: UnixTimestamp, validity: UnixTimestamp) -> Self{
+    pub fn new(amount: u64, destination: Pubkey, vault_index: u64, warmup: UnixTimestamp, validity: UnixTimestamp) -> Self{
+        Self::new_crankable(amount, destination, vault_index, warmup, validity, false, 0)
+    }
+
+    /// Transfer which anyone may execute within its validity, or refund once it has expired.
+    /// The crank is paid `crank_tip` lamports by the authority.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_crankable(amount: u64, destination: Pubkey, vault_index: u64, warmup: UnixTimestamp, validity: UnixTimestamp, allows_crank: bool, crank_tip: u64) -> Self{
         Self { 
             discriminator: PimeInstruction::BookTransfer as u8, 
             amount: amount.to_le_bytes(),
             destination,
             vault_index: vault_index.to_le_bytes(),
-            transfer_index: transfer_index.to_le_bytes(),
             warmup: warmup.to_le_bytes(),
             validity: validity.to_le_bytes(),
+            allows_crank: allows_crank as u8,
+            crank_tip: crank_tip.to_le_bytes(),
+            allows_partial: 0,
+            stream: 0i64.to_le_bytes(),
+            memo: Memo::empty(),
+            claim_link: 0,
+            hash_lock: HashLock::None as u8,
+            hash: [0; HASH_LEN],
+            swap: Pubkey::default(),
+            swap_amount: 0u64.to_le_bytes(),
         }
     }
 
+    /// Lets the transfer be drawn in several partial executions, up to its amount.
+    pub fn with_partial_draws(mut self) -> Self {
+        self.allows_partial = 1;
+        self
+    }
+
+    /// Releases the amount linearly over `stream` seconds after the warm-up.
+    pub fn with_stream(mut self, stream: UnixTimestamp) -> Self {
+        self.stream = stream.to_le_bytes();
+        self
+    }
+
+    /// Stores the memo with the transfer, to be attached to the transaction executing it.
+    pub fn with_memo(mut self, memo: Memo) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    /// Makes the destination an ephemeral claim key, whose holder claims the transfer to an account
+    /// of their choosing once warmed up.
+    pub fn as_claim_link(mut self) -> Self {
+        self.claim_link = 1;
+        self
+    }
+
+    /// Locks the transfer with the hash, so that it can only be executed with its preimage, and is
+    /// refunded to the vault once expired.
+    pub fn with_hash_lock(mut self, hash_lock: HashLock, hash: [u8; HASH_LEN]) -> Self {
+        self.hash_lock = hash_lock as u8;
+        self.hash = hash;
+        self
+    }
+
+    /// Makes the transfer one side of a swap, settled together with the counterpart transfer once it
+    /// pays `amount` in return. See `SettleSwap`.
+    pub fn with_swap(mut self, counterpart: Pubkey, amount: u64) -> Self {
+        self.swap = counterpart;
+        self.swap_amount = amount.to_le_bytes();
+        self
+    }
+
     pub fn amount(&self) -> u64 {
          u64::from_le_bytes(self.amount)
     }
@@ -39,8 +114,52 @@ impl BookTransferInstructionData {
         u64::from_le_bytes(self.vault_index)
     }
 
-    pub fn transfer_index(&self) -> u64 {
-        u64::from_le_bytes(self.transfer_index)
+    pub fn warmup(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.warmup)
+    }
+
+    pub fn validity(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.validity)
+    }
+
+    pub fn allows_crank(&self) -> bool {
+        self.allows_crank != 0
+    }
+
+    pub fn crank_tip(&self) -> u64 {
+        u64::from_le_bytes(self.crank_tip)
+    }
+
+    pub fn allows_partial(&self) -> bool {
+        self.allows_partial != 0
+    }
+
+    pub fn stream(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.stream)
+    }
+
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+
+    pub fn is_claim_link(&self) -> bool {
+        self.claim_link != 0
+    }
+
+    pub fn hash_lock(&self) -> u8 {
+        self.hash_lock
+    }
+
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+
+    pub fn swap(&self) -> &Pubkey {
+        &self.swap
+    }
+
+    pub fn swap_amount(&self) -> u64 {
+        u64::from_le_bytes(self.swap_amount)
     }
 }
 
@@ -49,3 +168,9 @@ impl BookTransferInstructionData {
 unsafe impl Transmutable for BookTransferInstructionData {
     const LEN: usize = size_of::<Self>();
 }
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for BookTransferInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::BookTransfer;
+}
@@ -1,4 +1,4 @@
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
 
 #[repr(C)]
 pub struct CloseVaultInstructionData {
@@ -26,3 +26,9 @@ impl CloseVaultInstructionData {
 unsafe impl Transmutable for CloseVaultInstructionData {
     const LEN: usize = size_of::<Self>();
 }
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for CloseVaultInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::CloseVault;
+}
@@ -0,0 +1,53 @@
+use pinocchio::sysvars::clock::UnixTimestamp;
+
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct CreateCampaignInstructionData {
+    pub discriminator: u8,
+    campaign_index: [u8; size_of::<u64>()],
+    target: [u8; size_of::<u64>()],
+    deadline: [u8; size_of::<UnixTimestamp>()],
+    allows_withdraw: u8,
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for CreateCampaignInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for CreateCampaignInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::CreateCampaign;
+}
+
+impl CreateCampaignInstructionData {
+    
+    pub fn new(campaign_index: u64, target: u64, deadline: UnixTimestamp, allows_withdraw: bool) -> Self{
+        Self { 
+            discriminator: PimeInstruction::CreateCampaign as u8, 
+            campaign_index: campaign_index.to_le_bytes(),
+            target: target.to_le_bytes(),
+            deadline: deadline.to_le_bytes(),
+            allows_withdraw: allows_withdraw as u8,
+        }
+    }
+
+    pub fn campaign_index(&self) -> u64 {
+        u64::from_le_bytes(self.campaign_index)
+    }
+
+    pub fn target(&self) -> u64 {
+        u64::from_le_bytes(self.target)
+    }
+
+    pub fn deadline(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.deadline)
+    }
+
+    pub fn allows_withdraw(&self) -> bool {
+        self.allows_withdraw != 0
+    }
+}
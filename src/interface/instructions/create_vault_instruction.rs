@@ -1,4 +1,6 @@
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use pinocchio::sysvars::clock::UnixTimestamp;
+
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
 
 #[repr(C)]
 pub struct CreateVaultInstructionData {
@@ -10,11 +12,14 @@ pub struct CreateVaultInstructionData {
     allows_transfers: u8,
     transfer_min_warmup: [u8; size_of::<u64>()],
     transfer_max_window: [u8; size_of::<u64>()],
+    withdraw_cooldown: [u8; size_of::<i64>()],
+    withdraw_warmup: [u8; size_of::<i64>()],
 }
 
 impl CreateVaultInstructionData {
     
-    pub fn new(vault_index: u64, timeframe: i64, max_transactions: u64, max_amount: u64, allows_transfers: bool, transfer_min_warmup: u64, transfer_max_window: u64,) -> Self{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(vault_index: u64, timeframe: i64, max_transactions: u64, max_amount: u64, allows_transfers: bool, transfer_min_warmup: u64, transfer_max_window: u64, withdraw_cooldown: i64, withdraw_warmup: i64) -> Self{
         Self { 
             discriminator: PimeInstruction::CreateVault as u8, 
             vault_index: vault_index.to_le_bytes(), 
@@ -24,6 +29,8 @@ impl CreateVaultInstructionData {
             allows_transfers: if allows_transfers { 1u8 } else { 0u8 },
             transfer_min_warmup: transfer_min_warmup.to_le_bytes(),
             transfer_max_window: transfer_max_window.to_le_bytes(),
+            withdraw_cooldown: withdraw_cooldown.to_le_bytes(),
+            withdraw_warmup: withdraw_warmup.to_le_bytes(),
         }
     }
 
@@ -42,6 +49,26 @@ impl CreateVaultInstructionData {
     pub fn max_amount(&self) -> u64 {
         u64::from_le_bytes(self.max_amount)
     }
+
+    pub fn allows_transfers(&self) -> bool {
+        self.allows_transfers != 0
+    }
+
+    pub fn transfer_min_warmup(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.transfer_min_warmup)
+    }
+
+    pub fn transfer_max_window(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.transfer_max_window)
+    }
+
+    pub fn withdraw_cooldown(&self) -> i64 {
+        i64::from_le_bytes(self.withdraw_cooldown)
+    }
+
+    pub fn withdraw_warmup(&self) -> i64 {
+        i64::from_le_bytes(self.withdraw_warmup)
+    }
 }
 
 /// # SAFETY : 
@@ -49,3 +76,9 @@ impl CreateVaultInstructionData {
 unsafe impl Transmutable for CreateVaultInstructionData {
     const LEN: usize = size_of::<Self>();
 }
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for CreateVaultInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::CreateVault;
+}
@@ -1,6 +1,6 @@
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{pubkey::Pubkey, sysvars::clock::UnixTimestamp};
 
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
 
 #[repr(C)]
 pub struct DepositToVaultInstructionData {
@@ -8,6 +8,8 @@ pub struct DepositToVaultInstructionData {
     vault_owner: Pubkey,
     vault_index: [u8; size_of::<u64>()],
     amount: [u8; size_of::<u64>()],
+    lot_index: [u8; size_of::<u64>()],
+    maturity: [u8; size_of::<UnixTimestamp>()],
 }
 
 /// # SAFETY : 
@@ -16,14 +18,28 @@ unsafe impl Transmutable for DepositToVaultInstructionData {
     const LEN: usize = size_of::<Self>();
 }
 
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for DepositToVaultInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::DepositToVault;
+}
+
 impl DepositToVaultInstructionData {
     
     pub fn new(vault_owner: Pubkey, vault_index: u64, amount: u64) -> Self{
+        Self::new_lot(vault_owner, vault_index, amount, 0, 0)
+    }
+
+    /// Deposit which is locked in a lot until its maturity.
+    /// A maturity of 0 does not create a lot.
+    pub fn new_lot(vault_owner: Pubkey, vault_index: u64, amount: u64, lot_index: u64, maturity: UnixTimestamp) -> Self{
         Self { 
             discriminator: PimeInstruction::DepositToVault as u8, 
             vault_owner, 
             vault_index: vault_index.to_le_bytes(), 
             amount: amount.to_le_bytes(),
+            lot_index: lot_index.to_le_bytes(),
+            maturity: maturity.to_le_bytes(),
         }
     }
 
@@ -39,5 +55,13 @@ impl DepositToVaultInstructionData {
         u64::from_le_bytes(self.amount)
     }
 
+    pub fn lot_index(&self) -> u64 {
+        u64::from_le_bytes(self.lot_index)
+    }
+
+    pub fn maturity(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.maturity)
+    }
+
 }
 
@@ -0,0 +1,45 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct DrawTransferInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    transfer_index: [u8; size_of::<u64>()],
+    amount: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for DrawTransferInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for DrawTransferInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::DrawTransfer;
+}
+
+impl DrawTransferInstructionData {
+    
+    pub fn new(vault_index: u64, transfer_index: u64, amount: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::DrawTransfer as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            transfer_index: transfer_index.to_le_bytes(),
+            amount: amount.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.transfer_index)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+}
@@ -1,10 +1,11 @@
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::{Transmutable, hash_lock::Preimage}};
 
 #[repr(C)]
 pub struct ExecuteTransferInstructionData {
     pub discriminator: u8,
     vault_index: [u8; size_of::<u64>()],
     transfer_index: [u8; size_of::<u64>()],
+    preimage: Preimage,
 }
 
 /// # SAFETY : 
@@ -13,6 +14,12 @@ unsafe impl Transmutable for ExecuteTransferInstructionData {
     const LEN: usize = size_of::<Self>();
 }
 
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for ExecuteTransferInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::ExecuteTransfer;
+}
+
 impl ExecuteTransferInstructionData{
     
     pub fn new(vault_index: u64, transfer_index: u64) -> Self{
@@ -20,9 +27,16 @@ impl ExecuteTransferInstructionData{
             discriminator: PimeInstruction::ExecuteTransfer as u8, 
             vault_index: vault_index.to_le_bytes(),
             transfer_index: transfer_index.to_le_bytes(),
+            preimage: Preimage::empty(),
         }
     }
 
+    /// Reveals the preimage of a hash-locked transfer's hash.
+    pub fn with_preimage(mut self, preimage: Preimage) -> Self {
+        self.preimage = preimage;
+        self
+    }
+
     pub fn vault_index(&self) -> u64 {
         u64::from_le_bytes(self.vault_index)
     }
@@ -30,5 +44,9 @@ impl ExecuteTransferInstructionData{
     pub fn transfer_index(&self) -> u64 {
         u64::from_le_bytes(self.transfer_index)
     }
+
+    pub fn preimage(&self) -> &Preimage {
+        &self.preimage
+    }
 }
 
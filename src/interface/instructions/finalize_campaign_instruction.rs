@@ -0,0 +1,33 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct FinalizeCampaignInstructionData {
+    pub discriminator: u8,
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for FinalizeCampaignInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for FinalizeCampaignInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::FinalizeCampaign;
+}
+
+impl FinalizeCampaignInstructionData {
+    
+    pub fn new() -> Self{
+        Self { 
+            discriminator: PimeInstruction::FinalizeCampaign as u8, 
+        }
+    }
+}
+
+impl Default for FinalizeCampaignInstructionData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,7 +2,25 @@ pub mod create_vault_instruction;
 pub mod deposit_to_vault_instruction;
 pub mod withdraw_from_vault;
 pub mod close_vault_instruction;
+pub mod migrate_account_instruction;
+pub mod release_lot_instruction;
+pub mod set_deposit_policy_instruction;
+pub mod set_depositor_instruction;
+pub mod return_deposit_instruction;
+pub mod set_vault_metadata_instruction;
+pub mod set_max_transactions_instruction;
 
 pub mod book_transfer;
 pub mod execute_transfer;
 pub mod unbook_transfer_instruction;
+pub mod crank_transfer_instruction;
+pub mod draw_transfer_instruction;
+pub mod amend_transfer_instruction;
+pub mod claim_transfer_instruction;
+pub mod settle_swap_instruction;
+
+pub mod create_campaign_instruction;
+pub mod pledge_to_campaign_instruction;
+pub mod withdraw_pledge_instruction;
+pub mod finalize_campaign_instruction;
+pub mod settle_pledge_instruction;
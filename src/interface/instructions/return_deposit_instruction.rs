@@ -0,0 +1,43 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct ReturnDepositInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    pub depositor: Pubkey,
+    amount: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for ReturnDepositInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for ReturnDepositInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::ReturnDeposit;
+}
+
+impl ReturnDepositInstructionData {
+    
+    pub fn new(vault_index: u64, depositor: Pubkey, amount: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::ReturnDeposit as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            depositor,
+            amount: amount.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::{DepositPolicy, Transmutable}};
+
+#[repr(C)]
+pub struct SetDepositPolicyInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    deposit_policy: u8,
+    min_deposit: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SetDepositPolicyInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SetDepositPolicyInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SetDepositPolicy;
+}
+
+impl SetDepositPolicyInstructionData {
+    
+    pub fn new(vault_index: u64, deposit_policy: DepositPolicy, min_deposit: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::SetDepositPolicy as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            deposit_policy: deposit_policy as u8,
+            min_deposit: min_deposit.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn deposit_policy(&self) -> u8 {
+        self.deposit_policy
+    }
+
+    pub fn min_deposit(&self) -> u64 {
+        u64::from_le_bytes(self.min_deposit)
+    }
+}
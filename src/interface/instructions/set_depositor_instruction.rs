@@ -0,0 +1,43 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct SetDepositorInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    pub depositor: Pubkey,
+    allowed: u8,
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SetDepositorInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SetDepositorInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SetDepositor;
+}
+
+impl SetDepositorInstructionData {
+    
+    pub fn new(vault_index: u64, depositor: Pubkey, allowed: bool) -> Self{
+        Self { 
+            discriminator: PimeInstruction::SetDepositor as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            depositor,
+            allowed: if allowed { 1u8 } else { 0u8 },
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn allowed(&self) -> bool {
+        self.allowed != 0
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct SetMaxTransactionsInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    max_transactions: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SetMaxTransactionsInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SetMaxTransactionsInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SetMaxTransactions;
+}
+
+impl SetMaxTransactionsInstructionData {
+    
+    pub fn new(vault_index: u64, max_transactions: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::SetMaxTransactions as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            max_transactions: max_transactions.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn max_transactions(&self) -> u64 {
+        u64::from_le_bytes(self.max_transactions)
+    }
+}
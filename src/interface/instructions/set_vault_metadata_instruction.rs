@@ -0,0 +1,72 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::{Transmutable, metadata_data::MetadataData}};
+
+#[repr(C)]
+pub struct SetVaultMetadataInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    category: u8,
+    name_len: u8,
+    name: [u8; MetadataData::MAX_NAME_LEN],
+    description_len: [u8; size_of::<u16>()],
+    description: [u8; MetadataData::MAX_DESCRIPTION_LEN],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SetVaultMetadataInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SetVaultMetadataInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SetVaultMetadata;
+}
+
+impl SetVaultMetadataInstructionData {
+    
+    /// Name and description are truncated to `MetadataData::MAX_NAME_LEN` and `MetadataData::MAX_DESCRIPTION_LEN` bytes.
+    pub fn new(vault_index: u64, category: u8, name: &str, description: &str) -> Self{
+        let name_len = name.len().min(MetadataData::MAX_NAME_LEN);
+        let description_len = description.len().min(MetadataData::MAX_DESCRIPTION_LEN);
+        let mut name_bytes = [0u8; MetadataData::MAX_NAME_LEN];
+        name_bytes[..name_len].copy_from_slice(&name.as_bytes()[..name_len]);
+        let mut description_bytes = [0u8; MetadataData::MAX_DESCRIPTION_LEN];
+        description_bytes[..description_len].copy_from_slice(&description.as_bytes()[..description_len]);
+        Self { 
+            discriminator: PimeInstruction::SetVaultMetadata as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            category,
+            name_len: name_len as u8,
+            name: name_bytes,
+            description_len: (description_len as u16).to_le_bytes(),
+            description: description_bytes,
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn category(&self) -> u8 {
+        self.category
+    }
+
+    /// Length of the name, as sent. May exceed `MetadataData::MAX_NAME_LEN`.
+    pub fn name_len(&self) -> usize {
+        self.name_len as usize
+    }
+
+    /// Length of the description, as sent. May exceed `MetadataData::MAX_DESCRIPTION_LEN`.
+    pub fn description_len(&self) -> usize {
+        u16::from_le_bytes(self.description_len) as usize
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..(self.name_len as usize).min(MetadataData::MAX_NAME_LEN)]
+    }
+
+    pub fn description(&self) -> &[u8] {
+        &self.description[..(u16::from_le_bytes(self.description_len) as usize).min(MetadataData::MAX_DESCRIPTION_LEN)]
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct SettlePledgeInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SettlePledgeInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SettlePledgeInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SettlePledge;
+}
+
+impl SettlePledgeInstructionData {
+    
+    pub fn new(vault_index: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::SettlePledge as u8, 
+            vault_index: vault_index.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+}
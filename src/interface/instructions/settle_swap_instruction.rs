@@ -0,0 +1,51 @@
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
+
+#[repr(C)]
+pub struct SettleSwapInstructionData {
+    pub discriminator: u8,
+    vault_index: [u8; size_of::<u64>()],
+    transfer_index: [u8; size_of::<u64>()],
+    counterpart_vault_index: [u8; size_of::<u64>()],
+    counterpart_transfer_index: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY : 
+/// All fields are of u8 and therefore without padding.
+unsafe impl Transmutable for SettleSwapInstructionData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for SettleSwapInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::SettleSwap;
+}
+
+impl SettleSwapInstructionData {
+    
+    pub fn new(vault_index: u64, transfer_index: u64, counterpart_vault_index: u64, counterpart_transfer_index: u64) -> Self{
+        Self { 
+            discriminator: PimeInstruction::SettleSwap as u8, 
+            vault_index: vault_index.to_le_bytes(),
+            transfer_index: transfer_index.to_le_bytes(),
+            counterpart_vault_index: counterpart_vault_index.to_le_bytes(),
+            counterpart_transfer_index: counterpart_transfer_index.to_le_bytes(),
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.transfer_index)
+    }
+
+    pub fn counterpart_vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.counterpart_vault_index)
+    }
+
+    pub fn counterpart_transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.counterpart_transfer_index)
+    }
+}
@@ -1,6 +1,6 @@
 use pinocchio::pubkey::Pubkey;
 
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::Transmutable};
 
 #[repr(C)]
 pub struct UnbookTransferInstructionData {
@@ -16,6 +16,12 @@ unsafe impl Transmutable for UnbookTransferInstructionData {
     const LEN: usize = size_of::<Self>();
 }
 
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for UnbookTransferInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::UnbookTransfer;
+}
+
 impl UnbookTransferInstructionData {
     pub fn new(vault_index: u64, transfer_index: u64, destination: Pubkey) -> Self{
         Self { 
@@ -1,10 +1,11 @@
-use crate::{interface::pime_instruction::PimeInstruction, states::Transmutable};
+use crate::{interface::{parser::InstructionData, pime_instruction::PimeInstruction}, states::{Transmutable, memo::Memo}};
 
 #[repr(C)]
 pub struct WithdrawFromVaultInstructionData {
     pub discriminator: u8,
     vault_index: [u8; size_of::<u64>()],
     amount: [u8; size_of::<u64>()],
+    memo: Memo,
 }
 
 /// # SAFETY : 
@@ -13,6 +14,12 @@ unsafe impl Transmutable for WithdrawFromVaultInstructionData {
     const LEN: usize = size_of::<Self>();
 }
 
+/// # SAFETY : 
+/// Starts with its discriminator, and all fields are of u8.
+unsafe impl InstructionData for WithdrawFromVaultInstructionData {
+    const INSTRUCTION: PimeInstruction = PimeInstruction::WithdrawFromVault;
+}
+
 impl WithdrawFromVaultInstructionData {
     
     pub fn new(amount: u64, vault_index: u64) -> Self{
@@ -20,9 +27,16 @@ impl WithdrawFromVaultInstructionData {
             discriminator: PimeInstruction::WithdrawFromVault as u8, 
             vault_index: vault_index.to_le_bytes(),
             amount: amount.to_le_bytes(),
+            memo: Memo::empty(),
         }
     }
 
+    /// Attaches the memo to the withdraw's transaction.
+    pub fn with_memo(mut self, memo: Memo) -> Self {
+        self.memo = memo;
+        self
+    }
+
     pub fn vault_index(&self) -> u64 {
         u64::from_le_bytes(self.vault_index)
     }
@@ -30,5 +44,9 @@ impl WithdrawFromVaultInstructionData {
     pub fn amount(&self) -> u64 {
         u64::from_le_bytes(self.amount)
     }
+
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
 }
 
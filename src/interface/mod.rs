@@ -1,2 +1,3 @@
 pub mod pime_instruction;
 pub mod instructions;
+pub mod parser;
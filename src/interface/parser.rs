@@ -0,0 +1,35 @@
+use pinocchio::program_error::ProgramError;
+
+use crate::{errors::PimeError, interface::pime_instruction::PimeInstruction, states::{Transmutable, as_bytes}};
+
+/// Instruction data with a fixed layout, sent as is by clients and read in place by the program.
+///
+/// # SAFETY
+/// The data must start with its `u8` discriminator, and only contain `u8` based fields so that it
+/// has an alignment of 1.
+pub unsafe trait InstructionData: Transmutable + Sized {
+    /// The instruction carrying this data.
+    const INSTRUCTION: PimeInstruction;
+
+    /// The data as sent in an instruction, discriminator included.
+    fn to_bytes(&self) -> &[u8] {
+        as_bytes(self)
+    }
+}
+
+/// Reads instruction data, discriminator included, as `T` without copying it.
+///
+/// The data has to be exactly `T::LEN` long, so that short and trailing data are rejected alike,
+/// and carry `T`'s discriminator.
+pub fn parse<T: InstructionData>(data: &[u8]) -> Result<&T, ProgramError> {
+    if data.len() != T::LEN {
+        msg!("Instruction data has an incorrect length.");
+        return Err(PimeError::InvalidInstructionDataLength.into());
+    }
+    if data[0] != T::INSTRUCTION as u8 {
+        msg!("Instruction data discriminator mismatch.");
+        return Err(PimeError::InstructionMismatch.into());
+    }
+    // SAFETY: The data is T::LEN long, and T has an alignment of 1.
+    Ok(unsafe { &*(data.as_ptr() as *const T) })
+}
@@ -1,7 +1,10 @@
 use pinocchio::program_error::ProgramError;
 
+use crate::errors::PimeError;
+
 
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PimeInstruction {
 
     /// Creates a new vault.
@@ -15,6 +18,7 @@ pub enum PimeInstruction {
     ///   3. `[]`           The mint address of the vault. 
     ///   4. `[]`           The token program. 
     ///   5. `[]`           The system program. 
+    ///   6. `[writeable]`  The authority's registry account.
     ///
     /// Data expected by this instruction:
     ///
@@ -22,6 +26,11 @@ pub enum PimeInstruction {
     ///   - `u64` The timeframe, in ms, which the vault's restriction encompasses.
     ///   - `u64` The number of withdraws allowed within a timeframe.
     ///   - `u64` The number of lamports allowed to be withdrawn within a timeframe.
+    ///   - `u8`  If the vault allows booked transfers.
+    ///   - `UnixTimestamp` The minimum warm-up of a booked transfer.
+    ///   - `UnixTimestamp` The maximum validity window of a booked transfer.
+    ///   - `UnixTimestamp` The cool-down between two consecutive withdraws.
+    ///   - `UnixTimestamp` The warm-up before the first withdraw of a newly created or funded vault.
     CreateVault = 0,
 
     /// Deposit tokens to a vault
@@ -31,36 +40,48 @@ pub enum PimeInstruction {
     ///
     ///   0. `[signer]`     The signer, and authority of the token account. 
     ///   1. `[writeable]`  The token account which the tokens will be withdrawn from
-    ///   1. `[writeable]`  The vault account.
-    ///   2. `[]`           The mint address of the vault. 
-    ///   3. `[]`           The token program. 
-    ///   4. `[]`           The system program. (Optional, if vault needs to be initialized) 
+    ///   2. `[writeable]`  The vault data account.
+    ///   3. `[writeable]`  The vault account.
+    ///   4. `[]`           The mint address of the vault. 
+    ///   5. `[]`           The token program. 
+    ///   6. `[]`           The system program. (Optional, if a lot or depositor needs to be initialized) 
+    ///   7. `[writeable]`  The lot account. (Optional, if the deposit is locked in a lot) 
+    ///   8. `[writeable]`  The depositor account. (Optional, if the signer is not the vault owner) 
     ///
     /// Data expected by this instruction:
     ///
     ///   - `[u8; 32]`  The vault owners' public key.
     ///   - `u64`       The vault's vault's index.
     ///   - `u64`       The amount to transfer in lamports (without decimals).
+    ///   - `u64`       The lot index.
+    ///   - `UnixTimestamp` The lot's maturity. 0 if the deposit is not locked in a lot.
     DepositToVault = 1,
 
-    /// Withdraw tokens to a vault
+    /// Withdraw tokens from a vault to a token account.
     ///
     ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[signer]`     The owner of the vault.
     ///   1. `[writeable]`  The vault data account.
-    ///   1. `[writeable]`  The vault account.
-    ///   2. `[]`           The mint address of the vault. 
-    ///   3. `[]`           The token program. 
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The token account to withdraw to.
+    ///   4. `[]`           The mint address of the vault. 
+    ///   5. `[]`           The token program. 
+    ///
+    ///   Remaining accounts, in any order:
+    ///   6. `[]`           (Optional) The memo program. (If a memo is given)
+    ///   7. `[]`           (Optional) Reference accounts, in the style of Solana Pay.
     ///
     /// Data expected by this instruction:
     ///
+    ///   - `u64`       The vault's index
     ///   - `u64`       The amount to withdraw in lamports (without decimals).
+    ///   - `Memo`      Memo attached to the transaction through the memo program. (Empty for none)
     WithdrawFromVault = 2,
 
     /// Close vault
-    ///
+    /// Only once the vault is empty, and has no open transfers or pledges.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -69,13 +90,133 @@ pub enum PimeInstruction {
     ///   1. `[writeable]`  The vault account.
     ///   2. `[]`           The mint address of the vault. 
     ///   3. `[]`           The token program. 
+    ///   5. `[writeable]`  The authority's registry account.
+    ///   6. `[writeable]`  The metadata account. Only required if the vault has metadata.
     ///
     /// Data expected by this instruction:
     ///
     ///   - `u64`       The vault's index
     CloseVault = 3,
 
-    /// Book a transfer.
+    /// Release a matured lot, closing it and returning its rent to its payer.
+    /// A lot's amount can be withdrawn once it has matured, whether or not it is released.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The lot account.
+    ///   3. `[writeable]`  The lot's rent payer.
+    ///   4. `[]`           The mint address of the vault. 
+    ///   5. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault's index
+    ///   - `u64`       The lot's index
+    ReleaseLot = 4,
+
+    /// Set who may deposit into a vault, and the minimum deposit.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[]`           The mint address of the vault. 
+    ///   3. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault's index
+    ///   - `u8`        The deposit policy. 0: Anyone, 1: Authority only, 2: Allowlist.
+    ///   - `u64`       The minimum deposit (without decimals).
+    SetDepositPolicy = 5,
+
+    /// Allow, or disallow, a depositor to deposit into a vault with an allowlist deposit policy.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[]`           The vault data account.
+    ///   2. `[writeable]`  The depositor account.
+    ///   3. `[]`           The mint address of the vault. 
+    ///   4. `[]`           The token program. 
+    ///   5. `[]`           The system program. (Optional, if the depositor needs to be initialized) 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault's index
+    ///   - `Pubkey`    The depositor.
+    ///   - `u8`        If the depositor is allowed.
+    SetDepositor = 6,
+
+    /// Return assets deposited by a third party back to it.
+    /// Can not return more than the depositor has deposited.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[]`           The vault data account.
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The depositor account.
+    ///   4. `[writeable]`  The depositor's token account.
+    ///   5. `[]`           The mint address of the vault. 
+    ///   6. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault's index
+    ///   - `Pubkey`    The depositor.
+    ///   - `u64`       The amount to return (without decimals).
+    ReturnDeposit = 7,
+
+    /// Set, or update, a vault's name, description and category.
+    /// Creates the vault's metadata account if it does not exist, otherwise resizes it to fit.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The metadata account.
+    ///   3. `[]`           The mint address of the vault. 
+    ///   4. `[]`           The token program. 
+    ///   5. `[]`           The system program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u8`        The category.
+    ///   - `u8`        The length of the name.
+    ///   - `[u8; 32]`  The UTF-8 name.
+    ///   - `u16`       The length of the description.
+    ///   - `[u8; 256]` The UTF-8 description.
+    SetVaultMetadata = 8,
+
+    /// Migrate an outdated vault data account to the current layout, in place.
+    /// Withdraws still within the vault's timeframe are carried over. The authority pays for, or
+    /// is refunded, the difference in rent.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writeable]` The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[]`           The mint address of the vault. 
+    ///   3. `[]`           The token program. 
+    ///   4. `[]`           The system program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    MigrateAccount = 9,
+
+    /// Book a transfer. It is assigned the vault's next transfer index, from which its transfer and
+    /// deposit accounts are derived.
     ///
     ///
     /// Accounts expected by this instruction:
@@ -88,23 +229,36 @@ pub enum PimeInstruction {
     ///   4. `[]`           The mint address of the vault/transfer. 
     ///   5. `[]`           The token program. 
     ///   6. `[]`           The system program. 
+    ///   7. `[]`           (Optional) Reference accounts, in the style of Solana Pay.
     ///
     /// Data expected by this instruction:
     ///
     ///   - `u64`       The amount to transfer (without decimals).
     ///   - `Pubkey`    Destination account.
     ///   - `u64`       The vault index.
-    ///   - `u64`       The transfer index.
     ///   - `UnixTimestamp` Warmup period
     ///   - `UnixTimestamp` Validity period
+    ///   - `u8`        If anyone may crank the transfer. See `CrankTransfer`.
+    ///   - `u64`       The crank tip, in lamports, paid by the authority on top of the rent.
+    ///   - `u8`        If the transfer may be drawn in several partial executions. See `DrawTransfer`.
+    ///   - `UnixTimestamp` Period after the warm-up over which the amount is released linearly. (0 releases it at once)
+    ///   - `Memo`      Memo stored with the transfer, and attached when it is executed. (Empty for none)
+    ///   - `u8`        If the destination is an ephemeral claim key. See `ClaimTransfer`.
+    ///   - `u8`        The hash lock's hash function. (0 none, 1 SHA-256, 2 Keccak-256)
+    ///   - `[u8; 32]`  The hash lock's hash.
+    ///   - `Pubkey`    The counterpart transfer, if the transfer is one side of a swap. See `SettleSwap`.
+    ///   - `u64`       The amount the counterpart transfer pays in return.
     BookTransfer = 10,
 
     /// Execute a transfer.
+    /// Partially drawable transfers draw what has been released so far, and are closed once fully drawn.
+    /// Hash-locked transfers are executed by anyone revealing their preimage, without the authority's signature.
+    /// Swaps can only be settled together with their counterpart, with `SettleSwap`.
     ///
     ///
     /// Accounts expected by this instruction:
     ///
-    ///   0. `[signer]`     The authority of the vault.
+    ///   0. `[signer]`     The authority of the vault. (Only writeable for hash-locked transfers)
     ///   1. `[writeable]`  The vault account.
     ///   2. `[writeable]`  The transfer account.
     ///   3. `[writeable]`  The deposit account.
@@ -114,14 +268,17 @@ pub enum PimeInstruction {
     ///   6. `[]`           (Optional) The system program. (If destination ATA needs to be created)
     ///   6. `[]`           (Optional) The destination ATA's owner/authority account. (If destination ATA needs to be created)
     ///   7. `[]`           (Optional) The ATA program. (If destination ATA needs to be created)
+    ///   8. `[]`           (Optional) The memo program. (If the transfer has a memo)
     ///
     /// Data expected by this instruction:
     ///
     ///   - `u64`       The vault index.
     ///   - `u64`       The transfer index.
+    ///   - `Preimage`  The preimage of the hash lock's hash, of up to 64 bytes. (Ignored if the transfer is not hash-locked)
     ExecuteTransfer = 11,
 
-    /// Unbook a transfer.
+    /// Unbook a transfer, refunding its undrawn remainder to the vault.
+    /// Hash-locked transfers can only be unbooked once expired.
     ///
     ///
     /// Accounts expected by this instruction:
@@ -138,18 +295,294 @@ pub enum PimeInstruction {
     ///
     ///   - `u64`       The vault index.
     ///   - `u64`       The transfer index.
+    ///   - `Pubkey`    The destination the transfer was booked with.
     ///
     UnbookTransfer = 12,
+
+    /// Change the number of withdraws a vault allows within its timeframe.
+    /// Lowering it applies right away. Raising it only applies once the vault's timeframe has
+    /// passed, and is replaced by any later change.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[]`           The mint address of the vault. 
+    ///   3. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The number of withdraws allowed within a timeframe.
+    SetMaxTransactions = 13,
+
+    /// Crank a transfer which allows it, without the authority's signature.
+    /// Within its validity the released assets are executed, and once expired the remainder is refunded to the vault.
+    /// When the transfer closes, the rent goes back to the authority, and the crank is paid the transfer's crank tip.
+    /// Expired hash-locked transfers are refunded by anyone, even without allowing the crank.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writeable]` The crank.
+    ///   1. `[writeable]`  The authority of the vault.
+    ///   2. `[writeable]`  The vault data account.
+    ///   3. `[writeable]`  The vault account.
+    ///   4. `[writeable]`  The transfer account.
+    ///   5. `[writeable]`  The deposit account.
+    ///   6. `[writeable]`  The destination account. (Has to exist)
+    ///   7. `[]`           The mint address of the vault/transfer. 
+    ///   8. `[]`           The token program. 
+    ///   9. `[]`           (Optional) The memo program. (If the transfer has a memo and is executed)
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The transfer index.
+    CrankTransfer = 14,
+
+    /// Draw part of a partially drawable transfer, signed by the owner of its destination.
+    /// The transfer is closed once fully drawn, and its rent goes back to the authority.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The owner of the destination account.
+    ///   1. `[writeable]`  The authority of the vault.
+    ///   2. `[writeable]`  The vault data account.
+    ///   3. `[writeable]`  The transfer account.
+    ///   4. `[writeable]`  The deposit account.
+    ///   5. `[writeable]`  The destination account.
+    ///   6. `[]`           The mint address of the vault/transfer. 
+    ///   7. `[]`           The token program. 
+    ///   8. `[]`           (Optional) The memo program. (If the transfer has a memo)
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The transfer index.
+    ///   - `u64`       The amount to draw (without decimals).
+    DrawTransfer = 15,
+
+    /// Amend the amount, destination or validity of a booked transfer.
+    /// Lowering the amount or shortening the validity applies right away, returning funds to the vault.
+    /// Raising the amount, changing the destination or extending the validity restarts the warm-up
+    /// from the amendment, for the vault's transfer min warm-up.
+    /// Hash-locked transfers and swaps can not be amended.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The authority of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The transfer account.
+    ///   4. `[writeable]`  The deposit account.
+    ///   5. `[]`           The mint address of the vault/transfer. 
+    ///   6. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The transfer index.
+    ///   - `u64`       The amended amount (without decimals).
+    ///   - `Pubkey`    The amended destination.
+    ///   - `UnixTimestamp` The amended validity period, from when the transfer was booked.
+    AmendTransfer = 16,
+
+    /// Claim a claim-link transfer, as the holder of its ephemeral claim key, to a token account of
+    /// the claimer's choosing. The destination ATA is created if it does not exist.
+    /// Claims what has been released so far, and closes the transfer once fully claimed.
+    /// Unclaimed transfers are refunded with `UnbookTransfer`, or `CrankTransfer` once expired.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]`     The claim key, the transfer's booked destination.
+    ///   1. `[signer, writeable]` The payer of the destination ATA, if it has to be created.
+    ///   2. `[writeable]`  The authority of the vault, refunded the rent once the transfer closes.
+    ///   3. `[writeable]`  The vault data account.
+    ///   4. `[writeable]`  The transfer account.
+    ///   5. `[writeable]`  The deposit account.
+    ///   6. `[writeable]`  The destination token account.
+    ///   7. `[]`           The mint address of the vault/transfer. 
+    ///   8. `[]`           The token program. 
+    ///   9. `[]`           (Optional) The system program. (If destination ATA needs to be created)
+    ///  10. `[]`           (Optional) The destination ATA's owner/authority account. (If destination ATA needs to be created)
+    ///  11. `[]`           (Optional) The ATA program. (If destination ATA needs to be created)
+    ///  12. `[]`           (Optional) The memo program. (If the transfer has a memo)
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The transfer index.
+    ClaimTransfer = 17,
+
+    /// Settle both sides of a swap at once, once both have warmed up and neither has expired.
+    /// Each side is a transfer booked with the other as its counterpart, and the amount the other pays.
+    /// Either side cancels by unbooking its transfer before settlement. Anyone may settle.
+    /// Both transfers are closed, and their rent goes back to their authorities.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]`  The authority of the first vault.
+    ///   1. `[writeable]`  The first vault data account.
+    ///   2. `[writeable]`  The first transfer account.
+    ///   3. `[writeable]`  The first deposit account.
+    ///   4. `[writeable]`  The first transfer's destination account. (Has to exist)
+    ///   5. `[]`           The mint address of the first vault/transfer. 
+    ///   6. `[]`           The token program of the first vault/transfer. 
+    ///   7. `[writeable]`  The authority of the counterpart vault.
+    ///   8. `[writeable]`  The counterpart vault data account.
+    ///   9. `[writeable]`  The counterpart transfer account.
+    ///  10. `[writeable]`  The counterpart deposit account.
+    ///  11. `[writeable]`  The counterpart transfer's destination account. (Has to exist)
+    ///  12. `[]`           The mint address of the counterpart vault/transfer. 
+    ///  13. `[]`           The token program of the counterpart vault/transfer. 
+    ///  14. `[]`           (Optional) The memo program. (If either transfer has a memo)
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The first vault index.
+    ///   - `u64`       The first transfer index.
+    ///   - `u64`       The counterpart vault index.
+    ///   - `u64`       The counterpart transfer index.
+    SettleSwap = 18,
+
+    /// Create a crowdfunding campaign, raising a target amount of a mint for a recipient by a deadline.
+    /// Its escrow token account is created along with it.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writeable]` The creator of the campaign.
+    ///   1. `[writeable]`  The campaign account.
+    ///   2. `[writeable]`  The campaign's escrow account.
+    ///   3. `[]`           The recipient token account. (Has to exist)
+    ///   4. `[]`           The mint address of the campaign. 
+    ///   5. `[]`           The token program. 
+    ///   6. `[]`           The system program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The campaign index.
+    ///   - `u64`       The target amount (without decimals).
+    ///   - `UnixTimestamp` The deadline.
+    ///   - `u8`        If pledgers may withdraw their pledges before the deadline.
+    CreateCampaign = 19,
+
+    /// Pledge from a vault to a campaign before its deadline, moving the amount into the campaign's escrow.
+    /// A vault holds at most one pledge to a campaign at a time. The pledge counts as an open
+    /// transfer of the vault until it is withdrawn or settled, so the vault can not be closed meanwhile.
+    /// The vault must allow transfers, the deadline must be at least the vault's min warm-up away,
+    /// and the pledge counts against the vault's withdraw limits.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writeable]` The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The campaign account.
+    ///   4. `[writeable]`  The campaign's escrow account.
+    ///   5. `[writeable]`  The pledge account.
+    ///   6. `[]`           The mint address of the vault/campaign. 
+    ///   7. `[]`           The token program. 
+    ///   8. `[]`           The system program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    ///   - `u64`       The amount to pledge (without decimals).
+    PledgeToCampaign = 20,
+
+    /// Withdraw a pledge back into its vault before the campaign's deadline, if the campaign allows it.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writeable]` The owner of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The campaign account.
+    ///   4. `[writeable]`  The campaign's escrow account.
+    ///   5. `[writeable]`  The pledge account.
+    ///   6. `[]`           The mint address of the vault/campaign. 
+    ///   7. `[]`           The token program. 
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    WithdrawPledge = 21,
+
+    /// Finalize a campaign whose pledges reached its target by the deadline, releasing its escrow to the recipient.
+    /// Anyone may finalize.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]`  The campaign account.
+    ///   1. `[writeable]`  The campaign's escrow account.
+    ///   2. `[writeable]`  The recipient token account.
+    ///   3. `[]`           The mint address of the campaign. 
+    ///   4. `[]`           The token program. 
+    FinalizeCampaign = 22,
+
+    /// Settle a pledge after the campaign's deadline, without the pledger's signature.
+    /// Pledges to a finalized campaign are recorded as executed, and pledges to a campaign which missed
+    /// its target are refunded into their vault. The pledge's rent goes back to the vault's authority.
+    /// Settling the last pledge closes the campaign and its escrow, and their rent goes back to the creator.
+    ///
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]`  The authority of the vault.
+    ///   1. `[writeable]`  The vault data account.
+    ///   2. `[writeable]`  The vault account.
+    ///   3. `[writeable]`  The campaign account.
+    ///   4. `[writeable]`  The campaign's escrow account.
+    ///   5. `[writeable]`  The pledge account.
+    ///   6. `[]`           The mint address of the vault/campaign. 
+    ///   7. `[]`           The token program. 
+    ///   8. `[writeable]`  The campaign's creator.
+    ///
+    /// Data expected by this instruction:
+    ///
+    ///   - `u64`       The vault index.
+    SettlePledge = 23,
 }
 
 impl TryFrom<u8> for PimeInstruction {
     type Error = ProgramError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value { 
-            0..=6 => Ok(unsafe { core::mem::transmute::<u8, PimeInstruction>(value) }),
-            _ => Err(ProgramError::InvalidInstructionData)
+            0 => Ok(PimeInstruction::CreateVault),
+            1 => Ok(PimeInstruction::DepositToVault),
+            2 => Ok(PimeInstruction::WithdrawFromVault),
+            3 => Ok(PimeInstruction::CloseVault),
+            4 => Ok(PimeInstruction::ReleaseLot),
+            5 => Ok(PimeInstruction::SetDepositPolicy),
+            6 => Ok(PimeInstruction::SetDepositor),
+            7 => Ok(PimeInstruction::ReturnDeposit),
+            8 => Ok(PimeInstruction::SetVaultMetadata),
+            9 => Ok(PimeInstruction::MigrateAccount),
+            10 => Ok(PimeInstruction::BookTransfer),
+            11 => Ok(PimeInstruction::ExecuteTransfer),
+            12 => Ok(PimeInstruction::UnbookTransfer),
+            13 => Ok(PimeInstruction::SetMaxTransactions),
+            14 => Ok(PimeInstruction::CrankTransfer),
+            15 => Ok(PimeInstruction::DrawTransfer),
+            16 => Ok(PimeInstruction::AmendTransfer),
+            17 => Ok(PimeInstruction::ClaimTransfer),
+            18 => Ok(PimeInstruction::SettleSwap),
+            19 => Ok(PimeInstruction::CreateCampaign),
+            20 => Ok(PimeInstruction::PledgeToCampaign),
+            21 => Ok(PimeInstruction::WithdrawPledge),
+            22 => Ok(PimeInstruction::FinalizeCampaign),
+            23 => Ok(PimeInstruction::SettlePledge),
+            _ => Err(PimeError::UnknownInstruction.into())
         }
     }
 }
-
-// TODO! Perform exhaustive conversion tests.
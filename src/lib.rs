@@ -1,10 +1,21 @@
 #![no_std]
 
 use pinocchio::{
-  ProgramResult, account_info::AccountInfo, default_allocator, hint::unlikely, msg, nostd_panic_handler, program_entrypoint, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}
+  ProgramResult, account_info::AccountInfo, default_allocator, hint::unlikely, nostd_panic_handler, program_entrypoint, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}
 };
 use pinocchio_pubkey::declare_id;
 
+use crate::{errors::PimeError, interface::{parser::parse, pime_instruction::PimeInstruction}};
+
+/// Forwards to `pinocchio::msg!` when the `logging` feature is enabled,
+/// and compiles to nothing otherwise.
+macro_rules! msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "logging")]
+        pinocchio::msg!($($arg)*);
+    };
+}
+
 pub mod interface;
 mod processors;
 pub mod states;
@@ -24,41 +35,107 @@ pub fn process_instruction(
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    let [inst, data @ ..] = instruction_data else {
-        return Err(ProgramError::InvalidInstructionData);
+    let [discriminator, ..] = instruction_data else {
+        return Err(PimeError::InvalidInstructionDataLength.into());
     };
 
-    match *inst {
-        0 => {
+    match PimeInstruction::try_from(*discriminator)? {
+        PimeInstruction::CreateVault => {
             msg!("Create vault");
-            processors::create_vault::process_create_vault(accounts, data)?
+            processors::create_vault::process_create_vault(accounts, parse(instruction_data)?)?
         },
-        1 => {
+        PimeInstruction::DepositToVault => {
             msg!("Deposit");
-            processors::deposit_to_vault::process_deposit_to_vault(accounts, data)?
+            processors::deposit_to_vault::process_deposit_to_vault(accounts, parse(instruction_data)?)?
         },
-        2 => {
+        PimeInstruction::WithdrawFromVault => {
             msg!("Withdraw");
-            processors::withdraw_from_vault::process_withdraw_from_vault(accounts, data)?
+            processors::withdraw_from_vault::process_withdraw_from_vault(accounts, parse(instruction_data)?)?
         },
-        3 => {
+        PimeInstruction::CloseVault => {
             msg!("Close");
-            processors::close_vault::process_close_vault(accounts, data)?
+            processors::close_vault::process_close_vault(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::ReleaseLot => {
+            msg!("Release lot");
+            processors::release_lot::process_release_lot(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::SetDepositPolicy => {
+            msg!("Set deposit policy");
+            processors::set_deposit_policy::process_set_deposit_policy(accounts, parse(instruction_data)?)?
         },
-        10 => {
+        PimeInstruction::SetDepositor => {
+            msg!("Set depositor");
+            processors::set_depositor::process_set_depositor(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::ReturnDeposit => {
+            msg!("Return deposit");
+            processors::return_deposit::process_return_deposit(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::SetVaultMetadata => {
+            msg!("Set vault metadata");
+            processors::set_vault_metadata::process_set_vault_metadata(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::MigrateAccount => {
+            msg!("Migrate account");
+            processors::migrate_account::process_migrate_account(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::BookTransfer => {
             msg!("Book transfer");
-            processors::transfer::book_transfer::process_book_transfer(accounts, data)?
+            processors::transfer::book_transfer::process_book_transfer(accounts, parse(instruction_data)?)?
         },
-       11 => {
+        PimeInstruction::ExecuteTransfer => {
             msg!("Execute transfer");
-            processors::transfer::execute_transfer::execute_transfer(accounts, data)?
+            processors::transfer::execute_transfer::execute_transfer(accounts, parse(instruction_data)?)?
         },
-       12 => {
+        PimeInstruction::UnbookTransfer => {
             msg!("Unbook transfer");
-            processors::transfer::unbook_transfer::unbook_transfer(accounts, data)?
+            processors::transfer::unbook_transfer::unbook_transfer(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::SetMaxTransactions => {
+            msg!("Set max transactions");
+            processors::set_max_transactions::process_set_max_transactions(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::CrankTransfer => {
+            msg!("Crank transfer");
+            processors::transfer::crank_transfer::crank_transfer(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::DrawTransfer => {
+            msg!("Draw transfer");
+            processors::transfer::draw_transfer::draw_transfer(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::AmendTransfer => {
+            msg!("Amend transfer");
+            processors::transfer::amend_transfer::amend_transfer(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::ClaimTransfer => {
+            msg!("Claim transfer");
+            processors::transfer::claim_transfer::claim_transfer(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::SettleSwap => {
+            msg!("Settle swap");
+            processors::transfer::settle_swap::settle_swap(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::CreateCampaign => {
+            msg!("Create campaign");
+            processors::campaign::create_campaign::create_campaign(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::PledgeToCampaign => {
+            msg!("Pledge to campaign");
+            processors::campaign::pledge_to_campaign::pledge_to_campaign(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::WithdrawPledge => {
+            msg!("Withdraw pledge");
+            processors::campaign::withdraw_pledge::withdraw_pledge(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::FinalizeCampaign => {
+            msg!("Finalize campaign");
+            processors::campaign::finalize_campaign::finalize_campaign(accounts, parse(instruction_data)?)?
+        },
+        PimeInstruction::SettlePledge => {
+            msg!("Settle pledge");
+            processors::campaign::settle_pledge::settle_pledge(accounts, parse(instruction_data)?)?
         },
-        _ => {return Err(ProgramError::InvalidInstructionData);}
-        
     }
 
   Ok(())
@@ -0,0 +1,83 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
+
+use crate::{errors::PimeError, interface::instructions::create_campaign_instruction::CreateCampaignInstructionData, processors::shared::{constraints::check, create_account::create_pda_account, create_deposit_account::create_deposit_account}, states::{Transmutable, as_bytes, campaign_data::CampaignData}};
+
+/// Creates a crowdfunding campaign, along with the escrow its pledges are held in.
+pub fn create_campaign(accounts: &[AccountInfo], data: &CreateCampaignInstructionData) -> ProgramResult {
+
+    let campaign_index = data.campaign_index();
+    let target = data.target();
+    let deadline = data.deadline();
+
+    if target == 0 || deadline <= Clock::get()?.unix_timestamp {
+        msg!("Campaign needs a target and a future deadline.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+
+    let [creator, campaign, escrow, recipient, mint, token_program, _system_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(creator, "Creator").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    // The escrow is released into the recipient, which has to hold the campaign's mint.
+    check(recipient, "Recipient").owned_by(token_program.key())?;
+    if !pubkey_eq(TokenAccount::from_account_info(recipient)?.mint(), mint.key()) {
+        msg!("Recipient does not hold the campaign's mint.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+
+    let campaign_pda = CampaignData::get_campaign_pda(creator.key(), campaign_index, mint.key(), token_program.key());
+    check(campaign, "Campaign").address(&campaign_pda.0)?.uninitialized()?.writable()?;
+
+    let escrow_pda = CampaignData::get_escrow_pda(campaign.key());
+    check(escrow, "Escrow").address(&escrow_pda.0)?.uninitialized()?.writable()?;
+
+    //      BUSINESS LOGIC
+
+    let campaign_index_bytes = campaign_index.to_le_bytes();
+    let campaign_bump = &[campaign_pda.1];
+    let campaign_signer_seeds = CampaignData::get_campaign_signer_seeds(
+        creator.key(),
+        &campaign_index_bytes,
+        mint.key(),
+        token_program.key(),
+        campaign_bump
+    );
+    create_pda_account(creator, campaign, CampaignData::LEN, &crate::ID, &Signer::from(&campaign_signer_seeds))?;
+    // SAFETY: Data is not previously borrowed and has the Transmutable trait.
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            campaign.data_ptr(),
+            CampaignData::LEN) }
+        .copy_from_slice(as_bytes(
+            &CampaignData::new(
+                /* creator */ *creator.key(),
+                /* recipient */ *recipient.key(),
+                /* mint */ *mint.key(),
+                /* token program */ *token_program.key(),
+                /* campaign index */ campaign_index,
+                /* campaign bump */ campaign_pda.1,
+                /* escrow bump */ escrow_pda.1,
+                /* target */ target,
+                /* deadline */ deadline,
+                /* allows withdraw */ data.allows_withdraw())
+        ));
+
+    let escrow_bump = &[escrow_pda.1];
+    let escrow_signer_seeds = CampaignData::get_escrow_signer_seeds(campaign.key(), escrow_bump);
+    create_deposit_account(
+        /* payer */ creator,
+        /* deposit */ escrow,
+        /* mint */ mint,
+        /* token_program */ token_program.key(),
+        /* deposit signer */ &Signer::from(&escrow_signer_seeds)
+    )?;
+
+    ProgramResult::Ok(())
+}
@@ -0,0 +1,60 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+
+use crate::{errors::PimeError, interface::instructions::finalize_campaign_instruction::FinalizeCampaignInstructionData, processors::shared::constraints::check, states::{campaign_data::CampaignData, load_mut}};
+
+/// Finalizes a campaign whose pledges reached its target by the deadline, releasing every pledge
+/// in its escrow to the recipient at once.
+pub fn finalize_campaign(accounts: &[AccountInfo], _data: &FinalizeCampaignInstructionData) -> ProgramResult {
+
+    let [campaign, escrow, recipient, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    // The campaign is trusted as the account is owned by this program.
+    check(campaign, "Campaign").writable()?;
+    let mut campaign_data = load_mut::<CampaignData>(campaign)?;
+    if !pubkey_eq(&campaign_data.mint, mint.key()) || !pubkey_eq(&campaign_data.token_program, token_program.key()) {
+        msg!("Supplied mint or token program does not match the campaign's.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+    let escrow_pda = CampaignData::create_escrow_pda(campaign.key(), campaign_data.escrow_bump())?;
+    check(escrow, "Escrow").address(&escrow_pda)?.writable()?;
+
+    if !pubkey_eq(&campaign_data.recipient, recipient.key()) {
+        msg!("Supplied recipient does not match the campaign's recipient.");
+        return Err(PimeError::DestinationMismatch.into());
+    }
+    check(recipient, "Recipient").owned_by(token_program.key())?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    // Pledges can be withdrawn until the deadline, so the total is only final afterwards.
+    if Clock::get()?.unix_timestamp <= campaign_data.deadline() {
+        msg!("Campaign has not yet passed its deadline.");
+        return Err(PimeError::CampaignActive.into());
+    }
+    if campaign_data.is_finalized() {
+        msg!("Campaign has already been finalized.");
+        return Err(PimeError::CampaignEnded.into());
+    }
+    if !campaign_data.reached_target() {
+        msg!("Campaign pledges did not reach its target.");
+        return Err(PimeError::CampaignTargetNotReached.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let escrow_bump = &[campaign_data.escrow_bump()];
+    let escrow_signer_seeds = CampaignData::get_escrow_signer_seeds(campaign.key(), escrow_bump);
+    pinocchio_token::instructions::Transfer {
+        from: escrow,
+        to: recipient,
+        authority: escrow,
+        amount: campaign_data.pledged(),
+    }.invoke_signed(&[Signer::from(&escrow_signer_seeds)])?;
+    campaign_data.set_finalized();
+
+    ProgramResult::Ok(())
+}
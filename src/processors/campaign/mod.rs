@@ -0,0 +1,5 @@
+pub(crate) mod create_campaign;
+pub(crate) mod pledge_to_campaign;
+pub(crate) mod withdraw_pledge;
+pub(crate) mod finalize_campaign;
+pub(crate) mod settle_pledge;
@@ -0,0 +1,111 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+
+use crate::{errors::PimeError, interface::instructions::pledge_to_campaign_instruction::PledgeToCampaignInstructionData, processors::shared::{constraints::check, create_account::create_pda_account}, states::{Transmutable, VaultAccount, VaultData, as_bytes, campaign_data::CampaignData, load_mut, pledge_data::PledgeData}};
+
+/// Pledges from a vault to a campaign, moving the amount into the campaign's escrow.
+/// The pledge counts as an open transfer of the vault, and stays in flight, until it is settled.
+/// As it leaves the vault, it is held to the vault's transfer warm-up and withdraw limits.
+pub fn pledge_to_campaign(accounts: &[AccountInfo], data: &PledgeToCampaignInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let amount = data.amount();
+
+    let [authority, vault_data, vault, campaign, escrow, pledge, mint, token_program, _system_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, history, lots, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    // The campaign is trusted as the account is owned by this program.
+    check(campaign, "Campaign").writable()?;
+    let mut campaign_data = load_mut::<CampaignData>(campaign)?;
+    if !pubkey_eq(&campaign_data.mint, mint.key()) || !pubkey_eq(&campaign_data.token_program, token_program.key()) {
+        msg!("Campaign does not raise the vault's mint.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+    let escrow_pda = CampaignData::create_escrow_pda(campaign.key(), campaign_data.escrow_bump())?;
+    check(escrow, "Escrow").address(&escrow_pda)?.writable()?;
+
+    let pledge_pda = PledgeData::get_pledge_pda(campaign.key(), vault_data.key());
+    check(pledge, "Pledge").address(&pledge_pda.0)?.uninitialized()?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    if !vault_data_account.allows_transfers() {
+        msg!("Vault does not allow transfers.");
+        return Err(PimeError::TransfersNotAllowed.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if !campaign_data.is_open(now) {
+        msg!("Campaign has passed its deadline.");
+        return Err(PimeError::CampaignEnded.into());
+    }
+    // The pledge is released at the deadline, which must not be sooner than a booked transfer could be.
+    if campaign_data.deadline().saturating_sub(now) < vault_data_account.transfer_min_warmup() {
+        msg!("Campaign deadline is sooner than the vault's min warm-up.");
+        return Err(PimeError::VaultWarmupViolation.into());
+    }
+    vault_data_account.check_withdraw_timing(history.last_withdraw(), now)?;
+    // Only matured funds can be pledged.
+    if amount > lots.unlocked_amount(TokenAccount::from_account_info(vault)?.amount(), now) {
+        msg!("Vault funds are locked until their lots mature.");
+        return Err(PimeError::FundsLocked.into());
+    }
+
+    // A raised limit applies once its delay has passed.
+    vault_data_account.apply_pending_max_transactions(now);
+
+    // The pledge counts against the vault's withdraw limits.
+    history.roll(now);
+    history.can_withdraw(amount, vault_data_account.max_amount(), vault_data_account.max_transactions())?;
+
+    //      BUSINESS LOGIC
+
+    let pledge_bump = &[pledge_pda.1];
+    let pledge_signer_seeds = PledgeData::get_pledge_signer_seeds(campaign.key(), vault_data.key(), pledge_bump);
+    create_pda_account(authority, pledge, PledgeData::LEN, &crate::ID, &Signer::from(&pledge_signer_seeds))?;
+    // SAFETY: Data is not previously borrowed and has the Transmutable trait.
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            pledge.data_ptr(),
+            PledgeData::LEN) }
+        .copy_from_slice(as_bytes(&PledgeData::new(*campaign.key(), *vault_data.key(), amount, pledge_pda.1)?));
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let vault_bump = &[vault_data_account.vault_bump()];
+    let vault_signer_seeds = VaultData::get_vault_signer_seeds(
+        authority.key(),
+        &vault_index_bytes,
+        mint.key(),
+        token_program.key(),
+        vault_bump);
+    Transfer {
+        from: vault,
+        to: escrow,
+        authority: vault,
+        amount
+    }.invoke_signed(&[Signer::from(&vault_signer_seeds)])?;
+
+    let open_transfers = vault_data_account.open_transfers() + 1;
+    vault_data_account.set_open_transfers(open_transfers);
+    vault_data_account.record_booked(amount, now);
+    history.record(now, amount)?;
+    campaign_data.record_pledged(amount);
+
+    ProgramResult::Ok(())
+}
@@ -0,0 +1,107 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
+
+use crate::{errors::PimeError, interface::instructions::settle_pledge_instruction::SettlePledgeInstructionData, processors::shared::{constraints::check, pledge::{close_pledge, refund_pledge}}, states::{VaultAccount, VaultData, campaign_data::CampaignData, load, load_mut, pledge_data::PledgeData}};
+
+/// Settles a pledge after the campaign's deadline, without the pledger's signature.
+///
+/// A pledge to a finalized campaign has been released to the recipient, and is recorded as
+/// executed. A pledge to a campaign which missed its target is refunded into its vault. Either
+/// way the pledge is closed, and its rent returned to the vault's authority.
+///
+/// Settling the last pledge also closes the campaign and its escrow, returning their rent to the
+/// campaign's creator.
+pub fn settle_pledge(accounts: &[AccountInfo], data: &SettlePledgeInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+
+    let [authority, vault_data, vault, campaign, escrow, pledge, mint, token_program, creator, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    // The authority does not sign, but receives the rent.
+    check(authority, "Authority").writable()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    // The campaign is trusted as the account is owned by this program.
+    check(campaign, "Campaign").writable()?;
+    let mut campaign_data = load_mut::<CampaignData>(campaign)?;
+    if !pubkey_eq(&campaign_data.mint, mint.key()) || !pubkey_eq(&campaign_data.token_program, token_program.key()) {
+        msg!("Campaign does not raise the vault's mint.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+    let escrow_pda = CampaignData::create_escrow_pda(campaign.key(), campaign_data.escrow_bump())?;
+    check(escrow, "Escrow").address(&escrow_pda)?.writable()?;
+
+    if !pubkey_eq(&campaign_data.creator, creator.key()) {
+        msg!("Supplied creator does not match the campaign's creator.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+    check(creator, "Creator").writable()?;
+
+    let pledge_data = load::<PledgeData>(pledge)?;
+    let pledge_pda = PledgeData::create_pledge_pda(campaign.key(), vault_data.key(), pledge_data.pledge_bump())?;
+    check(pledge, "Pledge").address(&pledge_pda)?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    let now = Clock::get()?.unix_timestamp;
+    if now <= campaign_data.deadline() {
+        msg!("Campaign has not yet passed its deadline.");
+        return Err(PimeError::CampaignActive.into());
+    }
+    // A successful campaign releases its pledges before they can be settled, so they are not refunded.
+    if !campaign_data.is_finalized() && campaign_data.reached_target() {
+        msg!("Campaign reached its target, and has to be finalized first.");
+        return Err(PimeError::CampaignNotFinalized.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let amount = pledge_data.amount();
+    drop(pledge_data);
+    if campaign_data.is_finalized() {
+        close_pledge(authority, pledge);
+        vault_data_account.record_executed(amount, now);
+    }
+    else {
+        refund_pledge(authority, vault, campaign, escrow, pledge, campaign_data.escrow_bump(), amount)?;
+        vault_data_account.record_unbooked(amount, now);
+    }
+    let open_transfers = vault_data_account.open_transfers().saturating_sub(1);
+    vault_data_account.set_open_transfers(open_transfers);
+    campaign_data.record_settled();
+
+    // Unpledged tokens sent to the escrow would fail its closing, so such a campaign is kept open.
+    if campaign_data.pledges() == 0 && TokenAccount::from_account_info(escrow)?.amount() == 0 {
+        let escrow_bump = &[campaign_data.escrow_bump()];
+        let escrow_signer_seeds = CampaignData::get_escrow_signer_seeds(campaign.key(), escrow_bump);
+        pinocchio_token::instructions::CloseAccount {
+            account: escrow,
+            destination: creator,
+            authority: escrow,
+        }.invoke_signed(&[Signer::from(&escrow_signer_seeds)])?;
+
+        drop(campaign_data);
+        // SAFETY: Campaign data is no longer borrowed.
+        unsafe {
+            *creator.borrow_mut_lamports_unchecked() += campaign.lamports();
+            campaign.close_unchecked();
+        }
+    }
+
+    ProgramResult::Ok(())
+}
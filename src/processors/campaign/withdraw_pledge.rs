@@ -0,0 +1,68 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+
+use crate::{errors::PimeError, interface::instructions::withdraw_pledge_instruction::WithdrawPledgeInstructionData, processors::shared::{constraints::check, pledge::refund_pledge}, states::{VaultAccount, VaultData, campaign_data::CampaignData, load, load_mut, pledge_data::PledgeData}};
+
+/// Withdraws a pledge back into its vault before the campaign's deadline, if the campaign allows it.
+pub fn withdraw_pledge(accounts: &[AccountInfo], data: &WithdrawPledgeInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+
+    let [authority, vault_data, vault, campaign, escrow, pledge, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?.writable()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    // The campaign is trusted as the account is owned by this program.
+    check(campaign, "Campaign").writable()?;
+    let mut campaign_data = load_mut::<CampaignData>(campaign)?;
+    if !pubkey_eq(&campaign_data.mint, mint.key()) || !pubkey_eq(&campaign_data.token_program, token_program.key()) {
+        msg!("Campaign does not raise the vault's mint.");
+        return Err(PimeError::InvalidCampaign.into());
+    }
+    let escrow_pda = CampaignData::create_escrow_pda(campaign.key(), campaign_data.escrow_bump())?;
+    check(escrow, "Escrow").address(&escrow_pda)?.writable()?;
+
+    let pledge_data = load::<PledgeData>(pledge)?;
+    let pledge_pda = PledgeData::create_pledge_pda(campaign.key(), vault_data.key(), pledge_data.pledge_bump())?;
+    check(pledge, "Pledge").address(&pledge_pda)?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    let now = Clock::get()?.unix_timestamp;
+    if !campaign_data.is_open(now) {
+        msg!("Campaign has passed its deadline, and its pledges can only be settled.");
+        return Err(PimeError::CampaignEnded.into());
+    }
+    if !campaign_data.allows_withdraw() {
+        msg!("Campaign does not allow withdrawing pledges.");
+        return Err(PimeError::PledgeWithdrawNotAllowed.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let amount = pledge_data.amount();
+    drop(pledge_data);
+    refund_pledge(authority, vault, campaign, escrow, pledge, campaign_data.escrow_bump(), amount)?;
+
+    let open_transfers = vault_data_account.open_transfers().saturating_sub(1);
+    vault_data_account.set_open_transfers(open_transfers);
+    vault_data_account.record_unbooked(amount, now);
+    campaign_data.record_withdrawn(amount);
+
+    ProgramResult::Ok(())
+}
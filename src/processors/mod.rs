@@ -4,3 +4,4 @@ pub(crate) mod vault;
 pub(crate) use vault::*;
 pub(crate) mod shared;
 pub(crate) mod transfer;
+pub(crate) mod campaign;
@@ -0,0 +1,41 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer};
+
+/// Closes an emptied transfer, along with its deposit.
+///
+/// The rent of both accounts goes back to the authority. If the transfer was cranked, the crank
+/// is paid the transfer's crank tip from the transfer account's lamports.
+/// The transfer account must no longer be borrowed.
+pub fn close_transfer(
+    authority: &AccountInfo,
+    transfer: &AccountInfo,
+    deposit: &AccountInfo,
+    deposit_signer: &Signer,
+    crank: Option<&AccountInfo>,
+    crank_tip: u64,
+) -> ProgramResult {
+    pinocchio_token::instructions::CloseAccount {
+        account: deposit,
+        destination: authority,
+        authority: deposit,
+    }.invoke_signed(core::slice::from_ref(deposit_signer))?;
+
+    let tip = match crank {
+        Some(_) => crank_tip.min(transfer.lamports()),
+        None => 0,
+    };
+    // SAFETY: Lamports are not borrowed elsewhere, and the transfer account is owned by this
+    // program and no longer borrowed.
+    //
+    // Note: This is safe since the runtime checks for balanced instructions
+    // before and after each CPI and instruction, and the total lamports
+    // supply is bound to `u64::MAX`.
+    unsafe {
+        if let Some(crank) = crank {
+            *crank.borrow_mut_lamports_unchecked() += tip;
+        }
+        *authority.borrow_mut_lamports_unchecked() += transfer.lamports() - tip;
+        transfer.close_unchecked();
+    }
+
+    Ok(())
+}
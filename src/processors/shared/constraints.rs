@@ -0,0 +1,89 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}};
+
+use crate::{errors::PimeError, processors::shared::create_account::is_uninitialized};
+
+/// Account constraints, stated in the order they are checked.
+///
+/// ```ignore
+/// check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+/// ```
+///
+/// Each violated constraint is logged as `"<name> <reason>"`, and fails with the same error in
+/// every processor:
+/// * `signer` - `ProgramError::MissingRequiredSignature`
+/// * `writable` - `ProgramError::Immutable`
+/// * `initialized` - `PimeError::AccountNotInitialized`
+/// * `uninitialized` - `ProgramError::AccountAlreadyInitialized`. Prefunded PDAs count as uninitialized.
+/// * `owned_by` - `PimeError::InvalidAccountOwner`
+/// * `address` - `PimeError::IncorrectPDA`
+/// * `min_len` - `PimeError::InvalidAccountLength`
+pub(crate) struct Constraints<'a> {
+    account: &'a AccountInfo,
+    #[cfg_attr(not(feature = "logging"), allow(dead_code))]
+    name: &'static str,
+}
+
+/// Starts stating the constraints of an account. `name` prefixes the logged violations.
+pub(crate) fn check<'a>(account: &'a AccountInfo, name: &'static str) -> Constraints<'a> {
+    Constraints { account, name }
+}
+
+impl<'a> Constraints<'a> {
+
+    pub(crate) fn signer(self) -> Result<Self, ProgramError> {
+        if !self.account.is_signer() {
+            return Err(self.fail("is not a signer.", ProgramError::MissingRequiredSignature));
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn writable(self) -> Result<Self, ProgramError> {
+        if !self.account.is_writable() {
+            return Err(self.fail("is not writable.", ProgramError::Immutable));
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn initialized(self) -> Result<Self, ProgramError> {
+        if self.account.lamports() == 0 {
+            return Err(self.fail("is not initialized.", PimeError::AccountNotInitialized.into()));
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn uninitialized(self) -> Result<Self, ProgramError> {
+        if !is_uninitialized(self.account) {
+            return Err(self.fail("is already initialized.", ProgramError::AccountAlreadyInitialized));
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn owned_by(self, owner: &Pubkey) -> Result<Self, ProgramError> {
+        if !self.account.is_owned_by(owner) {
+            return Err(self.fail("has an incorrect owner.", PimeError::InvalidAccountOwner.into()));
+        }
+        Ok(self)
+    }
+
+    /// The account's address has to be `expected`, usually a PDA.
+    pub(crate) fn address(self, expected: &Pubkey) -> Result<Self, ProgramError> {
+        if !pubkey_eq(self.account.key(), expected) {
+            return Err(self.fail("has an incorrect address.", PimeError::IncorrectPDA.into()));
+        }
+        Ok(self)
+    }
+
+    pub(crate) fn min_len(self, len: usize) -> Result<Self, ProgramError> {
+        if self.account.data_len() < len {
+            return Err(self.fail("has insufficient data.", PimeError::InvalidAccountLength.into()));
+        }
+        Ok(self)
+    }
+
+    #[allow(unused_variables)]
+    fn fail(&self, reason: &str, error: ProgramError) -> ProgramError {
+        #[cfg(feature = "logging")]
+        pinocchio_log::log!("{} {}", self.name, reason);
+        error
+    }
+}
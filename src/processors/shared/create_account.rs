@@ -0,0 +1,52 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, pubkey::Pubkey, sysvars::{Sysvar, rent::Rent}};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+
+/// Creates a rent exempt PDA of `space` bytes, owned by `owner`.
+///
+/// Anyone can send lamports to a PDA before it is created, which makes `CreateAccount` fail.
+/// Such a prefunded account is instead topped up to rent exemption by the payer, allocated and
+/// assigned, so that it can not block the creation.
+pub fn create_pda_account(
+    payer: &AccountInfo,
+    account: &AccountInfo,
+    space: usize,
+    owner: &Pubkey,
+    account_signer: &Signer,
+) -> ProgramResult {
+    let signer = core::slice::from_ref(account_signer);
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return CreateAccount {
+            from: payer,
+            to: account,
+            lamports: rent,
+            space: space as u64,
+            owner,
+        }.invoke_signed(signer);
+    }
+
+    if account.lamports() < rent {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: rent - account.lamports(),
+        }.invoke()?;
+    }
+    // Only accounts owned by the system program can be allocated, so allocate before assigning.
+    Allocate {
+        account,
+        space: space as u64,
+    }.invoke_signed(signer)?;
+    Assign {
+        account,
+        owner,
+    }.invoke_signed(signer)
+}
+
+/// If the account has not been created. A prefunded PDA holds lamports, but is still owned by
+/// the system program and without data.
+pub fn is_uninitialized(account: &AccountInfo) -> bool {
+    account.lamports() == 0 
+        || (account.is_owned_by(&pinocchio_system::ID) && account.data_is_empty())
+}
@@ -1,6 +1,8 @@
 use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, pubkey::Pubkey};
 use pinocchio_token::state::TokenAccount;
 
+use crate::processors::shared::create_account::create_pda_account;
+
 
 pub fn create_deposit_account (
     payer: &AccountInfo,
@@ -11,13 +13,12 @@ pub fn create_deposit_account (
 ) -> ProgramResult {
 
     let signer = core::slice::from_ref(deposit_signer);
-    pinocchio_system::create_account_with_minimum_balance_signed(
+    create_pda_account(
+            /* payer */ payer, 
             /* account */ deposit, 
             /* space */ TokenAccount::LEN,
             /* owner */ token_program, 
-            /* payer */ payer, 
-            /* rent sysvar */ None,
-            /* signers */ signer,
+            /* signer */ deposit_signer,
         )?;
 
     pinocchio_token::instructions::InitializeAccount3 {
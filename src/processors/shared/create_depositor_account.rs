@@ -0,0 +1,26 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, pubkey::Pubkey};
+
+use crate::{processors::shared::create_account::create_pda_account, states::{Transmutable, as_bytes, depositor_data::DepositorData}};
+
+/// Create a vault's depositor record.
+///
+/// Will fail if account exists (Does not check)
+pub fn create_depositor_account(
+    payer: &AccountInfo,
+    depositor_record: &AccountInfo,
+    vault_data: &Pubkey,
+    depositor: &Pubkey,
+    allowed: bool,
+    depositor_signer: &Signer,
+) -> ProgramResult {
+    create_pda_account(payer, depositor_record, DepositorData::LEN, &crate::ID, depositor_signer)?;
+
+    // SAFETY: Data is not previously borrowed and has the Transmutable trait.
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            depositor_record.data_ptr(), 
+            DepositorData::LEN) }
+        .copy_from_slice(as_bytes(&DepositorData::new(*vault_data, *depositor, allowed)));
+
+    Ok(())
+}
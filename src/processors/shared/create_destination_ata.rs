@@ -0,0 +1,45 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError, pubkey::{find_program_address, pubkey_eq}};
+
+use crate::errors::PimeError;
+
+/// Creates the destination's associated token account, funded by `funding_account`, if it does
+/// not exist yet.
+///
+/// The system program, the ATA's owner and the associated token program are expected at the start
+/// of `remaining`, and only required if the destination has to be created.
+pub fn create_destination_ata(
+    funding_account: &AccountInfo,
+    destination: &AccountInfo,
+    mint: &AccountInfo,
+    token_program: &AccountInfo,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    if destination.lamports() != 0 {
+        return Ok(());
+    }
+    let [system_program, ata_owner, a_token, _remainder @ .. ] = remaining else {
+        msg!("Requires system program, ata owner, and associated token program.");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !pubkey_eq(a_token.key(), &pinocchio_associated_token_account::ID) {
+        msg!("Associated token program is incorrect.");
+        return Err(PimeError::UnsupportedAssociatedTokenProgram.into());
+    }
+    let ata = find_program_address(&[
+        ata_owner.key(),
+        token_program.key(),
+        mint.key(),
+    ], a_token.key());
+    if !pubkey_eq(destination.key(), &ata.0) {
+        msg!("Destination ATA is not derived from the provided owner.");
+        return Err(PimeError::DestinationMismatch.into());
+    }
+    pinocchio_associated_token_account::instructions::Create{
+        funding_account,
+        account: destination,
+        wallet: ata_owner,
+        mint,
+        system_program,
+        token_program
+    }.invoke()
+}
@@ -1,9 +1,11 @@
 use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, pubkey::Pubkey};
 use pinocchio_token::state::TokenAccount;
 
+use crate::processors::shared::create_account::create_pda_account;
+
 /// Create the Vault Token Account.
 ///
-/// Will fail if account exists (Does not check)
+/// Will fail if account exists (Does not check), but tolerates it being prefunded.
 pub fn create_vault_account (
     payer: &AccountInfo, 
     vault: &AccountInfo,
@@ -13,13 +15,12 @@ pub fn create_vault_account (
 ) -> ProgramResult {
     let signer = core::slice::from_ref(vault_signer);
 
-    pinocchio_system::create_account_with_minimum_balance_signed(
+    create_pda_account(
+            /* payer */ payer, 
             /* account */ vault, 
             /* space */ TokenAccount::LEN,
             /* owner */ token_program, 
-            /* payer */ payer, 
-            /* rent sysvar */ None,
-            /* signers */ signer,
+            /* signer */ vault_signer,
         )?;
 
     pinocchio_token::instructions::InitializeAccount3 {
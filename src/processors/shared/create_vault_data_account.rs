@@ -1,28 +1,31 @@
-use pinocchio::{account_info::AccountInfo, instruction::Signer, program_error::ProgramError, sysvars::clock::UnixTimestamp};
+use pinocchio::{account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey, sysvars::{Sysvar, clock::{Clock, UnixTimestamp}}};
 
-use crate::states::{Transmutable, VaultData, VaultHistory, as_bytes};
+use crate::{processors::shared::create_account::create_pda_account, states::{Transmutable, VaultData, as_bytes, lot_schedule::LotSchedule, transfer_registry::TransferRegistry, withdraw_history::WithdrawHistory}};
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn process_create_vault_data_account(
     authority: &AccountInfo, 
     vault_data: &AccountInfo, 
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    vault_index: u64,
+    vault_data_bump: u8,
+    vault_bump: u8,
     max_transactions: u64, 
     timeframe: i64, 
     max_lamports: u64, 
     allows_transfers: u8,
     transfer_min_warmup: UnixTimestamp,
     transfer_max_window: UnixTimestamp,
+    withdraw_cooldown: UnixTimestamp,
+    withdraw_warmup: UnixTimestamp,
     vault_data_signer: &Signer) -> Result<(), ProgramError> {
-    let signer = core::slice::from_ref(vault_data_signer);
-
-    let vault_data_size = size_of::<VaultData>() + (max_transactions as usize * size_of::<VaultHistory>());
-    pinocchio_system::
-        create_account_with_minimum_balance_signed(
+    create_pda_account(
+            /* payer */ authority, 
             /* account */ vault_data, 
-            /* space */ vault_data_size, 
+            /* space */ VaultData::SPACE, 
             /* owner */ &crate::ID, 
-            /* payer */ authority, 
-            /* rent sysvar */ None,
-            /* signer seeds */ signer
+            /* signer */ vault_data_signer
         )?;
 
     // SAFETY: Data is not previously borrowed and is represented by a valid format.
@@ -31,26 +34,41 @@ pub(crate) fn process_create_vault_data_account(
             vault_data.data_ptr(), 
             size_of::<VaultData>())
     };
-    vault_data_mut.copy_from_slice(as_bytes(
-        &VaultData::new(
-            *authority.key(), 
-            timeframe, 
-            max_lamports, 
-            max_transactions,
-            allows_transfers,
-            transfer_min_warmup,
-            transfer_max_window,
-        )));
+    let mut new_vault_data = VaultData::new(
+        *authority.key(), 
+        *mint,
+        *token_program,
+        vault_index,
+        vault_data_bump,
+        vault_bump,
+        timeframe, 
+        max_lamports, 
+        max_transactions,
+        allows_transfers,
+        transfer_min_warmup,
+        transfer_max_window,
+        withdraw_cooldown,
+        withdraw_warmup,
+    );
+    // A new vault has to warm up before its first withdraw.
+    new_vault_data.start_withdraw_warmup(Clock::get()?.unix_timestamp);
+    vault_data_mut.copy_from_slice(as_bytes(&new_vault_data));
 
-    let h = VaultHistory::new(UnixTimestamp::MIN, u64::MIN);
-    let fake_history = as_bytes(&h);
-    for i in 0..(max_transactions as usize) {
-        unsafe {
-            core::slice::from_raw_parts_mut(
-                vault_data.data_ptr().add(VaultData::LEN + i * VaultHistory::LEN), 
-                VaultHistory::LEN)
-                .copy_from_slice(fake_history);
-        }
+    let history = WithdrawHistory::new(timeframe);
+    // SAFETY: Data is not previously borrowed, and the account is VaultData::SPACE long.
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            vault_data.data_ptr().add(VaultData::LEN), 
+            WithdrawHistory::LEN)
+            .copy_from_slice(as_bytes(&history));
+        core::slice::from_raw_parts_mut(
+            vault_data.data_ptr().add(VaultData::LEN + WithdrawHistory::LEN), 
+            TransferRegistry::LEN)
+            .copy_from_slice(as_bytes(&TransferRegistry::new()));
+        core::slice::from_raw_parts_mut(
+            vault_data.data_ptr().add(VaultData::LEN + WithdrawHistory::LEN + TransferRegistry::LEN), 
+            LotSchedule::LEN)
+            .copy_from_slice(as_bytes(&LotSchedule::new()));
     }
 
     Ok(())
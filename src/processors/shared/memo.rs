@@ -0,0 +1,25 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, cpi::invoke, instruction::Instruction, program_error::ProgramError, pubkey::pubkey_eq};
+
+use crate::states::memo::{MEMO_PROGRAM_ID, Memo};
+
+/// Attaches the memo to the transaction through the SPL Memo program.
+///
+/// The memo program is looked up among the instruction's remaining accounts, which may also hold
+/// reference accounts. Nothing is attached for an empty memo.
+pub fn emit_memo(memo: &Memo, remaining: &[AccountInfo]) -> ProgramResult {
+    if memo.is_empty() {
+        return Ok(());
+    }
+    let Some(memo_program) = remaining.iter().find(|a| pubkey_eq(a.key(), &MEMO_PROGRAM_ID)) else {
+        msg!("Requires the memo program to attach the memo.");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    invoke::<0>(
+        &Instruction {
+            program_id: memo_program.key(),
+            data: memo.as_slice(),
+            accounts: &[],
+        },
+        &[],
+    )
+}
@@ -1,5 +1,14 @@
 pub mod create_vault_account;
 pub mod create_vault_data_account;
 pub mod create_deposit_account;
+pub mod create_depositor_account;
 pub mod transfer;
 pub mod withdraw;
+pub mod registry;
+pub mod resize_account;
+pub mod constraints;
+pub mod create_account;
+pub mod close_transfer;
+pub mod memo;
+pub mod create_destination_ata;
+pub mod pledge;
@@ -0,0 +1,41 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer};
+
+use crate::states::campaign_data::CampaignData;
+
+/// Refunds a pledge from the campaign's escrow into the vault it came from, and closes the pledge.
+///
+/// The vault, campaign, escrow and pledge must already have been verified by the caller, and the
+/// pledge no longer be borrowed.
+pub fn refund_pledge(
+    authority: &AccountInfo,
+    vault: &AccountInfo,
+    campaign: &AccountInfo,
+    escrow: &AccountInfo,
+    pledge: &AccountInfo,
+    escrow_bump: u8,
+    amount: u64,
+) -> ProgramResult {
+    let escrow_bump = &[escrow_bump];
+    let escrow_signer_seeds = CampaignData::get_escrow_signer_seeds(campaign.key(), escrow_bump);
+    pinocchio_token::instructions::Transfer {
+        from: escrow,
+        to: vault,
+        authority: escrow,
+        amount,
+    }.invoke_signed(&[Signer::from(&escrow_signer_seeds)])?;
+
+    close_pledge(authority, pledge);
+    Ok(())
+}
+
+/// Closes a settled pledge, returning its rent to the vault's authority.
+///
+/// The pledge must no longer be borrowed.
+pub fn close_pledge(authority: &AccountInfo, pledge: &AccountInfo) {
+    // SAFETY: Lamports are not borrowed elsewhere, and the pledge account is owned by this
+    // program and no longer borrowed.
+    unsafe {
+        *authority.borrow_mut_lamports_unchecked() += pledge.lamports();
+        pledge.close_unchecked();
+    }
+}
@@ -0,0 +1,76 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{errors::PimeError, processors::shared::{constraints::check, create_account::{create_pda_account, is_uninitialized}, resize_account::resize_account}, states::{Transmutable, as_bytes, load, load_mut, registry_data::{RegistryData, RegistryEntry, VaultStatus}}};
+
+/// Verifies that the supplied account is the authority's registry.
+fn check_registry(authority: &AccountInfo, registry: &AccountInfo) -> Result<u8, ProgramError> {
+    let registry_pda = RegistryData::get_registry_pda(authority.key());
+    check(registry, "Registry").address(&registry_pda.0)?.writable()?;
+    if !is_uninitialized(registry) {
+        load::<RegistryData>(registry)?;
+    }
+    Ok(registry_pda.1)
+}
+
+/// Adds a vault to its authority's registry, creating the registry if it does not exist.
+/// Moves the registry's next index past the vault's index.
+pub fn register_vault(authority: &AccountInfo, registry: &AccountInfo, vault_index: u64, mint: &Pubkey, token_program: &Pubkey) -> ProgramResult {
+    let registry_bump = check_registry(authority, registry)?;
+
+    if is_uninitialized(registry) {
+        let registry_bump = &[registry_bump];
+        let registry_seeds = RegistryData::get_registry_signer_seeds(authority.key(), registry_bump);
+        create_pda_account(authority, registry, RegistryData::LEN, &crate::ID, &Signer::from(&registry_seeds))?;
+        // SAFETY: Data is not previously borrowed and has the Transmutable trait.
+        unsafe { core::slice::from_raw_parts_mut(registry.data_ptr(), RegistryData::LEN) }
+            .copy_from_slice(as_bytes(&RegistryData::new(*authority.key())));
+    }
+
+    let count = load::<RegistryData>(registry)?.count();
+    resize_account(authority, registry, RegistryData::space(count as usize + 1))?;
+
+    // SAFETY: Registry was resized to fit the new entry, and data pointers are refreshed after the resize.
+    unsafe {
+        core::slice::from_raw_parts_mut(
+            registry.data_ptr().add(RegistryData::space(count as usize)),
+            RegistryEntry::LEN)
+            .copy_from_slice(as_bytes(&RegistryEntry::new(vault_index, *mint, *token_program, VaultStatus::Active)));
+    }
+    let mut registry_data = load_mut::<RegistryData>(registry)?;
+    registry_data.set_count(count + 1);
+    if vault_index >= registry_data.next_index() {
+        registry_data.set_next_index(vault_index.saturating_add(1));
+    }
+
+    Ok(())
+}
+
+/// Removes a vault from its authority's registry.
+/// Vaults created before the registry existed are not registered, and are ignored.
+pub fn unregister_vault(authority: &AccountInfo, registry: &AccountInfo, vault_index: u64, mint: &Pubkey, token_program: &Pubkey) -> ProgramResult {
+    check_registry(authority, registry)?;
+    if is_uninitialized(registry) {
+        return Ok(());
+    }
+
+    let count = load::<RegistryData>(registry)?.count() as usize;
+    if registry.data_len() < RegistryData::space(count) {
+        msg!("Registry has insufficient data.");
+        return Err(PimeError::InvalidAccountLength.into());
+    }
+
+    let entry_ptr = |i: usize| unsafe { registry.data_ptr().add(RegistryData::space(i)) };
+    // SAFETY: All `count` entries are within the registry's data, and entries are Transmutable.
+    let Some(position) = (0..count).find(|&i| unsafe { &*(entry_ptr(i) as *const RegistryEntry) }.is_vault(vault_index, mint, token_program)) else {
+        return Ok(());
+    };
+
+    // Move the last entry into the removed entry's place.
+    if position != count - 1 {
+        // SAFETY: Both entries are within the registry's data and do not overlap.
+        unsafe { core::ptr::copy_nonoverlapping(entry_ptr(count - 1), entry_ptr(position), RegistryEntry::LEN) };
+    }
+    load_mut::<RegistryData>(registry)?.set_count(count as u64 - 1);
+
+    resize_account(authority, registry, RegistryData::space(count - 1))
+}
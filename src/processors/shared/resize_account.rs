@@ -0,0 +1,27 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, sysvars::{Sysvar, rent::Rent}};
+use pinocchio_system::instructions::Transfer;
+
+/// Resizes a program owned account, keeping it rent exempt.
+/// Missing rent is paid by the payer, and excess rent is refunded to it.
+pub fn resize_account(payer: &AccountInfo, account: &AccountInfo, new_len: usize) -> ProgramResult {
+    account.resize(new_len)?;
+
+    let rent = Rent::get()?.minimum_balance(new_len);
+    if account.lamports() < rent {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: rent - account.lamports(),
+        }.invoke()?;
+    }
+    else if account.lamports() > rent {
+        let excess = account.lamports() - rent;
+        // SAFETY: Lamports are not borrowed elsewhere, and the account is owned by this program.
+        unsafe {
+            *account.borrow_mut_lamports_unchecked() -= excess;
+            *payer.borrow_mut_lamports_unchecked() += excess;
+        }
+    }
+
+    Ok(())
+}
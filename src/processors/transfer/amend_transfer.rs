@@ -0,0 +1,153 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+
+use crate::{errors::PimeError, interface::instructions::amend_transfer_instruction::AmendTransferInstructionData, processors::shared::constraints::check, states::{VaultAccount, VaultData, load_mut, transfer_data::TransferData}};
+
+/// Amends the amount, destination or validity of a booked transfer.
+///
+/// Lowering the amount or shortening the validity applies right away, and the lowered amount
+/// is returned to the vault. Raising the amount, changing the destination or extending the
+/// validity restarts the warm-up from now, for the vault's transfer min warm-up. A restarted
+/// warm-up never ends before the booked one.
+pub fn amend_transfer(accounts: &[AccountInfo], data: &AmendTransferInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let transfer_index = data.transfer_index();
+    let amount = data.amount();
+    let destination = &data.destination;
+    let validity = data.validity();
+
+    if validity < 0 {
+        msg!("Validity must be positive.");
+        return Err(PimeError::InvalidTransferValidity.into());
+    }
+
+    let [authority, vault_data, vault, transfer, deposit, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, transfers, lots, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    check(transfer, "Transfer").writable()?;
+    let mut transfer_data = load_mut::<TransferData>(transfer)?;
+    // The PDAs are derived from the destination the transfer was booked with.
+    let booked_destination = transfer_data.booked_destination;
+    let transfer_pda = TransferData::create_transfer_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.transfer_bump())?;
+    check(transfer, "Transfer").address(&transfer_pda)?;
+
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    // The counterparty of a hash lock relies on its terms until it expires.
+    if transfer_data.is_hash_locked() {
+        msg!("Hash-locked transfers can not be amended.");
+        return Err(PimeError::HashLocked.into());
+    }
+    // The counterpart of a swap holds it to the terms it was booked with.
+    if transfer_data.is_swap() {
+        msg!("Swaps can not be amended.");
+        return Err(PimeError::TransferIsSwap.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now > transfer_data.created().saturating_add(transfer_data.validity()) {
+        msg!("Transfer has expired. Close this transfer and create a new one.");
+        return Err(PimeError::TransferExpired.into());
+    }
+    if amount <= transfer_data.drawn() {
+        msg!("Amended amount must exceed what has been drawn.");
+        return Err(PimeError::InvalidAmendment.into());
+    }
+
+    let booked_amount = transfer_data.amount();
+    let restarts_warmup = amount > booked_amount 
+        || !pubkey_eq(destination, &transfer_data.destination)
+        || validity > transfer_data.validity();
+    let warmup = if restarts_warmup {
+        // Released no earlier than the vault's min warm-up from now, and never earlier than booked.
+        let release = now.checked_add(vault_data_account.transfer_min_warmup()).ok_or(ProgramError::ArithmeticOverflow)?;
+        release.checked_sub(transfer_data.created()).ok_or(ProgramError::ArithmeticOverflow)?.max(transfer_data.warmup())
+    }
+    else {
+        transfer_data.warmup()
+    };
+    if warmup.saturating_add(transfer_data.stream()) > validity {
+        msg!("Warm-up and stream must end within the validity.");
+        return Err(PimeError::InvalidTransferStream.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let transfer_index_bytes = transfer_index.to_le_bytes();
+    if amount < booked_amount {
+        // Return the lowered amount to the vault.
+        let deposit_bump = &[transfer_data.deposit_bump()];
+        let deposit_signer_seeds = TransferData::get_deposit_signer_seeds(
+            authority.key(), 
+            &booked_destination,
+            &vault_index_bytes, 
+            &transfer_index_bytes, 
+            mint.key(), 
+            token_program.key(), 
+            deposit_bump
+        );
+        Transfer {
+            from: deposit,
+            to: vault,
+            authority: deposit,
+            amount: booked_amount - amount,
+        }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
+        vault_data_account.record_unbooked(booked_amount - amount, now);
+    }
+    else if amount > booked_amount {
+        // Only matured funds can be booked.
+        if amount - booked_amount > lots.unlocked_amount(TokenAccount::from_account_info(vault)?.amount(), now) {
+            msg!("Vault funds are locked until their lots mature.");
+            return Err(PimeError::FundsLocked.into());
+        }
+        let vault_bump = &[vault_data_account.vault_bump()];
+        let vault_signer_seeds = VaultData::get_vault_signer_seeds(
+            authority.key(), 
+            &vault_index_bytes, 
+            mint.key(), 
+            token_program.key(), 
+            vault_bump);
+        Transfer {
+            from: vault,
+            to: deposit,
+            authority: vault,
+            amount: amount - booked_amount,
+        }.invoke_signed(&[Signer::from(&vault_signer_seeds)])?;
+        vault_data_account.record_booked(amount - booked_amount, now);
+    }
+
+    transfer_data.set_amount(amount);
+    transfer_data.destination = *destination;
+    transfer_data.set_validity(validity);
+    transfer_data.set_warmup(warmup);
+    if let Some(entry) = transfers.entry_mut(transfer_index) {
+        entry.amend(
+            amount - transfer_data.drawn(), 
+            *destination, 
+            transfer_data.created().saturating_add(warmup), 
+            transfer_data.created().saturating_add(validity));
+    }
+
+    ProgramResult::Ok(())
+}
@@ -1,129 +1,94 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}, sysvars::{Sysvar, rent::Rent}};
-use pinocchio_system::instructions::CreateAccount;
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey, sysvars::{Sysvar, clock::Clock}};
 use pinocchio_token::{instructions::Transfer, state::TokenAccount};
 
-use crate::{errors::PimeError, interface::instructions::book_transfer::BookTransferInstructionData, processors::shared::create_deposit_account::create_deposit_account, states::{Transmutable, VaultData, as_bytes, from_bytes, transfer_data::TransferData}};
+use crate::{errors::PimeError, interface::instructions::book_transfer::BookTransferInstructionData, processors::shared::{constraints::check, create_account::create_pda_account, create_deposit_account::create_deposit_account}, states::{VaultAccount, VaultData, as_bytes, hash_lock::HashLock, load_mut, transfer_data::TransferData, transfer_registry::TransferEntry}};
 
 /// Books a transfer and stores the assets in a temporary vault.
-pub fn process_book_transfer(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
-
-    if instruction_data.len() < size_of::<BookTransferInstructionData>() - size_of::<u8>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    // SAFETY: instruction data is long enough
-    let (amount, destination, vault_index, transfer_index, warmup, validity) = unsafe { (
-        u64::from_le_bytes( *(instruction_data.as_ptr() as *const [u8; size_of::<u64>()])), 
-        &*(instruction_data.as_ptr().add(size_of::<u64>()) as *const Pubkey), 
-        u64::from_le_bytes( *(instruction_data.as_ptr().add(size_of::<Pubkey>() + size_of::<u64>()) as *const [u8; size_of::<u64>()])),
-        u64::from_le_bytes( *(instruction_data.as_ptr().add(size_of::<Pubkey>() + 2 * size_of::<u64>()) as *const [u8; size_of::<u64>()])),
-        i64::from_le_bytes( *(instruction_data.as_ptr().add(size_of::<Pubkey>() + 2 * size_of::<u64>() + size_of::<i64>()) as *const [u8; size_of::<u64>()])),
-        i64::from_le_bytes( *(instruction_data.as_ptr().add(size_of::<Pubkey>() + 2 * size_of::<u64>() + 2 * size_of::<i64>()) as *const [u8; size_of::<u64>()])),
-    ) 
-    };
+pub fn process_book_transfer(accounts: &[AccountInfo], data: &BookTransferInstructionData) -> ProgramResult {
+
+    let amount = data.amount();
+    let destination = &data.destination;
+    let vault_index = data.vault_index();
+    let warmup = data.warmup();
+    let validity = data.validity();
+    let allows_crank = data.allows_crank();
+    let crank_tip = data.crank_tip();
+    let allows_partial = data.allows_partial();
+    let stream = data.stream();
+    let memo = data.memo();
+    let claim_link = data.is_claim_link();
+    let hash_lock = HashLock::try_from(data.hash_lock())?;
+    let swap = data.swap();
+    let swap_amount = data.swap_amount();
 
     if warmup < 0 {
         msg!("Warm-up must be positive.");
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(PimeError::InvalidTransferWarmup.into());
     }
     if validity < 0 {
         msg!("Validity must be positive.");
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(PimeError::InvalidTransferValidity.into());
+    }
+    if stream < 0 || warmup.saturating_add(stream) > validity {
+        msg!("Stream must be positive and end within the validity.");
+        return Err(PimeError::InvalidTransferStream.into());
+    }
+    if crank_tip > 0 && !allows_crank {
+        msg!("Crank tip requires the transfer to allow being cranked.");
+        return Err(PimeError::InvalidCrankTip.into());
+    }
+    memo.check()?;
+    // Partial draws and claims are not checked against the preimage.
+    if hash_lock != HashLock::None && (allows_partial || stream > 0 || claim_link) {
+        msg!("Hash locks can not be combined with partial draws or claim links.");
+        return Err(PimeError::InvalidHashLock.into());
+    }
+    // Swaps are only settled in full, together with their counterpart.
+    if *swap != Pubkey::default() && (swap_amount == 0 || allows_crank || allows_partial || stream > 0 || claim_link || hash_lock != HashLock::None) {
+        msg!("Swaps require a counterpart amount, and can not be combined with cranks, partial draws, claim links or hash locks.");
+        return Err(PimeError::InvalidSwap.into());
     }
 
     let [authority, vault_data, vault, transfer, deposit, mint, token_program, _remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    if !authority.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(authority, "Authority").signer()?;
 
-    if !mint.is_owned_by(token_program.key()) {
-        msg!("Mint not owned by supplied token program.");
-        return Err(ProgramError::IllegalOwner);
-    }
+    check(mint, "Mint").owned_by(token_program.key())?;
 
-    let vault_data_pda = VaultData::get_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(vault_data.key(), &vault_data_pda.0) {
-        msg!("Incorrect vault data PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_data.lamports() == 0 {
-        msg!("Vault data is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault_data.is_owned_by(&crate::ID) {
-        msg!("Vault data account is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault_data.is_writable() {
-        msg!("Vault data is not writable.");
-        return Err(ProgramError::Immutable);
-    }
-    if vault_data.data_len() < size_of::<VaultData>() {
-        msg!("Vault data is of incorrect size.");
-        return Err(ProgramError::AccountDataTooSmall);
-    }
-    // SAFETY: Vault data is not borrowed before this. 
-    let vault_data_account = 
-    unsafe { from_bytes::<VaultData>(&vault_data.borrow_data_unchecked()[..size_of::<VaultData>()]) }?;
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, transfers, lots, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
     if vault_data_account.transfer_min_warmup() < warmup {
         msg!("The instructed warm-up violates the vaults min warm-up.");
         return Err(PimeError::VaultWarmupViolation.into());
     }
 
-    let vault_pda = VaultData::get_vault_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(vault.key(), &vault_pda.0) {
-        msg!("Incorrect vault PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault.lamports() == 0 {
-        msg!("Vault is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault.is_owned_by(token_program.key()) {
-        msg!("Vault account is not owned by the supplied token program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault.is_writable() {
-        msg!("Vault is not writeable.");
-        return Err(ProgramError::Immutable);
-    }
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
     if vault.data_len() < TokenAccount::LEN {
         msg!("Vault is not of enough length. Is it really a token account?");
-        return Err(ProgramError::AccountDataTooSmall);
+        return Err(PimeError::InvalidTokenAccount.into());
+    }
+    // Only matured funds can be booked.
+    let now = Clock::get()?.unix_timestamp;
+    if amount > lots.unlocked_amount(TokenAccount::from_account_info(vault)?.amount(), now) {
+        msg!("Vault funds are locked until their lots mature.");
+        return Err(PimeError::FundsLocked.into());
     }
-    // SAFETY vault is read-only by this call, and not used after the Token Program CPI.
-    // let vault_account = unsafe { TokenAccount::from_bytes_unchecked(vault.borrow_data_unchecked()) };
+
+    // Transfer indices are assigned by the vault, and never reused within it.
+    let transfer_index = vault_data_account.take_transfer_index()?;
 
     let transfer_pda = TransferData::get_transfer_pda(authority.key(), destination, vault_index, transfer_index, mint.key(), token_program.key());
-    if !pubkey_eq(transfer.key(), &transfer_pda.0) {
-        msg!("Incorrect transfer PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if transfer.lamports() != 0 {
-        msg!("A transfer is already booked.");
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
-    if !transfer.is_writable() {
-        msg!("Transfer is not writable.");
-        return Err(ProgramError::Immutable);
-    }
+    check(transfer, "Transfer").address(&transfer_pda.0)?.uninitialized()?.writable()?;
 
     let deposit_pda = TransferData::get_deposit_pda(authority.key(), destination, vault_index, transfer_index, mint.key(), token_program.key());
-    if !pubkey_eq(deposit.key(), &deposit_pda.0) {
-        msg!("Incorrect deposit PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if deposit.lamports() != 0 {
-        msg!("The deposit is already in use");
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
-    if !deposit.is_writable() {
-        msg!("Deposit is not writable.");
-        return Err(ProgramError::Immutable);
-    }
+    check(deposit, "Deposit").address(&deposit_pda.0)?.uninitialized()?.writable()?;
 
     //      Create transfer account and assign data
     let transfer_index_bytes = transfer_index.to_le_bytes();
@@ -138,13 +103,7 @@ pub fn process_book_transfer(accounts: &[AccountInfo], instruction_data: &[u8])
         token_program.key(), 
         transfer_bump
     );
-    CreateAccount {
-        from: authority,
-        to: transfer,
-        lamports: Rent::get()?.minimum_balance(size_of::<TransferData>()),
-        space: size_of::<TransferData>() as u64,
-        owner: &crate::ID,
-    }.invoke_signed(&[Signer::from(&transfer_seed)])?;
+    create_pda_account(authority, transfer, size_of::<TransferData>(), &crate::ID, &Signer::from(&transfer_seed))?;
     // SAFETY: Data is not previously borrowed and has the Transmutable trait.
     unsafe {
         core::slice::from_raw_parts_mut(
@@ -155,9 +114,33 @@ pub fn process_book_transfer(accounts: &[AccountInfo], instruction_data: &[u8])
                 /* vault data */ *vault_data.key(), 
                 /* amount */ amount,
                 /* destination */ *destination,
+                /* mint */ *mint.key(),
+                /* token program */ *token_program.key(),
+                /* vault index */ vault_index,
+                /* transfer index */ transfer_index,
+                /* transfer bump */ transfer_pda.1,
+                /* deposit bump */ deposit_pda.1,
                 /* warm-up */ warmup, 
-                /* validity */ validity)?
+                /* validity */ validity,
+                /* allows crank */ allows_crank,
+                /* crank tip */ crank_tip,
+                /* allows partial */ allows_partial,
+                /* stream */ stream,
+                /* memo */ *memo,
+                /* claim link */ claim_link,
+                /* hash lock */ hash_lock,
+                /* hash */ *data.hash(),
+                /* swap */ *swap,
+                /* swap amount */ swap_amount)?
         ));
+    // The crank tip is held by the transfer account on top of its rent.
+    if crank_tip > 0 {
+        pinocchio_system::instructions::Transfer {
+            from: authority,
+            to: transfer,
+            lamports: crank_tip,
+        }.invoke()?;
+    }
 
     //      Create deposit token account
     let deposit_bump = &[deposit_pda.1];
@@ -179,7 +162,7 @@ pub fn process_book_transfer(accounts: &[AccountInfo], instruction_data: &[u8])
     )?;
 
     //      Transfer from vault to deposit
-    let vault_bump = &[vault_pda.1]; // Prevent dropping
+    let vault_bump = &[vault_data_account.vault_bump()]; // Prevent dropping
     let vault_signer_seed = VaultData::get_vault_signer_seeds(
         authority.key(), 
         &vault_index_bytes, 
@@ -194,10 +177,17 @@ pub fn process_book_transfer(accounts: &[AccountInfo], instruction_data: &[u8])
     }.invoke_signed(&[Signer::from(&vault_signer_seed)])?;
 
 
-    // Decrement open transfers from the vault data.
-    // SAFETY: Only mutable here. Vault data bytes are a valid representation of VaultData
-    let vault_data_mut = unsafe { &mut *(vault_data.data_ptr() as *mut VaultData) };
-    vault_data_mut.set_open_transfers(vault_data_mut.open_transfers() + 1);
+    // Increment open transfers from the vault data.
+    let open_transfers = vault_data_account.open_transfers() + 1;
+    vault_data_account.set_open_transfers(open_transfers);
+    vault_data_account.record_booked(amount, now);
+    transfers.register(TransferEntry::new(
+        transfer_index, 
+        *destination, 
+        amount, 
+        now.saturating_add(warmup), 
+        now.saturating_add(validity)
+    ))?;
 
     ProgramResult::Ok(())
 }
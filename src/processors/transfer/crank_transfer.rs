@@ -0,0 +1,138 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
+
+use crate::{errors::PimeError, interface::instructions::crank_transfer_instruction::CrankTransferInstructionData, processors::shared::{close_transfer::close_transfer, constraints::check, memo::emit_memo}, states::{VaultAccount, VaultData, load_mut, transfer_data::TransferData}};
+
+/// Completes a transfer which allows being cranked, without the authority's signature.
+///
+/// Within its validity the released assets are sent to its destination, and once it has expired
+/// the undrawn remainder is refunded to the vault. When the transfer closes, the rent goes back
+/// to the authority, while the crank is paid the transfer's crank tip.
+pub fn crank_transfer(accounts: &[AccountInfo], data: &CrankTransferInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let transfer_index = data.transfer_index();
+
+    let [crank, authority, vault_data, vault, transfer, deposit, destination, mint, token_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(crank, "Crank").signer()?.writable()?;
+
+    // The authority does not sign, but receives the rent.
+    check(authority, "Authority").writable()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, transfers, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    let mut transfer_data = load_mut::<TransferData>(transfer)?;
+    // The PDAs are derived from the destination the transfer was booked with.
+    let booked_destination = transfer_data.booked_destination;
+    let transfer_pda = TransferData::create_transfer_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.transfer_bump())?;
+    check(transfer, "Transfer").address(&transfer_pda)?.writable()?;
+
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    let now = Clock::get()?.unix_timestamp;
+    let expired = now > transfer_data.created().saturating_add(transfer_data.validity());
+    // Expired hash locks are refunded by anyone, so that the vault does not depend on its authority.
+    let refunds_hash_lock = expired && transfer_data.is_hash_locked();
+    if !transfer_data.allows_crank() && !refunds_hash_lock {
+        msg!("Transfer does not allow being cranked.");
+        return Err(PimeError::CrankNotAllowed.into());
+    }
+    if !pubkey_eq(&transfer_data.destination, destination.key()) {
+        msg!("Supplied destination account does not match expected account");
+        return Err(PimeError::DestinationMismatch.into());
+    }
+    if now < transfer_data.created().saturating_add(transfer_data.warmup()) {
+        msg!("Warm-up period has not yet passed.");
+        return Err(PimeError::TransferWarmingUp.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let transfer_index_bytes = transfer_index.to_le_bytes();
+    let deposit_bump = &[transfer_data.deposit_bump()];
+    let deposit_signer_seeds = TransferData::get_deposit_signer_seeds(
+        authority.key(), 
+        &booked_destination,
+        &vault_index_bytes, 
+        &transfer_index_bytes, 
+        mint.key(), 
+        token_program.key(), 
+        deposit_bump
+    );
+
+    if expired {
+        // Refund the undrawn remainder into the vault.
+        let deposit_amount = TokenAccount::from_account_info(deposit)?.amount();
+        pinocchio_token::instructions::Transfer {
+            from: deposit,
+            to: vault,
+            authority: deposit,
+            amount: deposit_amount,
+        }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
+        vault_data_mut.record_unbooked(deposit_amount, now);
+    }
+    else {
+        // Claim links are only cranked back into the vault once expired.
+        if transfer_data.is_claim_link() {
+            msg!("Claim-link transfers can only be claimed by their claim key.");
+            return Err(PimeError::TransferIsClaimLink.into());
+        }
+        if transfer_data.is_hash_locked() {
+            msg!("Hash-locked transfers can only be executed with their preimage.");
+            return Err(PimeError::HashLocked.into());
+        }
+        // The crank can not create the destination, so it has to exist.
+        check(destination, "Destination").initialized()?.owned_by(token_program.key())?.writable()?;
+        let amount = transfer_data.drawable(now);
+        if amount == 0 {
+            msg!("Nothing has been released since the last draw.");
+            return Err(PimeError::DrawExceedsReleased.into());
+        }
+        pinocchio_token::instructions::Transfer {
+            from: deposit,
+            to: destination,
+            authority: deposit,
+            amount,
+        }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
+        transfer_data.record_drawn(amount);
+        vault_data_mut.record_executed(amount, now);
+        if let Some(entry) = transfers.entry_mut(transfer_index) {
+            entry.record_drawn(amount);
+        }
+        // Attach the booked memo, so that the payment can be reconciled.
+        emit_memo(transfer_data.memo(), remaining)?;
+    }
+
+    // A partially drawn transfer stays open until it is drained or expires.
+    if expired || transfer_data.is_drained() {
+        let crank_tip = transfer_data.crank_tip();
+        drop(transfer_data);
+        close_transfer(authority, transfer, deposit, &Signer::from(&deposit_signer_seeds), Some(crank), crank_tip)?;
+
+        // Decrement open transfers from the vault data.
+        let open_transfers = vault_data_mut.open_transfers().checked_sub(1).ok_or(PimeError::NoOpenTransfers)?;
+        vault_data_mut.set_open_transfers(open_transfers);
+        transfers.unregister(transfer_index);
+    }
+
+    ProgramResult::Ok(())
+}
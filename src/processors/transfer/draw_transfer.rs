@@ -0,0 +1,112 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
+
+use crate::{errors::PimeError, interface::instructions::draw_transfer_instruction::DrawTransferInstructionData, processors::shared::{close_transfer::close_transfer, constraints::check, memo::emit_memo}, states::{VaultAccount, VaultData, load_mut, transfer_data::TransferData}};
+
+/// Draws part of a partially drawable transfer to its destination, signed by the destination's owner.
+/// The transfer is closed once fully drawn, and its rent goes back to the authority.
+pub fn draw_transfer(accounts: &[AccountInfo], data: &DrawTransferInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let transfer_index = data.transfer_index();
+    let amount = data.amount();
+
+    let [recipient, authority, vault_data, transfer, deposit, destination, mint, token_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(recipient, "Recipient").signer()?;
+
+    // The authority does not sign, but receives the rent.
+    check(authority, "Authority").writable()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, transfers, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let mut transfer_data = load_mut::<TransferData>(transfer)?;
+    // The PDAs are derived from the destination the transfer was booked with.
+    let booked_destination = transfer_data.booked_destination;
+    let transfer_pda = TransferData::create_transfer_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.transfer_bump())?;
+    check(transfer, "Transfer").address(&transfer_pda)?.writable()?;
+
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    check(destination, "Destination").initialized()?.owned_by(token_program.key())?.writable()?;
+    if !pubkey_eq(&transfer_data.destination, destination.key()) {
+        msg!("Supplied destination account does not match expected account");
+        return Err(PimeError::DestinationMismatch.into());
+    }
+    if !pubkey_eq(TokenAccount::from_account_info(destination)?.owner(), recipient.key()) {
+        msg!("Only the owner of the destination may draw.");
+        return Err(PimeError::AuthorityError.into());
+    }
+
+    //      DATA SAFETY CHECKS
+
+    if !transfer_data.allows_partial() {
+        msg!("Transfer does not allow partial draws.");
+        return Err(PimeError::PartialDrawNotAllowed.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now < transfer_data.created().saturating_add(transfer_data.warmup()) {
+        msg!("Warm-up period has not yet passed.");
+        return Err(PimeError::TransferWarmingUp.into());
+    }
+    if now > transfer_data.created().saturating_add(transfer_data.validity()) {
+        msg!("Transfer has expired.");
+        return Err(PimeError::TransferExpired.into());
+    }
+    if amount == 0 || amount > transfer_data.drawable(now) {
+        msg!("Draw exceeds the released amount.");
+        return Err(PimeError::DrawExceedsReleased.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let transfer_index_bytes = transfer_index.to_le_bytes();
+    let deposit_bump = &[transfer_data.deposit_bump()];
+    let deposit_signer_seeds = TransferData::get_deposit_signer_seeds(
+        authority.key(), 
+        &booked_destination,
+        &vault_index_bytes, 
+        &transfer_index_bytes, 
+        mint.key(), 
+        token_program.key(), 
+        deposit_bump
+    );
+    pinocchio_token::instructions::Transfer {
+        from: deposit,
+        to: destination,
+        authority: deposit,
+        amount,
+    }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
+    transfer_data.record_drawn(amount);
+    vault_data_mut.record_executed(amount, now);
+    // Attach the booked memo, so that the payment can be reconciled.
+    emit_memo(transfer_data.memo(), remaining)?;
+
+    if transfer_data.is_drained() {
+        drop(transfer_data);
+        close_transfer(authority, transfer, deposit, &Signer::from(&deposit_signer_seeds), None, 0)?;
+
+        // Decrement open transfers from the vault data.
+        let open_transfers = vault_data_mut.open_transfers().checked_sub(1).ok_or(PimeError::NoOpenTransfers)?;
+        vault_data_mut.set_open_transfers(open_transfers);
+        transfers.unregister(transfer_index);
+    }
+    else if let Some(entry) = transfers.entry_mut(transfer_index) {
+        entry.record_drawn(amount);
+    }
+
+    ProgramResult::Ok(())
+}
@@ -1,173 +1,128 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::{Pubkey, find_program_address, pubkey_eq}, sysvars::{Sysvar, clock::Clock}};
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
 
-use crate::{errors::PimeError, interface::instructions::execute_transfer::ExecuteTransferInstructionData, states::{VaultData, from_bytes, transfer_data::TransferData}};
+use crate::{errors::PimeError, interface::instructions::execute_transfer::ExecuteTransferInstructionData, processors::shared::{close_transfer::close_transfer, constraints::check, create_destination_ata::create_destination_ata, memo::emit_memo}, states::{VaultAccount, VaultData, load_mut, transfer_data::TransferData}};
 
 /// Transfers assets from its booked vault to the received.
-pub fn execute_transfer(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
-    
-    //      Deserialize instruction data
-    if instruction_data.len() < size_of::<ExecuteTransferInstructionData>() - size_of::<u8>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    let (vault_index, transfer_index) = (
-        u64::from_le_bytes(unsafe {*(instruction_data.as_ptr() as *const [u8; size_of::<u64>()])}),
-        u64::from_le_bytes(unsafe {*(instruction_data.as_ptr().add(size_of::<u64>()) as *const [u8; size_of::<u64>()])})
-    );
+/// Partially drawable transfers draw what has been released so far, and are closed once fully drawn.
+/// Hash-locked transfers are executed by anyone revealing the preimage of their hash, without the
+/// authority's signature.
+pub fn execute_transfer(accounts: &[AccountInfo], data: &ExecuteTransferInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let transfer_index = data.transfer_index();
 
     let [authority, vault_data, transfer, deposit, destination, mint, token_program, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    if !authority.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, transfers, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
 
-    let vault_data_pda = VaultData::get_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(vault_data.key(), &vault_data_pda.0) {
-        msg!("Invalid Vault Data PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_data.lamports() == 0 {
-        msg!("Vault data is not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault_data.is_owned_by(&crate::ID) {
-        msg!("Vault data has illegal owner.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault_data.is_writable() {
-        msg!("Vault data is not mutable.");
-        return Err(ProgramError::Immutable);
-    }
+    let mut transfer_data = TransferData::load_upgraded(
+        transfer,
+        authority.key(),
+        destination.key(),
+        vault_index,
+        transfer_index,
+        mint.key(),
+        token_program.key()
+    )?;
+    // The PDAs are derived from the destination the transfer was booked with.
+    let booked_destination = transfer_data.booked_destination;
+    let transfer_pda = TransferData::create_transfer_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.transfer_bump())?;
+    check(transfer, "Transfer").address(&transfer_pda)?;
 
-    let transfer_pda = TransferData::get_transfer_pda(authority.key(), destination.key(), vault_index, transfer_index, mint.key(), token_program.key());
-    if !pubkey_eq(transfer.key(), &transfer_pda.0) {
-        msg!("Incorrect Transfer PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if transfer.lamports() == 0 {
-        msg!("Transfer is not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !transfer.is_owned_by(&crate::ID) {
-        msg!("Transfer is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    //      Check that target account is the account the deposit should go to
-    if transfer.data_len() > size_of::<TransferData>() {
-        msg!("Transfer has invalid account data.");
-        return Err(ProgramError::AccountDataTooSmall);
-    }
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?;
 
-    let deposit_pda = TransferData::get_deposit_pda(authority.key(), destination.key(), vault_index, transfer_index, mint.key(), token_program.key());
-    if !pubkey_eq(deposit.key(), &deposit_pda.0) {
-        msg!("Incorrect Deposit PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if deposit.lamports() == 0 {
-        msg!("Deposit it not created. Has a transfer been booked?");
-        return Err(ProgramError::UninitializedAccount);
+    // The preimage replaces the authority's signature, which still funds the destination ATA if it signs.
+    if transfer_data.is_hash_locked() {
+        check(authority, "Authority").writable()?;
+        transfer_data.check_preimage(data.preimage())?;
     }
-    if !deposit.is_owned_by(token_program.key()) {
-        msg!("Deposit account is not owned by the supplied token program");
-        return Err(ProgramError::InvalidAccountOwner);
+    else {
+        check(authority, "Authority").signer()?;
     }
 
-    if destination.lamports() == 0 {
-        let [system_program, ata_owner, a_token, _remainder @ .. ] = remaining else {
-            msg!("Requires system program, ata owner, and associated token program.");
-            return Err(ProgramError::NotEnoughAccountKeys);
-        };
-        if !pubkey_eq(a_token.key(), &pinocchio_associated_token_account::ID) {
-            msg!("Associated token program is incorrect.");
-            return Err(ProgramError::IllegalOwner);
-        }
-        let ata = find_program_address(&[
-            ata_owner.key(),
-            token_program.key(),
-            mint.key(),
-        ], a_token.key());
-        if !pubkey_eq(destination.key(), &ata.0) {
-            msg!("Destination ATA is not derived from the provided owner.");
-            return Err(PimeError::DestinationMismatch.into());
-        }
-        pinocchio_associated_token_account::instructions::Create{
-            funding_account: authority,
-            account: destination,
-            wallet: ata_owner,
-            mint,
-            system_program,
-            token_program
-        }.invoke()?;
+    if transfer_data.is_claim_link() {
+        msg!("Claim-link transfers can only be claimed by their claim key.");
+        return Err(PimeError::TransferIsClaimLink.into());
     }
-    if !destination.is_owned_by(token_program.key()) {
-        msg!("Destination account is not owned by the supplied token program");
-        return Err(ProgramError::InvalidAccountOwner);
+    if transfer_data.is_swap() {
+        msg!("Swaps can only be settled together with their counterpart.");
+        return Err(PimeError::TransferIsSwap.into());
     }
+
+    create_destination_ata(authority, destination, mint, token_program, remaining)?;
+    check(destination, "Destination").owned_by(token_program.key())?;
     
-    if !mint.is_owned_by(token_program.key()) {
-        msg!("Mint now owned by the supplied token program.");
-        return Err(ProgramError::InvalidAccountOwner);
-    }
+    check(mint, "Mint").owned_by(token_program.key())?;
 
     //      Data safety checks
-    // SAFETY data is not borrowed earlier and of type Transmutable
-    let transfer_data = unsafe { from_bytes::<TransferData>(transfer.borrow_data_unchecked())? } ;
+    //      Check that target account is the account the deposit should go to
     if !pubkey_eq(&transfer_data.destination, destination.key()) {
         msg!("Supplied destination account does not match expected account");
         return Err(PimeError::DestinationMismatch.into());
     }
     let now = Clock::get()?.unix_timestamp;
-    if now < transfer_data.created() + transfer_data.warmup() {
+    if now < transfer_data.created().saturating_add(transfer_data.warmup()) {
         msg!("Warm-up period has not yet passed.");
         return Err(PimeError::TransferWarmingUp.into());
     }
-    if now > transfer_data.created() + transfer_data.validity() {
+    if now > transfer_data.created().saturating_add(transfer_data.validity()) {
         msg!("Transfer has expired. Close this transfer and create a new one.");
         return Err(PimeError::TransferExpired.into());
     }
     //      Transfer from deposit to target account
     let vault_index_bytes = vault_index.to_le_bytes();
     let transfer_index_bytes = transfer_index.to_le_bytes();
-    let deposit_bump = &[deposit_pda.1];
+    let deposit_bump = &[transfer_data.deposit_bump()];
     let deposit_signer_seeds = TransferData::get_deposit_signer_seeds(
         authority.key(), 
-        destination.key(),
+        &booked_destination,
         &vault_index_bytes, 
         &transfer_index_bytes, 
         mint.key(), 
         token_program.key(), 
         deposit_bump
     );
+    let amount = transfer_data.drawable(now);
+    if amount == 0 {
+        msg!("Nothing has been released since the last draw.");
+        return Err(PimeError::DrawExceedsReleased.into());
+    }
     pinocchio_token::instructions::Transfer {
         from: deposit,
         to: destination,
         authority: deposit,
-        amount: transfer_data.amount(),
+        amount,
     }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
-
-    //      Close deposit
-    pinocchio_token::instructions::CloseAccount {
-        account: deposit,
-        destination: authority,
-        authority: deposit,
-    }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
-
-    //      Close transfer
-    unsafe {
-        // Moves the lamports to the destination account.
-        //
-        // Note: This is safe since the runtime checks for balanced instructions
-        // before and after each CPI and instruction, and the total lamports
-        // supply is bound to `u64::MAX`.
-        *authority.borrow_mut_lamports_unchecked() += transfer.lamports();
-        transfer.close_unchecked();
-    };
-
-    // Decrement open transfers from the vault data.
-    // SAFETY: Only mutable here. Vault data bytes are a valid representation of VaultData
-    let vault_data_mut = unsafe { &mut *(vault_data.data_ptr() as *mut VaultData) };
-    vault_data_mut.set_open_transfers(vault_data_mut.open_transfers() - 1);
+    transfer_data.record_drawn(amount);
+    vault_data_mut.record_executed(amount, now);
+    // Attach the booked memo, so that the payment can be reconciled.
+    emit_memo(transfer_data.memo(), remaining)?;
+
+    //      Close deposit and transfer once fully drawn
+    if transfer_data.is_drained() {
+        close_transfer(authority, transfer, deposit, &Signer::from(&deposit_signer_seeds), None, 0)?;
+
+        // Decrement open transfers from the vault data.
+        let open_transfers = vault_data_mut.open_transfers().checked_sub(1).ok_or(PimeError::NoOpenTransfers)?;
+        vault_data_mut.set_open_transfers(open_transfers);
+        transfers.unregister(transfer_index);
+    }
+    else {
+        // Version 0 transfers are not streamed, and drained at once, so only the current layout
+        // is written back.
+        *load_mut::<TransferData>(transfer)? = transfer_data;
+        if let Some(entry) = transfers.entry_mut(transfer_index) {
+            entry.record_drawn(amount);
+        }
+    }
     
     ProgramResult::Ok(())
 }
@@ -1,3 +1,8 @@
 pub(crate) mod book_transfer;
 pub(crate) mod execute_transfer;
 pub(crate) mod unbook_transfer;
+pub(crate) mod crank_transfer;
+pub(crate) mod draw_transfer;
+pub(crate) mod amend_transfer;
+pub(crate) mod claim_transfer;
+pub(crate) mod settle_swap;
@@ -0,0 +1,123 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::{Clock, UnixTimestamp}}};
+
+use crate::{errors::PimeError, interface::instructions::settle_swap_instruction::SettleSwapInstructionData, processors::shared::{close_transfer::close_transfer, constraints::check, memo::emit_memo}, states::{VaultAccount, VaultData, load, load_mut, transfer_data::TransferData}};
+
+/// Accounts of one side of a swap.
+const SWAP_LEG_ACCOUNTS: usize = 7;
+
+/// Settles both sides of a swap, each transfer paying its destination in full and closing.
+///
+/// Both transfers have to be booked as swaps with each other, for each other's amount. As the
+/// instruction is atomic, either both sides settle or neither does.
+pub fn settle_swap(accounts: &[AccountInfo], data: &SettleSwapInstructionData) -> ProgramResult {
+
+    let Some((leg, rest)) = accounts.split_first_chunk::<SWAP_LEG_ACCOUNTS>() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let Some((counterpart_leg, remaining)) = rest.split_first_chunk::<SWAP_LEG_ACCOUNTS>() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      DATA SAFETY CHECKS
+
+    // Each side holds the other to the terms it was booked with.
+    {
+        let (transfer, counterpart_transfer) = (&leg[2], &counterpart_leg[2]);
+        let transfer_data = load::<TransferData>(transfer)?;
+        let counterpart_data = load::<TransferData>(counterpart_transfer)?;
+        if !transfer_data.swaps_with(counterpart_transfer.key(), &counterpart_data)
+            || !counterpart_data.swaps_with(transfer.key(), &transfer_data) {
+            msg!("Transfers are not booked as swaps with each other.");
+            return Err(PimeError::SwapMismatch.into());
+        }
+    }
+
+    //      BUSINESS LOGIC
+
+    let now = Clock::get()?.unix_timestamp;
+    settle_leg(leg, data.vault_index(), data.transfer_index(), now, remaining)?;
+    settle_leg(counterpart_leg, data.counterpart_vault_index(), data.counterpart_transfer_index(), now, remaining)?;
+
+    ProgramResult::Ok(())
+}
+
+/// Verifies one side of the swap, and transfers its whole amount to its destination before closing it.
+fn settle_leg(leg: &[AccountInfo; SWAP_LEG_ACCOUNTS], vault_index: u64, transfer_index: u64, now: UnixTimestamp, remaining: &[AccountInfo]) -> ProgramResult {
+
+    let [authority, vault_data, transfer, deposit, destination, mint, token_program] = leg;
+
+    //      ACCOUNT SAFETY CHECKS
+
+    // The authority does not sign, but receives the rent.
+    check(authority, "Authority").writable()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, transfers, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let mut transfer_data = load_mut::<TransferData>(transfer)?;
+    // Swaps can not be amended, so the booked destination is the destination.
+    let booked_destination = transfer_data.booked_destination;
+    let transfer_pda = TransferData::create_transfer_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.transfer_bump())?;
+    check(transfer, "Transfer").address(&transfer_pda)?.writable()?;
+
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), &booked_destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    if !pubkey_eq(&transfer_data.destination, destination.key()) {
+        msg!("Supplied destination account does not match expected account");
+        return Err(PimeError::DestinationMismatch.into());
+    }
+    check(destination, "Destination").owned_by(token_program.key())?.writable()?;
+
+    //      DATA SAFETY CHECKS
+
+    if now < transfer_data.created().saturating_add(transfer_data.warmup()) {
+        msg!("Warm-up period has not yet passed.");
+        return Err(PimeError::TransferWarmingUp.into());
+    }
+    if now > transfer_data.created().saturating_add(transfer_data.validity()) {
+        msg!("Transfer has expired, and can only be unbooked.");
+        return Err(PimeError::TransferExpired.into());
+    }
+
+    //      BUSINESS LOGIC
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let transfer_index_bytes = transfer_index.to_le_bytes();
+    let deposit_bump = &[transfer_data.deposit_bump()];
+    let deposit_signer_seeds = TransferData::get_deposit_signer_seeds(
+        authority.key(),
+        &booked_destination,
+        &vault_index_bytes,
+        &transfer_index_bytes,
+        mint.key(),
+        token_program.key(),
+        deposit_bump
+    );
+    let amount = transfer_data.amount();
+    pinocchio_token::instructions::Transfer {
+        from: deposit,
+        to: destination,
+        authority: deposit,
+        amount,
+    }.invoke_signed(&[Signer::from(&deposit_signer_seeds)])?;
+    transfer_data.record_drawn(amount);
+    vault_data_mut.record_executed(amount, now);
+    emit_memo(transfer_data.memo(), remaining)?;
+
+    drop(transfer_data);
+    close_transfer(authority, transfer, deposit, &Signer::from(&deposit_signer_seeds), None, 0)?;
+
+    // Decrement open transfers from the vault data.
+    let open_transfers = vault_data_mut.open_transfers().checked_sub(1).ok_or(PimeError::NoOpenTransfers)?;
+    vault_data_mut.set_open_transfers(open_transfers);
+    transfers.unregister(transfer_index);
+
+    Ok(())
+}
@@ -1,103 +1,61 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}};
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
 use pinocchio_token::state::TokenAccount;
 
-use crate::{errors::PimeError, interface::instructions::unbook_transfer_instruction::UnbookTransferInstructionData, states::{VaultData, transfer_data::TransferData}};
+use crate::{errors::PimeError, interface::instructions::unbook_transfer_instruction::UnbookTransferInstructionData, processors::shared::{close_transfer::close_transfer, constraints::check}, states::{VaultAccount, VaultData, load_mut, transfer_data::TransferData}};
 
 /// Closes a booked transfer account.
 /// If the booking was never proceeded, the assets are transferred back to its owner.
-pub fn unbook_transfer(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
-    
-    // Deserialize instruction data
-    let (vault_index, transfer_index, destination) = if instruction_data.len() < size_of::<UnbookTransferInstructionData>() - size_of::<u8>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    else {
-        (
-            u64::from_le_bytes( unsafe { *(instruction_data.as_ptr() as *const [u8; size_of::<u64>()]) }),
-            u64::from_le_bytes( unsafe { *(instruction_data.as_ptr().add(size_of::<u64>()) as *const [u8; size_of::<u64>()]) }),
-            unsafe {&*(instruction_data.as_ptr().add(2 * size_of::<u64>()) as *const Pubkey)}
-        )
-    };
+/// Hash-locked transfers can only be unbooked once expired, so that their counterparty can rely on them.
+pub fn unbook_transfer(accounts: &[AccountInfo], data: &UnbookTransferInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let transfer_index = data.transfer_index();
+    let destination = &data.destination;
 
     // Safety checks on accounts
     let [authority, vault, vault_data, transfer, deposit, mint, token_program, _remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    if !authority.is_signer() {
-        msg!("Authority must be signer.");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(authority, "Authority").signer()?;
 
-    let vault_pda = VaultData::get_vault_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(&vault_pda.0, vault.key()) {
-        msg!("Vault PDA incorrect.");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault.lamports() == 0 {
-        msg!("Vault is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault.is_owned_by(token_program.key()) {
-        msg!("Vault is not owned by the supplied token program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault.is_writable() {
-        msg!("Vault is not writeable.");
-        return Err(ProgramError::Immutable);
-    }
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, transfers, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
 
-    let vault_data_pda = VaultData::get_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(&vault_data_pda.0, vault_data.key()) {
-        msg!("Vault data PDA incorrect.");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_data.lamports() == 0 {
-        msg!("Vault data is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault_data.is_owned_by(&crate::ID) {
-        msg!("Vault data is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault_data.is_writable() {
-        msg!("Vault data is not writeable.");
-        return Err(ProgramError::Immutable);
-    }
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
 
-    let transfer_pda = TransferData::get_transfer_pda(
+    let transfer_data = TransferData::load_upgraded(
+        transfer,
+        authority.key(),
+        destination,
+        vault_index,
+        transfer_index,
+        mint.key(),
+        token_program.key()
+    )?;
+    let transfer_pda = TransferData::create_transfer_pda(
         authority.key(), 
         destination, 
         vault_index, 
         transfer_index, 
         mint.key(), 
-        token_program.key()
-    );
-    if !pubkey_eq(&transfer_pda.0, transfer.key()) {
-        msg!("Transfer PDA incorrect.");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if transfer.lamports() == 0 {
-        msg!("Transfer is not initilized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !transfer.is_owned_by(&crate::ID) {
-        msg!("Transfer is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
-    }
+        token_program.key(),
+        transfer_data.transfer_bump(),
+    )?;
+    check(transfer, "Transfer").address(&transfer_pda)?;
 
-    let deposit_pda = TransferData::get_deposit_pda(authority.key(), destination, vault_index, transfer_index, mint.key(), token_program.key());
-    if !pubkey_eq(&deposit_pda.0, deposit.key()) {
-        msg!("Deposit PDA incorrect.");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if deposit.lamports() == 0 {
-        msg!("Deposit is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !deposit.is_owned_by(token_program.key()){
-        msg!("The deposit is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
+    let deposit_pda = TransferData::create_deposit_pda(authority.key(), destination, vault_index, transfer_index, mint.key(), token_program.key(), transfer_data.deposit_bump())?;
+    check(deposit, "Deposit").address(&deposit_pda)?.initialized()?.owned_by(token_program.key())?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if transfer_data.is_hash_locked() && now <= transfer_data.created().saturating_add(transfer_data.validity()) {
+        msg!("Hash-locked transfers can only be refunded once expired.");
+        return Err(PimeError::HashLocked.into());
     }
 
     if !pubkey_eq(token_program.key(), &pinocchio_token::ID) {
@@ -105,20 +63,17 @@ pub fn unbook_transfer(accounts: &[AccountInfo], instruction_data: &[u8]) -> Pro
         return Err(PimeError::UnsupportedTokenProgram.into());
     }
 
-    if !mint.is_owned_by(token_program.key()) {
-        msg!("Mint is not owned by the provided token program.");
-        return Err(ProgramError::InvalidAccountOwner);
-    }
+    check(mint, "Mint").owned_by(token_program.key())?;
 
     if vault.data_len() != TokenAccount::LEN {
         msg!("Vault does not contain enough data. Is it really a token account?");
-        return Err(ProgramError::AccountDataTooSmall);
+        return Err(PimeError::InvalidTokenAccount.into());
     }
-    let vault_acc = unsafe {&*(vault.data_ptr() as *const TokenAccount)};
+    let deposit_amount = TokenAccount::from_account_info(deposit)?.amount();
 
     let vault_index_bytes = vault_index.to_le_bytes();
     let transfer_index_bytes = transfer_index.to_le_bytes();
-    let deposit_bump = &[deposit_pda.1];
+    let deposit_bump = &[transfer_data.deposit_bump()];
     let deposit_seeds = TransferData::get_deposit_signer_seeds(
         authority.key(), 
         destination,
@@ -137,27 +92,17 @@ pub fn unbook_transfer(accounts: &[AccountInfo], instruction_data: &[u8]) -> Pro
         from: deposit,
         to: vault,
         authority: deposit,
-        amount: vault_acc.amount()
+        amount: deposit_amount
     }.invoke_signed(&[Signer::from(&deposit_seeds)])?;
     
-    // Close the deposit account
-    pinocchio_token::instructions::CloseAccount {
-        account: deposit,
-        destination: authority,
-        authority: deposit,
-    }.invoke_signed(&[Signer::from(&deposit_seeds)])?;
-    
-    // Close the transfer account
-    // SAFETY: Is not borrowed earlier. Transfer account is empty.
-    unsafe {
-        *authority.borrow_mut_lamports_unchecked() += transfer.lamports();
-        transfer.close_unchecked();
-    }
+    // Close the deposit and transfer accounts
+    close_transfer(authority, transfer, deposit, &Signer::from(&deposit_seeds), None, 0)?;
 
     // Decrement open transfers from the vault data.
-    // SAFETY: Only mutable here. Vault data bytes are a valid representation of VaultData
-    let vault_data_mut = unsafe { &mut *(vault_data.data_ptr() as *mut VaultData) };
-    vault_data_mut.set_open_transfers(vault_data_mut.open_transfers() - 1);
+    let open_transfers = vault_data_account.open_transfers().checked_sub(1).ok_or(PimeError::NoOpenTransfers)?;
+    vault_data_account.set_open_transfers(open_transfers);
+    transfers.unregister(transfer_index);
+    vault_data_account.record_unbooked(deposit_amount, now);
     
     ProgramResult::Ok(())
 }
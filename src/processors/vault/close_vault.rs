@@ -1,83 +1,36 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::pubkey_eq};
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError};
 use pinocchio_token::state::TokenAccount;
 
-use crate::{errors::PimeError, interface::instructions::close_vault_instruction::CloseVaultInstructionData, states::{Transmutable, VaultData, from_bytes}};
+use crate::{errors::PimeError, interface::instructions::close_vault_instruction::CloseVaultInstructionData, processors::shared::{self, constraints::check}, states::{VaultData, load, metadata_data::MetadataData}};
 
-pub fn process_close_vault(accounts: &[AccountInfo], inst_data: &[u8]) -> ProgramResult {
+pub fn process_close_vault(accounts: &[AccountInfo], data: &CloseVaultInstructionData) -> ProgramResult {
     
-    //      INSTRUCTION DESERIALAZATION
-
-    let vault_index = if inst_data.len() < CloseVaultInstructionData::LEN - size_of::<u8>() {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    else {
-        u64::from_le_bytes(unsafe { *(inst_data.as_ptr() as *const [u8; size_of::<u64>()]) }) 
-    };
+    let vault_index = data.vault_index();
     
-    let [authority_info, vault_info, vault_data_info, mint_info, token_program_info, _remaining @ ..] = accounts else {
+    let [authority_info, vault_info, vault_data_info, mint_info, token_program_info, registry_info, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     //      ACCOUNT SAFETY CHECKS
 
-    if !authority_info.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(authority_info, "Authority").signer()?;
 
-    if !mint_info.is_owned_by(token_program_info.key()) {
-        msg!("Mint is not owned by the supplied token program.");
-        return Err(PimeError::UnsupportedTokenProgram.into());
-    }
+    check(mint_info, "Mint").owned_by(token_program_info.key())?;
 
-    let vault_data_pda = VaultData::get_vault_data_pda(authority_info.key(), vault_index, mint_info.key(), token_program_info.key());
-    if !pubkey_eq(vault_data_info.key(), &vault_data_pda.0) {
-        msg!("Incorrect Vault data PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_data_info.lamports() == 0 {
-        msg!("Vault data is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault_data_info.is_owned_by(&crate::ID) {
-        msg!("Vault data is not owned by this program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault_data_info.is_writable() {
-        msg!("Vault data is not writable.");
-        return Err(ProgramError::Immutable);
-    }
+    check(vault_data_info, "Vault data").writable()?;
+    let vault_data = load::<VaultData>(vault_data_info)?;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority_info.key(), vault_index, mint_info.key(), token_program_info.key(), vault_data.vault_data_bump())?;
+    check(vault_data_info, "Vault data").address(&vault_data_pda)?;
 
-    let vault_pda = VaultData::get_vault_pda(authority_info.key(), vault_index, mint_info.key(), token_program_info.key());
-    if !pubkey_eq(vault_info.key(), &vault_pda.0) {
-        msg!("Incorrect Vault PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_info.lamports() == 0 {
-        msg!("Vault is not initialized.");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    if !vault_info.is_owned_by(token_program_info.key()) {
-        msg!("Vault is not owned by the supplied token program.");
-        return Err(ProgramError::IllegalOwner);
-    }
-    if !vault_info.is_writable() {
-        msg!("Vault is not writable.");
-        return Err(ProgramError::Immutable);
-    }
+    let vault_pda = VaultData::create_vault_pda(authority_info.key(), vault_index, mint_info.key(), token_program_info.key(), vault_data.vault_bump())?;
+    check(vault_info, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program_info.key())?.writable()?;
 
     //      BUSINESS LOGIC
 
-    // Make sure vault has no open transfers
-    let vault_data = if vault_data_info.data_len() < VaultData::LEN {
-        msg!("Vault data has insufficient data.");
-        return Err(ProgramError::AccountDataTooSmall);
-    }
-    else {
-        // SAFETY: Vault data is a valid representation and is of enough bytes.
-        unsafe { &*(vault_data_info.data_ptr() as *const VaultData) }
-    };
-    if vault_data.open_transfers() != 0 {
-        msg!("The vault has open transfers.");
+    // Make sure vault has no open transfers or pledges, whose refunds are paid into the vault.
+    if vault_data.open_transfers() != 0 || vault_data.in_flight_amount() != 0 {
+        msg!("The vault has open transfers or pledges.");
         return Err(PimeError::VaultHasOpenTransfers.into());
     }
 
@@ -88,9 +41,22 @@ pub fn process_close_vault(accounts: &[AccountInfo], inst_data: &[u8]) -> Progra
         return Err(PimeError::VaultIsNotEmpty.into());
     }
 
+    // The vault's metadata has to be closed along with it.
+    let metadata_info = if vault_data.has_metadata() {
+        let [metadata_info, ..] = remaining else {
+            msg!("The vault has metadata which must be closed with it.");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let metadata_pda = MetadataData::get_metadata_pda(authority_info.key(), vault_index, mint_info.key(), token_program_info.key());
+        check(metadata_info, "Metadata").address(&metadata_pda.0)?.writable()?;
+        load::<MetadataData>(metadata_info)?;
+        Some(metadata_info)
+    }
+    else { None };
+
     // close vault account
     let vault_index_bytes = vault_index.to_le_bytes();
-    let vault_bump = &[vault_pda.1];
+    let vault_bump = &[vault_data.vault_bump()];
     let vault_signer = VaultData::get_vault_signer_seeds(
         authority_info.key(),
         &vault_index_bytes, 
@@ -104,11 +70,23 @@ pub fn process_close_vault(accounts: &[AccountInfo], inst_data: &[u8]) -> Progra
     }.invoke_signed(&[Signer::from(&vault_signer)])?;
 
     // close vault data account
-    // SAFETY: Is not borrowed earlier. Transfer account is empty.
+    drop(vault_data);
+    // SAFETY: Vault data is no longer borrowed.
     unsafe {
         *authority_info.borrow_mut_lamports_unchecked() += vault_data_info.lamports();
         vault_data_info.close_unchecked();
     }
 
+    // close metadata account
+    if let Some(metadata_info) = metadata_info {
+        // SAFETY: Is not borrowed earlier.
+        unsafe {
+            *authority_info.borrow_mut_lamports_unchecked() += metadata_info.lamports();
+            metadata_info.close_unchecked();
+        }
+    }
+
+    shared::registry::unregister_vault(authority_info, registry_info, vault_index, mint_info.key(), token_program_info.key())?;
+
     ProgramResult::Ok(())
 }
@@ -1,67 +1,43 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::pubkey_eq, sysvars::clock::UnixTimestamp};
-use crate::{errors::PimeError, interface::instructions::create_vault_instruction::CreateVaultInstructionData, processors::shared, states::VaultData};
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError};
+use crate::{errors::PimeError, interface::instructions::create_vault_instruction::CreateVaultInstructionData, processors::shared::{self, constraints::check, create_account::is_uninitialized}, states::VaultData};
 
 /// Create new vault given a vault index, authority, mint (with corresponding token program), and
 /// settings.
-pub fn process_create_vault(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+pub fn process_create_vault(accounts: &[AccountInfo], data: &CreateVaultInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let timeframe = data.timeframe();
+    let max_transactions = data.max_transactions();
+    let max_amount = data.max_amount();
+    let withdraw_cooldown = data.withdraw_cooldown();
+    let withdraw_warmup = data.withdraw_warmup();
 
     // Validate instruction data
-    let (
-        vault_index, 
-        timeframe, 
-        max_transactions, 
-        max_amount, 
-        allows_transfers,
-        transfer_min_warmup, 
-        tranfer_max_window, 
-    ) = if instruction_data.len() < size_of::<CreateVaultInstructionData>() - size_of::<u8>() {
-        msg!("Not enough instruction data. Did you include all fields?");
-        return Err(ProgramError::InvalidInstructionData);
-    }
-    else {
-        (
-            u64::from_le_bytes(unsafe { *(instruction_data.as_ptr() as *const [u8; size_of::<u64>()]) }),
-            i64::from_le_bytes(unsafe { *(instruction_data.as_ptr().add(size_of::<u64>()) as *const [u8; size_of::<u64>()]) }),
-            u64::from_le_bytes(unsafe { *(instruction_data.as_ptr().add(size_of::<u64>() * 2) as *const [u8; size_of::<u64>()]) }),
-            u64::from_le_bytes(unsafe { *(instruction_data.as_ptr().add(size_of::<u64>() * 3) as *const [u8; size_of::<u64>()]) }),
-            unsafe { &*(instruction_data.as_ptr().add(size_of::<u64>() * 3 + size_of::<u8>()) as *const u8) },
-            UnixTimestamp::from_le_bytes(unsafe { *(instruction_data.as_ptr().add(size_of::<u64>() * 4 + size_of::<u8>()) as *const [u8; size_of::<UnixTimestamp>()]) }),
-            UnixTimestamp::from_le_bytes(unsafe { *(instruction_data.as_ptr().add(size_of::<u64>() * 5 + size_of::<u8>()) as *const [u8; size_of::<UnixTimestamp>()]) }),
-        )
-    };
     if timeframe < 0 {
         msg!("Timeframe must be > 0");
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(PimeError::InvalidTimeframe.into());
+    }
+    if withdraw_cooldown < 0 {
+        msg!("Withdraw cool-down must be positive.");
+        return Err(PimeError::InvalidWithdrawCooldown.into());
+    }
+    if withdraw_warmup < 0 {
+        msg!("Withdraw warm-up must be positive.");
+        return Err(PimeError::InvalidWithdrawWarmup.into());
     }
     
-    let [authority, vault_data, vault, mint, token_program, _remaining @ ..] = accounts else {
+    let [authority, vault_data, vault, mint, token_program, _system_program, registry, _remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     //      Validate account infos
 
-    if !authority.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(authority, "Authority").signer()?;
 
-    if !mint.is_owned_by(token_program.key()) {
-        msg!("Mint is now owned by supplied token program.");
-        return Err(ProgramError::InvalidAccountOwner);
-    }
+    check(mint, "Mint").owned_by(token_program.key())?;
 
     let vault_data_pda = VaultData::get_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(&vault_data_pda.0, vault_data.key()) {
-        msg!("Vault data PDA incorrect");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if vault_data.lamports() != 0 {
-        msg!("Vault data is already initialized.");
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
-    if !vault_data.is_writable() {
-        msg!("Vault data is not mutable.");
-        return Err(ProgramError::Immutable);
-    }
+    check(vault_data, "Vault data").address(&vault_data_pda.0)?.uninitialized()?.writable()?;
 
     let vault_data_pda_bump = &[vault_data_pda.1]; // prevent dropping
     let vault_index_bytes = vault_index.to_le_bytes();
@@ -71,26 +47,28 @@ pub fn process_create_vault(accounts: &[AccountInfo], instruction_data: &[u8]) -
         mint.key(), 
         token_program.key(), 
         vault_data_pda_bump);
+    let vault_pda = VaultData::get_vault_pda(authority.key(), vault_index, mint.key(), token_program.key());
     shared::create_vault_data_account::process_create_vault_data_account(
         authority,
         vault_data,
+        mint.key(),
+        token_program.key(),
+        vault_index,
+        vault_data_pda.1,
+        vault_pda.1,
         max_transactions,
         timeframe,
         max_amount,
-        *allows_transfers,
-        transfer_min_warmup,
-        tranfer_max_window,
+        data.allows_transfers() as u8,
+        data.transfer_min_warmup(),
+        data.transfer_max_window(),
+        withdraw_cooldown,
+        withdraw_warmup,
         &Signer::from(&vault_data_signer_seeds),
     )?;
     
-    let vault_pda = VaultData::get_vault_pda(authority.key(), vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(&vault_pda.0, vault.key()) {
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if !vault.is_writable() {
-        return Err(ProgramError::Immutable);
-    }
-    if vault.lamports() == 0 { // If account has not been initialized, init it
+    check(vault, "Vault").address(&vault_pda.0)?.writable()?;
+    if is_uninitialized(vault) { // If account has not been initialized, init it
         let vault_bump = &[vault_pda.1];
         let vault_seeds = VaultData::get_vault_signer_seeds(
             authority.key(), 
@@ -106,11 +84,13 @@ pub fn process_create_vault(accounts: &[AccountInfo], instruction_data: &[u8]) -
             &Signer::from(&vault_seeds),
         )?;
     }
-    else if !vault.is_owned_by(&pinocchio_token::ID) { // Force vault to be owned by token program
-        msg!("Be aware, the vault is not owned by the token program. This may be inteded.");
+    else {
+        // Force vault to be owned by token program
         // (TODO fix so that is supports other programs, but with safety (pre init attacks etc)
-        return Err(PimeError::UnsupportedTokenProgram.into());
+        check(vault, "Vault").owned_by(&pinocchio_token::ID)?;
     }
 
+    shared::registry::register_vault(authority, registry, vault_index, mint.key(), token_program.key())?;
+
     ProgramResult::Ok(())
 }
@@ -1,76 +1,96 @@
-use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, msg, program_error::ProgramError, pubkey::{Pubkey, pubkey_eq}};
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
 
-use crate::{errors::PimeError, interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData, processors, states::VaultData};
+use crate::{errors::PimeError, interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData, processors::{self, shared::{constraints::check, create_account::{create_pda_account, is_uninitialized}}}, states::{DepositPolicy, Transmutable, VaultAccount, VaultData, as_bytes, depositor_data::DepositorData, load_mut, lot_data::LotData, lot_schedule::LotEntry}};
 
-pub fn process_deposit_to_vault(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+pub fn process_deposit_to_vault(accounts: &[AccountInfo], data: &DepositToVaultInstructionData) -> ProgramResult {
 
-    // Extract instruction data
-    let (vault_owner, vault_index, amount) = 
-    // instruction size - discriminator
-    if instruction_data.len() >= size_of::<DepositToVaultInstructionData>() - size_of::<u8>() { 
-        (
-            unsafe { &*(instruction_data.as_ptr() as *const Pubkey) },
-            u64::from_le_bytes( unsafe { *(instruction_data.as_ptr().add(size_of::<Pubkey>()) as *const [u8; size_of::<u64>()]) } ),
-            u64::from_le_bytes( unsafe { *(instruction_data.as_ptr().add(size_of::<Pubkey>() + size_of::<u64>()) as *const [u8; size_of::<u64>()]) } ),
-        )
-    }
-    else {
-        return Err(ProgramError::InvalidInstructionData);
-    };
+    let vault_owner = &data.vault_owner();
+    let vault_index = data.vault_index();
+    let amount = data.amount();
+    let lot_index = data.lot_index();
+    let maturity = data.maturity();
 
     // Extract accounts
-    let [from_authority, from, vault, mint, token_program, _remaining @ ..] = accounts else {
+    let [from_authority, from, vault_data, vault, mint, token_program, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     //      Validate account infos
 
-    if !from_authority.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    check(from_authority, "Depositor").signer()?;
 
     if !pubkey_eq(token_program.key(), &pinocchio_token::ID) {
         return Err(PimeError::UnsupportedTokenProgram.into());
     } 
 
-    if !mint.is_owned_by(token_program.key()) {
-        msg!("Mint is now owned by supplied token program.");
-        return Err(ProgramError::InvalidAccountOwner);
-    }
+    check(mint, "Mint").owned_by(token_program.key())?;
 
-    let vault_pda = VaultData::get_vault_pda(vault_owner, vault_index, mint.key(), token_program.key());
-    if !pubkey_eq(&vault_pda.0, vault.key()) {
-        msg!("Incorrect vault PDA");
-        return Err(PimeError::IncorrectPDA.into());
-    }
-    if !vault.is_writable() {
-        msg!("Vault needs to be writeable.");
-        return Err(ProgramError::Immutable);
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, lots, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(vault_owner, vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    // Vaults are only created by their authority, see process_create_vault.
+    let vault_pda = VaultData::create_vault_pda(vault_owner, vault_index, mint.key(), token_program.key(), vault_data_mut.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.writable()?.initialized()?.owned_by(&pinocchio_token::ID)?;
+    let vault_pre_amount = TokenAccount::from_account_info(vault)?.amount();
+
+    //      Deposit policy
+
+    if amount < vault_data_mut.min_deposit() {
+        msg!("Deposit is below the vault's minimum deposit.");
+        return Err(PimeError::DepositBelowMinimum.into());
     }
-    if vault.lamports() == 0 {
-        if !pubkey_eq(token_program.key(), &pinocchio_token::ID) {
-            msg!("Unsupported token program.");
-            return Err(PimeError::UnsupportedTokenProgram.into());
+
+    let now = Clock::get()?.unix_timestamp;
+
+    // Deposits from anyone but the authority are recorded.
+    if !pubkey_eq(from_authority.key(), vault_owner) {
+        let policy = vault_data_mut.deposit_policy()?;
+        if policy == DepositPolicy::AuthorityOnly {
+            msg!("Only the vault's authority may deposit.");
+            return Err(PimeError::DepositorNotAllowed.into());
         }
-        let vault_index_bytes = vault_index.to_le_bytes();
-        let vault_bump = &[vault_pda.1];
-        let vault_signer_seeds = VaultData::get_vault_signer_seeds(
-            vault_owner, 
-            &vault_index_bytes, 
-            mint.key(), 
-            token_program.key(), 
-            vault_bump);
-        processors::shared::create_vault_account::create_vault_account(
-            /* payer */ from_authority,
-            /* vault */ vault,
-            /* mint */ mint,
-            /* token program */ token_program.key(),
-            /* vault signer */ &Signer::from(&vault_signer_seeds)
-        )?;
-    } 
-    else if !vault.is_owned_by(&pinocchio_token::ID) {
-        msg!("Vault is not owned by the supplied Token Program.");
-        return Err(ProgramError::InvalidAccountOwner);
+
+        let [_system_program, _lot, depositor_record, _remaining @ ..] = remaining else {
+            msg!("Requires system program, lot, and depositor accounts.");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let depositor_pda = DepositorData::get_depositor_pda(vault_owner, vault_index, from_authority.key(), mint.key(), token_program.key());
+        check(depositor_record, "Depositor record").address(&depositor_pda.0)?.writable()?;
+        if is_uninitialized(depositor_record) {
+            if policy == DepositPolicy::Allowlist {
+                msg!("Depositor is not on the vault's allowlist.");
+                return Err(PimeError::DepositorNotAllowed.into());
+            }
+            let vault_index_bytes = vault_index.to_le_bytes();
+            let depositor_bump = &[depositor_pda.1];
+            let depositor_seeds = DepositorData::get_depositor_signer_seeds(
+                vault_owner, 
+                &vault_index_bytes, 
+                from_authority.key(), 
+                mint.key(), 
+                token_program.key(), 
+                depositor_bump);
+            processors::shared::create_depositor_account::create_depositor_account(
+                /* payer */ from_authority,
+                /* depositor record */ depositor_record,
+                /* vault data */ vault_data.key(),
+                /* depositor */ from_authority.key(),
+                /* allowed */ false,
+                /* depositor signer */ &Signer::from(&depositor_seeds),
+            )?;
+        }
+
+        let mut depositor_data = load_mut::<DepositorData>(depositor_record)?;
+        if policy == DepositPolicy::Allowlist && !depositor_data.allowed() {
+            msg!("Depositor is not on the vault's allowlist.");
+            return Err(PimeError::DepositorNotAllowed.into());
+        }
+        depositor_data.record_deposit(amount, now);
     }
 
     //      Business logic
@@ -82,6 +102,58 @@ pub fn process_deposit_to_vault(accounts: &[AccountInfo], instruction_data: &[u8
         authority: from_authority,
         amount
     }.invoke()?;
+    vault_data_mut.record_deposit(amount, now);
+
+    // A newly funded vault has to warm up before its first withdraw.
+    if vault_pre_amount == 0 {
+        vault_data_mut.start_withdraw_warmup(now);
+    }
+
+    //      Lock the deposit in a lot
+
+    if maturity != 0 {
+        let [_system_program, lot, _remaining @ ..] = remaining else {
+            msg!("Requires system program and lot account.");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if maturity <= now {
+            msg!("Lot maturity must be in the future.");
+            return Err(PimeError::InvalidLotMaturity.into());
+        }
+        if amount == 0 {
+            msg!("Lot amount must be above zero.");
+            return Err(PimeError::InvalidLotAmount.into());
+        }
+
+        let lot_pda = LotData::get_lot_pda(vault_owner, vault_index, lot_index, mint.key(), token_program.key());
+        check(lot, "Lot").address(&lot_pda.0)?.uninitialized()?.writable()?;
+
+        let vault_index_bytes = vault_index.to_le_bytes();
+        let lot_index_bytes = lot_index.to_le_bytes();
+        let lot_bump = &[lot_pda.1];
+        let lot_seeds = LotData::get_lot_signer_seeds(
+            vault_owner, 
+            &vault_index_bytes, 
+            &lot_index_bytes, 
+            mint.key(), 
+            token_program.key(), 
+            lot_bump);
+        create_pda_account(from_authority, lot, LotData::LEN, &crate::ID, &Signer::from(&lot_seeds))?;
+        // SAFETY: Data is not previously borrowed and has the Transmutable trait.
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                lot.data_ptr(), 
+                LotData::LEN) }
+            .copy_from_slice(as_bytes(
+                &LotData::new(
+                    /* vault data */ *vault_data.key(), 
+                    /* payer */ *from_authority.key(), 
+                    /* amount */ amount, 
+                    /* maturity */ maturity)?
+            ));
+
+        lots.schedule(LotEntry::new(lot_index, amount, maturity), now)?;
+    }
 
     ProgramResult::Ok(())
 }
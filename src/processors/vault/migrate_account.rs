@@ -0,0 +1,71 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError, sysvars::{Sysvar, clock::{Clock, UnixTimestamp}}};
+
+use crate::{errors::PimeError, interface::instructions::migrate_account_instruction::MigrateAccountInstructionData, processors::shared::{constraints::check, resize_account::resize_account}, states::{Transmutable, VaultData, VaultDataV1, VaultHistory, as_bytes, load, load_version, lot_schedule::LotSchedule, transfer_registry::TransferRegistry, withdraw_history::WithdrawHistory}};
+
+/// Migrates a version 1 vault data account to the current layout, in place.
+/// Its identity fields are filled in from the instruction's accounts, and the withdraws of its
+/// history ring which are still within the timeframe are folded into the bucketed withdraw history.
+/// The new fields start out as for a new vault without withdraw cool-down or warm-up. Transfers
+/// which are already open are not registered.
+pub fn process_migrate_account(accounts: &[AccountInfo], data: &MigrateAccountInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+
+    let [authority, vault_data, mint, token_program, _system_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    let vault_data_pda = VaultData::get_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key());
+    check(vault_data, "Vault data").address(&vault_data_pda.0)?.writable()?;
+    if load_version(vault_data, VaultData::DISCRIMINATOR)? == VaultData::VERSION {
+        msg!("Vault data is already up to date.");
+        return Err(PimeError::AccountNotMigratable.into());
+    }
+    let vault_data_v1 = load::<VaultDataV1>(vault_data)?;
+    let history_len = vault_data_v1.max_transactions() as usize * VaultHistory::LEN;
+    check(vault_data, "Vault data").min_len(VaultDataV1::LEN + history_len)?;
+
+    //      BUSINESS LOGIC
+
+    // Version 1 does not store its identity, which is restored from the accounts.
+    let vault_pda = VaultData::get_vault_pda(authority.key(), vault_index, mint.key(), token_program.key());
+    let upgraded = vault_data_v1.upgrade(*mint.key(), *token_program.key(), vault_index, vault_data_pda.1, vault_pda.1);
+
+    // Fold the withdraws still within the timeframe into the buckets.
+    let now = Clock::get()?.unix_timestamp;
+    let timeframe = upgraded.timeframe();
+    let mut history = WithdrawHistory::new(timeframe);
+    history.roll(now);
+    for index in 0..vault_data_v1.max_transactions() {
+        // SAFETY: Version 1 vault data is followed by max_transactions history entries.
+        let entry = unsafe { VaultData::history_at(vault_data.data_ptr().add(VaultDataV1::LEN), index) };
+        // Unused history slots are stamped with UnixTimestamp::MIN.
+        if entry.timestamp() == UnixTimestamp::MIN || entry.timestamp().saturating_add(timeframe) < now {
+            continue;
+        }
+        history.record(entry.timestamp(), entry.amount())?;
+    }
+
+    drop(vault_data_v1);
+    resize_account(authority, vault_data, VaultData::SPACE)?;
+
+    // SAFETY: Only mutable here. The account has been resized to VaultData::SPACE.
+    unsafe {
+        core::slice::from_raw_parts_mut(vault_data.data_ptr(), VaultData::LEN)
+            .copy_from_slice(as_bytes(&upgraded));
+        core::slice::from_raw_parts_mut(vault_data.data_ptr().add(VaultData::LEN), WithdrawHistory::LEN)
+            .copy_from_slice(as_bytes(&history));
+        core::slice::from_raw_parts_mut(vault_data.data_ptr().add(VaultData::LEN + WithdrawHistory::LEN), TransferRegistry::LEN)
+            .copy_from_slice(as_bytes(&TransferRegistry::new()));
+        core::slice::from_raw_parts_mut(vault_data.data_ptr().add(VaultData::LEN + WithdrawHistory::LEN + TransferRegistry::LEN), LotSchedule::LEN)
+            .copy_from_slice(as_bytes(&LotSchedule::new()));
+    }
+
+    ProgramResult::Ok(())
+}
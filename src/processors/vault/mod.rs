@@ -2,3 +2,10 @@ pub(crate) mod create_vault;
 pub(crate) mod deposit_to_vault;
 pub(crate) mod withdraw_from_vault;
 pub(crate) mod close_vault;
+pub(crate) mod migrate_account;
+pub(crate) mod release_lot;
+pub(crate) mod set_deposit_policy;
+pub(crate) mod set_depositor;
+pub(crate) mod return_deposit;
+pub(crate) mod set_vault_metadata;
+pub(crate) mod set_max_transactions;
@@ -0,0 +1,57 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+
+use crate::{errors::PimeError, interface::instructions::release_lot_instruction::ReleaseLotInstructionData, processors::shared::constraints::check, states::{VaultAccount, VaultData, load, load_mut, lot_data::LotData}};
+
+/// Releases a matured lot, closing it and removing it from the vault's lot schedule.
+/// The lot's amount is already withdrawable once it has matured, so this only returns the lot's
+/// rent to whoever paid for it.
+pub fn process_release_lot(accounts: &[AccountInfo], data: &ReleaseLotInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let lot_index = data.lot_index();
+
+    let [authority, vault_data, lot, payer, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_account = load_mut::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_mut, lots, .. } = &mut *vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let lot_pda = LotData::get_lot_pda(authority.key(), vault_index, lot_index, mint.key(), token_program.key());
+    check(lot, "Lot").address(&lot_pda.0)?.writable()?;
+    let lot_data = load::<LotData>(lot)?;
+
+    //      BUSINESS LOGIC
+
+    if !pubkey_eq(&lot_data.payer, payer.key()) {
+        msg!("Supplied payer does not match the lot's rent payer.");
+        return Err(PimeError::LotPayerMismatch.into());
+    }
+    check(payer, "Payer").writable()?;
+    if Clock::get()?.unix_timestamp < lot_data.maturity() {
+        msg!("Lot has not yet matured.");
+        return Err(PimeError::LotNotMatured.into());
+    }
+
+    lots.unschedule(lot_index);
+    drop(lot_data);
+
+    // Close the lot account
+    // SAFETY: Lot data is no longer borrowed.
+    unsafe {
+        *payer.borrow_mut_lamports_unchecked() += lot.lamports();
+        lot.close_unchecked();
+    }
+
+    ProgramResult::Ok(())
+}
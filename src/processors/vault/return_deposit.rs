@@ -0,0 +1,84 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::pubkey_eq, sysvars::{Sysvar, clock::Clock}};
+use pinocchio_token::state::TokenAccount;
+
+use crate::{errors::PimeError, interface::instructions::return_deposit_instruction::ReturnDepositInstructionData, processors::shared::constraints::check, states::{VaultAccount, VaultData, depositor_data::DepositorData, load, load_mut}};
+
+/// Returns assets deposited by a third party back to the depositor.
+/// At most what the depositor has deposited, and not yet been returned, can be returned.
+pub fn process_return_deposit(accounts: &[AccountInfo], data: &ReturnDepositInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let depositor = &data.depositor;
+    let amount = data.amount();
+
+    let [authority, vault_data, vault, depositor_record, to, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    if !pubkey_eq(token_program.key(), &pinocchio_token::ID) {
+        msg!("Token program not supported.");
+        return Err(PimeError::UnsupportedTokenProgram.into());
+    }
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    let vault_account = load::<VaultAccount>(vault_data)?;
+    let VaultAccount { data: vault_data_account, lots, .. } = &*vault_account;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let vault_pda = VaultData::create_vault_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_account.vault_bump())?;
+    check(vault, "Vault").address(&vault_pda)?.initialized()?.owned_by(token_program.key())?.writable()?;
+
+    let depositor_pda = DepositorData::get_depositor_pda(authority.key(), vault_index, depositor, mint.key(), token_program.key());
+    check(depositor_record, "Depositor record").address(&depositor_pda.0)?.writable()?;
+    let mut depositor_data = load_mut::<DepositorData>(depositor_record)?;
+
+    // Assets can only be returned to a token account owned by the depositor.
+    {
+        let to_account = TokenAccount::from_account_info(to)?;
+        if !pubkey_eq(to_account.owner(), depositor) {
+            msg!("Receiving account is not owned by the depositor.");
+            return Err(PimeError::DestinationMismatch.into());
+        }
+    }
+
+    //      BUSINESS LOGIC
+
+    if amount > depositor_data.returnable() {
+        msg!("Can not return more than the depositor has deposited.");
+        return Err(PimeError::ReturnExceedsDeposits.into());
+    }
+
+    // Locked funds can not be returned.
+    let now = Clock::get()?.unix_timestamp;
+    if amount > lots.unlocked_amount(TokenAccount::from_account_info(vault)?.amount(), now) {
+        msg!("Vault funds are locked until their lots mature.");
+        return Err(PimeError::FundsLocked.into());
+    }
+
+    let vault_index_bytes = vault_index.to_le_bytes();
+    let vault_bump = &[vault_data_account.vault_bump()];
+    let vault_signer_seeds = VaultData::get_vault_signer_seeds(
+        authority.key(), 
+        &vault_index_bytes, 
+        mint.key(), 
+        token_program.key(), 
+        vault_bump);
+    pinocchio_token::instructions::Transfer {
+        from: vault,
+        to,
+        authority: vault,
+        amount,
+    }.invoke_signed(&[Signer::from(&vault_signer_seeds)])?;
+
+    let returned = depositor_data.returned() + amount;
+    depositor_data.set_returned(returned);
+
+    ProgramResult::Ok(())
+}
@@ -0,0 +1,34 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError};
+
+use crate::{interface::instructions::set_deposit_policy_instruction::SetDepositPolicyInstructionData, processors::shared::constraints::check, states::{DepositPolicy, VaultData, load_mut}};
+
+/// Sets who may deposit into the vault, and the vault's minimum deposit.
+pub fn process_set_deposit_policy(accounts: &[AccountInfo], data: &SetDepositPolicyInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let deposit_policy = DepositPolicy::try_from(data.deposit_policy())?;
+    let min_deposit = data.min_deposit();
+
+    let [authority, vault_data, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_data_mut = load_mut::<VaultData>(vault_data)?;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    //      BUSINESS LOGIC
+
+    vault_data_mut.set_deposit_policy(deposit_policy);
+    vault_data_mut.set_min_deposit(&min_deposit);
+
+    ProgramResult::Ok(())
+}
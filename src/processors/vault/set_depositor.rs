@@ -0,0 +1,56 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError};
+
+use crate::{interface::instructions::set_depositor_instruction::SetDepositorInstructionData, processors::shared::{self, constraints::check, create_account::is_uninitialized}, states::{VaultData, depositor_data::DepositorData, load, load_mut}};
+
+/// Allows, or disallows, a depositor to deposit into a vault with an allowlist deposit policy.
+/// Creates the depositor's record if it does not exist.
+pub fn process_set_depositor(accounts: &[AccountInfo], data: &SetDepositorInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let depositor = &data.depositor;
+    let allowed = data.allowed();
+
+    let [authority, vault_data, depositor_record, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    let vault_data_bump = load::<VaultData>(vault_data)?.vault_data_bump();
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_bump)?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let depositor_pda = DepositorData::get_depositor_pda(authority.key(), vault_index, depositor, mint.key(), token_program.key());
+    check(depositor_record, "Depositor record").address(&depositor_pda.0)?.writable()?;
+
+    //      BUSINESS LOGIC
+
+    if is_uninitialized(depositor_record) {
+        let vault_index_bytes = vault_index.to_le_bytes();
+        let depositor_bump = &[depositor_pda.1];
+        let depositor_seeds = DepositorData::get_depositor_signer_seeds(
+            authority.key(), 
+            &vault_index_bytes, 
+            depositor, 
+            mint.key(), 
+            token_program.key(), 
+            depositor_bump);
+        return shared::create_depositor_account::create_depositor_account(
+            /* payer */ authority,
+            /* depositor record */ depositor_record,
+            /* vault data */ vault_data.key(),
+            /* depositor */ depositor,
+            /* allowed */ allowed,
+            /* depositor signer */ &Signer::from(&depositor_seeds),
+        );
+    }
+
+    load_mut::<DepositorData>(depositor_record)?.set_allowed(allowed);
+
+    ProgramResult::Ok(())
+}
@@ -0,0 +1,34 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, program_error::ProgramError, sysvars::{Sysvar, clock::Clock}};
+
+use crate::{interface::instructions::set_max_transactions_instruction::SetMaxTransactionsInstructionData, processors::shared::constraints::check, states::{VaultData, load_mut}};
+
+/// Changes the number of withdraws the vault allows within its timeframe.
+/// Lowering it applies right away, while raising it only applies once a timeframe has passed.
+pub fn process_set_max_transactions(accounts: &[AccountInfo], data: &SetMaxTransactionsInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let max_transactions = data.max_transactions();
+
+    let [authority, vault_data, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_data_mut = load_mut::<VaultData>(vault_data)?;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    //      BUSINESS LOGIC
+
+    // The withdraw history is bucketed by time rather than per withdraw, so it is kept as is.
+    vault_data_mut.change_max_transactions(max_transactions, Clock::get()?.unix_timestamp);
+
+    ProgramResult::Ok(())
+}
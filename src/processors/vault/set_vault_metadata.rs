@@ -0,0 +1,80 @@
+use pinocchio::{ProgramResult, account_info::AccountInfo, instruction::Signer, program_error::ProgramError, sysvars::{Sysvar, clock::Clock}};
+
+use crate::{errors::PimeError, interface::instructions::set_vault_metadata_instruction::SetVaultMetadataInstructionData, processors::shared::{self, constraints::check, create_account::{create_pda_account, is_uninitialized}}, states::{Transmutable, VaultData, as_bytes, load, load_mut, metadata_data::MetadataData}};
+
+/// Sets, or updates, a vault's metadata.
+/// The metadata account is created on first use, and resized to fit the name and description on updates.
+pub fn process_set_vault_metadata(accounts: &[AccountInfo], data: &SetVaultMetadataInstructionData) -> ProgramResult {
+
+    let vault_index = data.vault_index();
+    let category = data.category();
+    if data.name_len() > MetadataData::MAX_NAME_LEN || data.description_len() > MetadataData::MAX_DESCRIPTION_LEN {
+        msg!("Name or description is too long.");
+        return Err(PimeError::InvalidMetadata.into());
+    }
+    let (name, description) = (data.name(), data.description());
+
+    if core::str::from_utf8(name).is_err() || core::str::from_utf8(description).is_err() {
+        msg!("Name and description must be valid UTF-8.");
+        return Err(PimeError::InvalidMetadata.into());
+    }
+
+    let [authority, vault_data, metadata, mint, token_program, _remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    //      ACCOUNT SAFETY CHECKS
+
+    check(authority, "Authority").signer()?;
+
+    check(mint, "Mint").owned_by(token_program.key())?;
+
+    check(vault_data, "Vault data").writable()?;
+    let mut vault_data_mut = load_mut::<VaultData>(vault_data)?;
+    // The stored bump is trusted as the account is owned by this program.
+    let vault_data_pda = VaultData::create_vault_data_pda(authority.key(), vault_index, mint.key(), token_program.key(), vault_data_mut.vault_data_bump())?;
+    check(vault_data, "Vault data").address(&vault_data_pda)?;
+
+    let metadata_pda = MetadataData::get_metadata_pda(authority.key(), vault_index, mint.key(), token_program.key());
+    check(metadata, "Metadata").address(&metadata_pda.0)?.writable()?;
+
+    //      BUSINESS LOGIC
+
+    let now = Clock::get()?.unix_timestamp;
+    let space = MetadataData::space(name.len(), description.len());
+    let created = if is_uninitialized(metadata) {
+        let vault_index_bytes = vault_index.to_le_bytes();
+        let metadata_bump = &[metadata_pda.1];
+        let metadata_seeds = MetadataData::get_metadata_signer_seeds(
+            authority.key(), 
+            &vault_index_bytes, 
+            mint.key(), 
+            token_program.key(), 
+            metadata_bump);
+        create_pda_account(authority, metadata, space, &crate::ID, &Signer::from(&metadata_seeds))?;
+
+        vault_data_mut.set_has_metadata(true);
+        now
+    }
+    else {
+        let created = load::<MetadataData>(metadata)?.created();
+
+        shared::resize_account::resize_account(authority, metadata, space)?;
+        created
+    };
+
+    // SAFETY: Data is not borrowed elsewhere, and is exactly `space` long.
+    let data = unsafe { core::slice::from_raw_parts_mut(metadata.data_ptr(), space) };
+    data[..MetadataData::LEN].copy_from_slice(as_bytes(&MetadataData::new(
+        /* vault data */ *vault_data.key(),
+        /* category */ category,
+        /* name len */ name.len() as u8,
+        /* description len */ description.len() as u16,
+        /* created */ created,
+        /* updated */ now,
+    )));
+    data[MetadataData::LEN..MetadataData::LEN + name.len()].copy_from_slice(name);
+    data[MetadataData::LEN + name.len()..].copy_from_slice(description);
+
+    ProgramResult::Ok(())
+}
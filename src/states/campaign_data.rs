@@ -0,0 +1,203 @@
+use pinocchio::{instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, create_program_address, find_program_address}, seeds, sysvars::clock::UnixTimestamp};
+
+use crate::{errors::PimeError, states::{AccountState, Transmutable}};
+
+/// A crowdfunding campaign, raising `target` of its mint for its recipient by its deadline.
+///
+/// Vault owners pledge from their vaults into the campaign's escrow. If the pledges reach the
+/// target by the deadline, the campaign is finalized and the escrow released to the recipient.
+/// Otherwise every pledge is refunded into the vault it came from.
+#[repr(C)]
+pub struct CampaignData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub creator: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    campaign_index: [u8; size_of::<u64>()],
+    campaign_bump: u8,
+    escrow_bump: u8,
+    target: [u8; size_of::<u64>()],
+    deadline: [u8; size_of::<UnixTimestamp>()],
+    pledged: [u8; size_of::<u64>()],
+    pledges: [u8; size_of::<u64>()],
+    allows_withdraw: u8,
+    finalized: u8,
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for CampaignData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for CampaignData {
+    const DISCRIMINATOR: u8 = 5;
+    const VERSION: u64 = 1;
+}
+
+impl CampaignData {
+    pub const CAMPAIGN_SEED: &[u8] = b"campaign";
+    pub const ESCROW_SEED: &[u8] = b"escrow";
+
+    pub const CREATOR_OFFSET: usize = core::mem::offset_of!(CampaignData, creator);
+    pub const RECIPIENT_OFFSET: usize = core::mem::offset_of!(CampaignData, recipient);
+    pub const MINT_OFFSET: usize = core::mem::offset_of!(CampaignData, mint);
+    pub const TOKEN_PROGRAM_OFFSET: usize = core::mem::offset_of!(CampaignData, token_program);
+    pub const CAMPAIGN_INDEX_OFFSET: usize = core::mem::offset_of!(CampaignData, campaign_index);
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        creator: Pubkey,
+        recipient: Pubkey,
+        mint: Pubkey,
+        token_program: Pubkey,
+        campaign_index: u64,
+        campaign_bump: u8,
+        escrow_bump: u8,
+        target: u64,
+        deadline: UnixTimestamp,
+        allows_withdraw: bool,
+    ) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            creator,
+            recipient,
+            mint,
+            token_program,
+            campaign_index: campaign_index.to_le_bytes(),
+            campaign_bump,
+            escrow_bump,
+            target: target.to_le_bytes(),
+            deadline: deadline.to_le_bytes(),
+            pledged: 0u64.to_le_bytes(),
+            pledges: 0u64.to_le_bytes(),
+            allows_withdraw: allows_withdraw as u8,
+            finalized: 0,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn campaign_index(&self) -> u64 {
+        u64::from_le_bytes(self.campaign_index)
+    }
+
+    /// Bump of the campaign PDA.
+    pub fn campaign_bump(&self) -> u8 {
+        self.campaign_bump
+    }
+
+    /// Bump of the escrow PDA.
+    pub fn escrow_bump(&self) -> u8 {
+        self.escrow_bump
+    }
+
+    /// Amount the pledges have to reach by the deadline.
+    pub fn target(&self) -> u64 {
+        u64::from_le_bytes(self.target)
+    }
+
+    /// Last moment pledges can be made or withdrawn.
+    pub fn deadline(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.deadline)
+    }
+
+    /// Amount pledged and not withdrawn.
+    pub fn pledged(&self) -> u64 {
+        u64::from_le_bytes(self.pledged)
+    }
+
+    /// Number of pledges which have not been settled yet.
+    pub fn pledges(&self) -> u64 {
+        u64::from_le_bytes(self.pledges)
+    }
+
+    /// If pledgers may withdraw their pledges before the deadline.
+    pub fn allows_withdraw(&self) -> bool {
+        self.allows_withdraw != 0
+    }
+
+    /// If the campaign reached its target, and the escrow has been released to the recipient.
+    pub fn is_finalized(&self) -> bool {
+        self.finalized != 0
+    }
+
+    /// If pledges can still be made or withdrawn at `now`.
+    pub fn is_open(&self, now: UnixTimestamp) -> bool {
+        now <= self.deadline() && !self.is_finalized()
+    }
+
+    pub fn reached_target(&self) -> bool {
+        self.pledged() >= self.target()
+    }
+
+    pub(crate) fn record_pledged(&mut self, amount: u64) {
+        self.pledged = self.pledged().saturating_add(amount).to_le_bytes();
+        self.pledges = self.pledges().saturating_add(1).to_le_bytes();
+    }
+
+    pub(crate) fn record_withdrawn(&mut self, amount: u64) {
+        self.pledged = self.pledged().saturating_sub(amount).to_le_bytes();
+        self.record_settled();
+    }
+
+    /// Records a pledge released or refunded after the deadline. The pledged total is kept.
+    pub(crate) fn record_settled(&mut self) {
+        self.pledges = self.pledges().saturating_sub(1).to_le_bytes();
+    }
+
+    pub(crate) fn set_finalized(&mut self) {
+        self.finalized = 1;
+    }
+
+    /// Get the campaign's PDA.
+    pub fn get_campaign_pda(creator: &Pubkey, campaign_index: u64, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
+        let seeds: &[&[u8]] = &[
+            Self::CAMPAIGN_SEED,
+            &campaign_index.to_le_bytes(),
+            creator,
+            mint,
+            token_program,
+        ];
+        find_program_address(seeds, &crate::ID)
+    }
+    pub fn get_campaign_signer_seeds<'a>(
+        creator: &'a Pubkey,
+        campaign_index: &'a [u8; size_of::<u64>()],
+        mint: &'a Pubkey,
+        token_program: &'a Pubkey,
+        bump: &'a [u8]) -> [Seed<'a>; 6] {
+        seeds!(
+            Self::CAMPAIGN_SEED,
+            campaign_index,
+            creator,
+            mint,
+            token_program,
+            bump
+        )
+    }
+
+    /// Get the PDA of the campaign's escrow token account.
+    pub fn get_escrow_pda(campaign: &Pubkey) -> (Pubkey, u8) {
+        find_program_address(&[Self::ESCROW_SEED, campaign], &crate::ID)
+    }
+    /// Get the escrow PDA from its stored bump.
+    pub fn create_escrow_pda(campaign: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[Self::ESCROW_SEED, campaign, &[bump]], &crate::ID)
+            .map_err(|_| PimeError::IncorrectPDA.into())
+    }
+    pub fn get_escrow_signer_seeds<'a>(campaign: &'a Pubkey, bump: &'a [u8]) -> [Seed<'a>; 3] {
+        seeds!(
+            Self::ESCROW_SEED,
+            campaign,
+            bump
+        )
+    }
+}
@@ -0,0 +1,128 @@
+use pinocchio::{instruction::Seed, pubkey::{Pubkey, find_program_address}, seeds, sysvars::clock::UnixTimestamp};
+
+use crate::states::{AccountState, Transmutable};
+
+/// A third party depositor of a vault.
+/// Acts as the depositor's allowlist entry, and records the depositor's deposits so that the
+/// vault's authority can see, and return, them.
+#[repr(C)]
+pub struct DepositorData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub vault_data: Pubkey,
+    pub depositor: Pubkey,
+    allowed: u8,
+    deposited: [u8; size_of::<u64>()],
+    returned: [u8; size_of::<u64>()],
+    deposits: [u8; size_of::<u64>()],
+    last_deposit: [u8; size_of::<UnixTimestamp>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for DepositorData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for DepositorData {
+    const DISCRIMINATOR: u8 = 2;
+    const VERSION: u64 = 1;
+}
+
+impl DepositorData {
+    pub const DEPOSITOR_SEED: &[u8] = b"depositor";
+
+    pub fn new(vault_data: Pubkey, depositor: Pubkey, allowed: bool) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            vault_data,
+            depositor,
+            allowed: allowed as u8,
+            deposited: 0u64.to_le_bytes(),
+            returned: 0u64.to_le_bytes(),
+            deposits: 0u64.to_le_bytes(),
+            last_deposit: UnixTimestamp::MIN.to_le_bytes(),
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    /// If the depositor is on the vault's allowlist.
+    pub fn allowed(&self) -> bool {
+        self.allowed != 0
+    }
+
+    pub(crate) fn set_allowed(&mut self, val: bool) {
+        self.allowed = val as u8;
+    }
+
+    /// Total amount deposited by the depositor.
+    pub fn deposited(&self) -> u64 {
+        u64::from_le_bytes(self.deposited)
+    }
+
+    /// Total amount returned to the depositor.
+    pub fn returned(&self) -> u64 {
+        u64::from_le_bytes(self.returned)
+    }
+
+    pub(crate) fn set_returned(&mut self, val: u64) {
+        self.returned = val.to_le_bytes();
+    }
+
+    /// Amount which can still be returned to the depositor.
+    pub fn returnable(&self) -> u64 {
+        self.deposited().saturating_sub(self.returned())
+    }
+
+    /// Number of deposits made by the depositor.
+    pub fn deposits(&self) -> u64 {
+        u64::from_le_bytes(self.deposits)
+    }
+
+    pub fn last_deposit(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.last_deposit)
+    }
+
+    /// Records a new deposit.
+    pub(crate) fn record_deposit(&mut self, amount: u64, now: UnixTimestamp) {
+        self.deposited = self.deposited().saturating_add(amount).to_le_bytes();
+        self.deposits = self.deposits().saturating_add(1).to_le_bytes();
+        self.last_deposit = now.to_le_bytes();
+    }
+
+    /// Get the depositor's PDA.
+    pub fn get_depositor_pda(authority: &Pubkey, vault_index: u64, depositor: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
+        let seeds: &[&[u8]] = &[
+            Self::DEPOSITOR_SEED,
+            &vault_index.to_le_bytes(),
+            authority,
+            depositor,
+            mint,
+            token_program,
+        ];
+        find_program_address(seeds, &crate::ID)
+    }
+    pub fn get_depositor_signer_seeds<'a>(
+        authority: &'a Pubkey,
+        vault_index: &'a [u8; size_of::<u64>()],
+        depositor: &'a Pubkey,
+        mint: &'a Pubkey,
+        token_program: &'a Pubkey,
+        bump: &'a [u8]) -> [Seed<'a>; 7] {
+        seeds!(
+            Self::DEPOSITOR_SEED,
+            vault_index,
+            authority,
+            depositor,
+            mint,
+            token_program,
+            bump
+        )
+    }
+}
@@ -0,0 +1,125 @@
+use pinocchio::program_error::ProgramError;
+
+use crate::{errors::PimeError, states::Transmutable};
+
+/// Length of a hash lock's hash.
+pub const HASH_LEN: usize = 32;
+
+/// Longest preimage, in bytes, which can be revealed to execute a hash lock.
+pub const MAX_PREIMAGE_LEN: usize = 64;
+
+/// Hash function a hash-locked transfer's preimage is checked with.
+///
+/// A hash-locked transfer can only be executed with the preimage of its hash, by anyone holding it,
+/// and is refunded to the vault once expired.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HashLock {
+    /// The transfer is not hash-locked.
+    None = 0,
+    Sha256 = 1,
+    Keccak256 = 2,
+}
+
+impl TryFrom<u8> for HashLock {
+    type Error = ProgramError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(HashLock::None),
+            1 => Ok(HashLock::Sha256),
+            2 => Ok(HashLock::Keccak256),
+            _ => Err(PimeError::InvalidHashLock.into()),
+        }
+    }
+}
+
+impl HashLock {
+
+    /// Hashes the preimage through the lock's syscall. Returns `None` if the transfer is not hash-locked.
+    /// Off-chain, the preimage is only hashed with the `host-hash` feature.
+    pub fn hash(&self, preimage: &[u8]) -> Result<Option<[u8; HASH_LEN]>, ProgramError> {
+        if *self == HashLock::None {
+            return Ok(None);
+        }
+
+        #[cfg(target_os = "solana")]
+        {
+            let mut hash = core::mem::MaybeUninit::<[u8; HASH_LEN]>::uninit();
+            let vals: &[&[u8]] = &[preimage];
+            // SAFETY: The syscalls read the slices of `vals`, and write `HASH_LEN` bytes to `hash`.
+            let result = unsafe {
+                match self {
+                    HashLock::Sha256 => pinocchio::syscalls::sol_sha256(vals as *const _ as *const u8, vals.len() as u64, hash.as_mut_ptr() as *mut u8),
+                    _ => pinocchio::syscalls::sol_keccak256(vals as *const _ as *const u8, vals.len() as u64, hash.as_mut_ptr() as *mut u8),
+                }
+            };
+            if result != 0 {
+                return Err(ProgramError::from(result));
+            }
+            // SAFETY: The syscall succeeded, so it has initialized the hash.
+            Ok(Some(unsafe { hash.assume_init() }))
+        }
+
+        #[cfg(all(not(target_os = "solana"), feature = "host-hash"))]
+        {
+            use sha2::Digest;
+            Ok(Some(match self {
+                HashLock::Sha256 => sha2::Sha256::digest(preimage).into(),
+                _ => sha3::Keccak256::digest(preimage).into(),
+            }))
+        }
+
+        #[cfg(all(not(target_os = "solana"), not(feature = "host-hash")))]
+        {
+            let _ = preimage;
+            Err(ProgramError::InvalidArgument)
+        }
+    }
+}
+
+/// A preimage of up to `MAX_PREIMAGE_LEN` bytes, revealed to execute a hash-locked transfer.
+///
+/// Only the first `len` bytes are hashed.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Preimage {
+    len: u8,
+    bytes: [u8; MAX_PREIMAGE_LEN],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for Preimage {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl Preimage {
+
+    /// Preimage holding `preimage`. Fails if it is longer than `MAX_PREIMAGE_LEN`.
+    pub fn new(preimage: &[u8]) -> Result<Self, ProgramError> {
+        if preimage.len() > MAX_PREIMAGE_LEN {
+            return Err(PimeError::InvalidPreimage.into());
+        }
+        let mut bytes = [0; MAX_PREIMAGE_LEN];
+        bytes[..preimage.len()].copy_from_slice(preimage);
+        Ok(Self { len: preimage.len() as u8, bytes })
+    }
+
+    pub const fn empty() -> Self {
+        Self { len: 0, bytes: [0; MAX_PREIMAGE_LEN] }
+    }
+
+    /// The preimage's bytes. Fails if its length exceeds `MAX_PREIMAGE_LEN`.
+    pub fn as_slice(&self) -> Result<&[u8], ProgramError> {
+        self.bytes.get(..self.len as usize).ok_or_else(|| {
+            msg!("Preimage is longer than the maximum preimage length.");
+            PimeError::InvalidPreimage.into()
+        })
+    }
+}
+
+impl Default for Preimage {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
@@ -0,0 +1,78 @@
+use pinocchio::{account_info::{AccountInfo, Ref, RefMut}, program_error::ProgramError};
+
+use crate::{errors::PimeError, states::Transmutable};
+
+/// A state stored in an account owned by this program.
+///
+/// # SAFETY
+/// The state must start with its `u8` discriminator, directly followed by its `u64` version.
+pub unsafe trait AccountState: Transmutable {
+    const DISCRIMINATOR: u8;
+    /// The current layout version.
+    const VERSION: u64;
+    /// The oldest layout version which can be read as this state.
+    const MIN_VERSION: u64 = Self::VERSION;
+}
+
+/// Loads a state from an account, tracking the borrow of its data.
+///
+/// The account has to be initialized, owned by this program, carry `T`'s discriminator and a
+/// readable version, and be at least `T::LEN` long. Each mismatch fails with its own `PimeError`.
+pub fn load<T: AccountState>(account: &AccountInfo) -> Result<Ref<'_, T>, ProgramError> {
+    check::<T>(account)?;
+    let data = account.try_borrow_data()?;
+    // SAFETY: The data is at least T::LEN long, and T is Transmutable.
+    Ok(Ref::map(data, |d| unsafe { &*(d.as_ptr() as *const T) }))
+}
+
+/// Mutably loads a state from an account. See `load`.
+pub fn load_mut<T: AccountState>(account: &AccountInfo) -> Result<RefMut<'_, T>, ProgramError> {
+    check::<T>(account)?;
+    let data = account.try_borrow_mut_data()?;
+    // SAFETY: The data is at least T::LEN long, and T is Transmutable.
+    Ok(RefMut::map(data, |d| unsafe { &mut *(d.as_mut_ptr() as *mut T) }))
+}
+
+/// Reads the version of an account of this program carrying `discriminator`, without requiring
+/// it to be readable as any state. Used to tell outdated accounts from current ones.
+pub fn load_version(account: &AccountInfo, discriminator: u8) -> Result<u64, ProgramError> {
+    if account.lamports() == 0 {
+        msg!("Account is not initialized.");
+        return Err(PimeError::AccountNotInitialized.into());
+    }
+    if !account.is_owned_by(&crate::ID) {
+        msg!("Account is not owned by this program.");
+        return Err(PimeError::InvalidAccountOwner.into());
+    }
+    // The version is checked before the length, as outdated layouts may be shorter.
+    if account.data_len() < size_of::<u8>() + size_of::<u64>() {
+        msg!("Account data is too short.");
+        return Err(PimeError::InvalidAccountLength.into());
+    }
+
+    // SAFETY: The account is owned by this program and long enough to hold the discriminator and
+    // version.
+    let data = unsafe { account.borrow_data_unchecked() };
+    if data[0] != discriminator {
+        msg!("Account discriminator mismatch.");
+        return Err(PimeError::InvalidDiscriminator.into());
+    }
+    Ok(u64::from_le_bytes(unsafe { *(data.as_ptr().add(size_of::<u8>()) as *const [u8; size_of::<u64>()]) }))
+}
+
+fn check<T: AccountState>(account: &AccountInfo) -> Result<(), ProgramError> {
+    let version = load_version(account, T::DISCRIMINATOR)?;
+    if version < T::MIN_VERSION {
+        msg!("Account has to be migrated.");
+        return Err(PimeError::OutdatedAccountVersion.into());
+    }
+    if version > T::VERSION {
+        msg!("Account version is not supported.");
+        return Err(PimeError::UnsupportedAccountVersion.into());
+    }
+    if account.data_len() < T::LEN {
+        msg!("Account data is too short.");
+        return Err(PimeError::InvalidAccountLength.into());
+    }
+    Ok(())
+}
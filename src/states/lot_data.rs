@@ -0,0 +1,92 @@
+use pinocchio::{instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, find_program_address}, seeds, sysvars::{Sysvar, clock::{Clock, UnixTimestamp}}};
+
+use crate::states::{AccountState, Transmutable};
+
+/// A term-deposit lot.
+/// The lot's amount is locked in its vault until the lot has matured. Releasing the lot afterwards
+/// only closes it and removes it from the vault's `LotSchedule`.
+#[repr(C)]
+pub struct LotData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub vault_data: Pubkey,
+    pub payer: Pubkey,
+    amount: [u8; size_of::<u64>()],
+    created: [u8; size_of::<UnixTimestamp>()],
+    maturity: [u8; size_of::<UnixTimestamp>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for LotData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for LotData {
+    const DISCRIMINATOR: u8 = 1;
+    const VERSION: u64 = 1;
+}
+
+impl LotData {
+    pub const LOT_SEED: &[u8] = b"lot";
+
+    pub fn new(vault_data: Pubkey, payer: Pubkey, amount: u64, maturity: UnixTimestamp) -> Result<Self, ProgramError> {
+        Ok(Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            vault_data,
+            payer,
+            amount: amount.to_le_bytes(),
+            created: Clock::get()?.unix_timestamp.to_le_bytes(),
+            maturity: maturity.to_le_bytes(),
+        })
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    pub fn created(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.created)
+    }
+
+    pub fn maturity(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.maturity)
+    }
+
+    /// Get the lot's PDA.
+    pub fn get_lot_pda(authority: &Pubkey, vault_index: u64, lot_index: u64, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
+        let seeds: &[&[u8]] = &[
+            Self::LOT_SEED,
+            &vault_index.to_le_bytes(),
+            &lot_index.to_le_bytes(),
+            authority,
+            mint,
+            token_program,
+        ];
+        find_program_address(seeds, &crate::ID)
+    }
+    pub fn get_lot_signer_seeds<'a>(
+        authority: &'a Pubkey,
+        vault_index: &'a [u8; size_of::<u64>()],
+        lot_index: &'a [u8; size_of::<u64>()],
+        mint: &'a Pubkey,
+        token_program: &'a Pubkey,
+        bump: &'a [u8]) -> [Seed<'a>; 7] {
+        seeds!(
+            Self::LOT_SEED,
+            vault_index,
+            lot_index,
+            authority,
+            mint,
+            token_program,
+            bump
+        )
+    }
+}
@@ -0,0 +1,152 @@
+use pinocchio::{program_error::ProgramError, sysvars::clock::UnixTimestamp};
+use crate::{errors::PimeError, states::Transmutable};
+
+/// Number of unmatured lots a vault can hold.
+pub const MAX_OPEN_LOTS: usize = 16;
+
+/// A vault's schedule of term-deposit lots, stored after its `TransferRegistry`.
+///
+/// Lets the vault tell how much of its assets are locked without reading the lot accounts: a
+/// lot's amount is locked until its maturity and withdrawable from then on. Only the first
+/// `count` entries are in use.
+#[repr(C)]
+pub struct LotSchedule {
+    count: [u8; size_of::<u64>()],
+    entries: [LotEntry; MAX_OPEN_LOTS],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for LotSchedule {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl LotSchedule {
+
+    pub const fn new() -> Self {
+        Self {
+            count: 0u64.to_le_bytes(),
+            entries: [const { LotEntry::empty() }; MAX_OPEN_LOTS],
+        }
+    }
+
+    /// Number of scheduled lots.
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+
+    /// The scheduled lots.
+    pub fn entries(&self) -> &[LotEntry] {
+        &self.entries[..(self.count() as usize).min(MAX_OPEN_LOTS)]
+    }
+
+    /// Amount of the vault's assets locked in lots which have not matured at `now`.
+    pub fn locked_amount(&self, now: UnixTimestamp) -> u64 {
+        self.entries().iter()
+            .filter(|e| e.maturity() > now)
+            .fold(0u64, |locked, e| locked.saturating_add(e.amount()))
+    }
+
+    /// Amount of the vault's assets which are not locked in an unmatured lot at `now`.
+    pub fn unlocked_amount(&self, vault_amount: u64, now: UnixTimestamp) -> u64 {
+        vault_amount.saturating_sub(self.locked_amount(now))
+    }
+
+    /// Schedules a lot. Matured lots no longer lock anything, so their entries are dropped to
+    /// make room. Fails if the schedule is still full.
+    pub(crate) fn schedule(&mut self, entry: LotEntry, now: UnixTimestamp) -> Result<(), ProgramError> {
+        if self.count() as usize >= MAX_OPEN_LOTS {
+            self.prune(now);
+        }
+        let count = self.count() as usize;
+        if count >= MAX_OPEN_LOTS {
+            msg!("Vault has too many unmatured lots.");
+            return Err(PimeError::LotScheduleFull.into());
+        }
+        self.entries[count] = entry;
+        self.count = (count as u64 + 1).to_le_bytes();
+        Ok(())
+    }
+
+    /// Removes a lot from the schedule. Unscheduled lots are ignored.
+    pub(crate) fn unschedule(&mut self, lot_index: u64) {
+        let count = (self.count() as usize).min(MAX_OPEN_LOTS);
+        let Some(position) = self.entries[..count].iter().position(|e| e.lot_index() == lot_index) else {
+            return;
+        };
+        self.remove(position, count);
+    }
+
+    /// Removes the lots which have matured at `now`.
+    fn prune(&mut self, now: UnixTimestamp) {
+        let mut count = (self.count() as usize).min(MAX_OPEN_LOTS);
+        let mut position = 0;
+        while position < count {
+            if self.entries[position].maturity() <= now {
+                self.remove(position, count);
+                count -= 1;
+            } else {
+                position += 1;
+            }
+        }
+    }
+
+    fn remove(&mut self, position: usize, count: usize) {
+        // Move the last entry into the removed entry's place.
+        self.entries.swap(position, count - 1);
+        self.entries[count - 1] = LotEntry::empty();
+        self.count = (count as u64 - 1).to_le_bytes();
+    }
+}
+
+impl Default for LotSchedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A lot in a vault's schedule.
+#[repr(C)]
+pub struct LotEntry {
+    lot_index: [u8; size_of::<u64>()],
+    amount: [u8; size_of::<u64>()],
+    maturity: [u8; size_of::<UnixTimestamp>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for LotEntry {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl LotEntry {
+
+    pub fn new(lot_index: u64, amount: u64, maturity: UnixTimestamp) -> Self {
+        Self {
+            lot_index: lot_index.to_le_bytes(),
+            amount: amount.to_le_bytes(),
+            maturity: maturity.to_le_bytes(),
+        }
+    }
+
+    const fn empty() -> Self {
+        Self {
+            lot_index: [0; size_of::<u64>()],
+            amount: [0; size_of::<u64>()],
+            maturity: [0; size_of::<UnixTimestamp>()],
+        }
+    }
+
+    pub fn lot_index(&self) -> u64 {
+        u64::from_le_bytes(self.lot_index)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// Timestamp from which the lot's amount can be withdrawn.
+    pub fn maturity(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.maturity)
+    }
+}
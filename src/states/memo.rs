@@ -0,0 +1,67 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+use pinocchio_pubkey::pubkey;
+
+use crate::{errors::PimeError, states::Transmutable};
+
+/// Longest memo, in bytes, a transfer or withdraw can carry.
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// The SPL Memo program, through which memos are attached to transactions.
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// A memo of up to `MAX_MEMO_LEN` bytes, such as an invoice number, used to reconcile payments.
+///
+/// Only the first `len` bytes are in use. An empty memo is not attached.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Memo {
+    len: u8,
+    bytes: [u8; MAX_MEMO_LEN],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for Memo {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl Memo {
+
+    /// Memo holding `memo`. Fails if it is longer than `MAX_MEMO_LEN`.
+    pub fn new(memo: &[u8]) -> Result<Self, ProgramError> {
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(PimeError::InvalidMemo.into());
+        }
+        let mut bytes = [0; MAX_MEMO_LEN];
+        bytes[..memo.len()].copy_from_slice(memo);
+        Ok(Self { len: memo.len() as u8, bytes })
+    }
+
+    pub const fn empty() -> Self {
+        Self { len: 0, bytes: [0; MAX_MEMO_LEN] }
+    }
+
+    /// The memo's bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..(self.len as usize).min(MAX_MEMO_LEN)]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks that the memo fits, and is valid UTF-8 as required by the SPL Memo program.
+    pub(crate) fn check(&self) -> Result<(), ProgramError> {
+        if self.len as usize > MAX_MEMO_LEN || core::str::from_utf8(self.as_slice()).is_err() {
+            msg!("Memo is too long or not valid UTF-8.");
+            return Err(PimeError::InvalidMemo.into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for Memo {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
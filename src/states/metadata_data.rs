@@ -0,0 +1,118 @@
+use pinocchio::{instruction::Seed, pubkey::{Pubkey, find_program_address}, seeds, sysvars::clock::UnixTimestamp};
+
+use crate::states::{AccountState, Transmutable};
+
+/// A vault's label and description.
+/// The account's data continues with the name's UTF-8 bytes, directly followed by the description's
+/// UTF-8 bytes. The account is resized whenever the metadata is updated.
+#[repr(C)]
+pub struct MetadataData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub vault_data: Pubkey,
+    created: [u8; size_of::<UnixTimestamp>()],
+    updated: [u8; size_of::<UnixTimestamp>()],
+    category: u8,
+    name_len: u8,
+    description_len: [u8; size_of::<u16>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for MetadataData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for MetadataData {
+    const DISCRIMINATOR: u8 = 3;
+    const VERSION: u64 = 1;
+}
+
+impl MetadataData {
+    pub const METADATA_SEED: &[u8] = b"metadata";
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    pub fn new(vault_data: Pubkey, category: u8, name_len: u8, description_len: u16, created: UnixTimestamp, updated: UnixTimestamp) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            vault_data,
+            created: created.to_le_bytes(),
+            updated: updated.to_le_bytes(),
+            category,
+            name_len,
+            description_len: description_len.to_le_bytes(),
+        }
+    }
+
+    /// Account size needed to store a name and description of the given lengths.
+    pub fn space(name_len: usize, description_len: usize) -> usize {
+        Self::LEN + name_len + description_len
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn created(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.created)
+    }
+
+    pub fn updated(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.updated)
+    }
+
+    /// User defined category of the vault.
+    pub fn category(&self) -> u8 {
+        self.category
+    }
+
+    pub fn name_len(&self) -> usize {
+        self.name_len as usize
+    }
+
+    pub fn description_len(&self) -> usize {
+        u16::from_le_bytes(self.description_len) as usize
+    }
+
+    /// The name's UTF-8 bytes, from the metadata account's data.
+    pub fn name<'a>(&self, account_data: &'a [u8]) -> &'a [u8] {
+        &account_data[Self::LEN..Self::LEN + self.name_len()]
+    }
+
+    /// The description's UTF-8 bytes, from the metadata account's data.
+    pub fn description<'a>(&self, account_data: &'a [u8]) -> &'a [u8] {
+        let start = Self::LEN + self.name_len();
+        &account_data[start..start + self.description_len()]
+    }
+
+    /// Get the metadata PDA.
+    pub fn get_metadata_pda(authority: &Pubkey, vault_index: u64, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
+        let seeds: &[&[u8]] = &[
+            Self::METADATA_SEED,
+            &vault_index.to_le_bytes(),
+            authority,
+            mint,
+            token_program,
+        ];
+        find_program_address(seeds, &crate::ID)
+    }
+    pub fn get_metadata_signer_seeds<'a>(
+        authority: &'a Pubkey,
+        vault_index: &'a [u8; size_of::<u64>()],
+        mint: &'a Pubkey,
+        token_program: &'a Pubkey,
+        bump: &'a [u8]) -> [Seed<'a>; 6] {
+        seeds!(
+            Self::METADATA_SEED,
+            vault_index,
+            authority,
+            mint,
+            token_program,
+            bump
+        )
+    }
+}
@@ -1,7 +1,22 @@
 mod vault_data;
+mod loader;
 pub mod transfer_data;
+pub mod lot_data;
+pub mod depositor_data;
+pub mod metadata_data;
+pub mod registry_data;
+pub mod withdraw_history;
+pub mod transfer_registry;
+pub mod lot_schedule;
+pub mod memo;
+pub mod hash_lock;
+pub mod campaign_data;
+pub mod pledge_data;
 use pinocchio::program_error::ProgramError;
+
+use crate::errors::PimeError;
 pub use vault_data::*;
+pub use loader::*;
 
 /// Trait can be converted from instruction data byte array.
 ///
@@ -26,7 +41,7 @@ pub fn as_bytes<T: Transmutable>(data: &T) -> &[u8] {
 /// Convert a data array to T
 pub fn from_bytes<T: Transmutable>(data: &[u8]) -> Result<&T, ProgramError> {
     if data.len() != core::mem::size_of::<T>() {
-        return Err(ProgramError::InvalidAccountData);
+        return Err(PimeError::InvalidAccountLength.into());
     }
     // Another way of casting, however, to owned variable.
     // Seems to be doing the same "ptr as *const T" casting behind the scenes.
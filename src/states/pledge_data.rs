@@ -0,0 +1,81 @@
+use pinocchio::{instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, create_program_address, find_program_address}, seeds, sysvars::{Sysvar, clock::{Clock, UnixTimestamp}}};
+
+use crate::{errors::PimeError, states::{AccountState, Transmutable}};
+
+/// A vault's pledge to a campaign, held in the campaign's escrow.
+/// Released to the campaign's recipient once the campaign is finalized, or refunded into the
+/// vault otherwise.
+#[repr(C)]
+pub struct PledgeData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub campaign: Pubkey,
+    pub vault_data: Pubkey,
+    amount: [u8; size_of::<u64>()],
+    created: [u8; size_of::<UnixTimestamp>()],
+    pledge_bump: u8,
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for PledgeData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for PledgeData {
+    const DISCRIMINATOR: u8 = 6;
+    const VERSION: u64 = 1;
+}
+
+impl PledgeData {
+    pub const PLEDGE_SEED: &[u8] = b"pledge";
+
+    pub fn new(campaign: Pubkey, vault_data: Pubkey, amount: u64, pledge_bump: u8) -> Result<Self, ProgramError> {
+        Ok(Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            campaign,
+            vault_data,
+            amount: amount.to_le_bytes(),
+            created: Clock::get()?.unix_timestamp.to_le_bytes(),
+            pledge_bump,
+        })
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    pub fn created(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.created)
+    }
+
+    /// Bump of the pledge PDA.
+    pub fn pledge_bump(&self) -> u8 {
+        self.pledge_bump
+    }
+
+    /// Get the pledge's PDA. A vault pledges to a campaign at most once at a time.
+    pub fn get_pledge_pda(campaign: &Pubkey, vault_data: &Pubkey) -> (Pubkey, u8) {
+        find_program_address(&[Self::PLEDGE_SEED, campaign, vault_data], &crate::ID)
+    }
+    /// Get the pledge PDA from its stored bump.
+    pub fn create_pledge_pda(campaign: &Pubkey, vault_data: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[Self::PLEDGE_SEED, campaign, vault_data, &[bump]], &crate::ID)
+            .map_err(|_| PimeError::IncorrectPDA.into())
+    }
+    pub fn get_pledge_signer_seeds<'a>(campaign: &'a Pubkey, vault_data: &'a Pubkey, bump: &'a [u8]) -> [Seed<'a>; 4] {
+        seeds!(
+            Self::PLEDGE_SEED,
+            campaign,
+            vault_data,
+            bump
+        )
+    }
+}
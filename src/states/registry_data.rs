@@ -0,0 +1,131 @@
+use pinocchio::{instruction::Seed, pubkey::{Pubkey, find_program_address}, seeds};
+
+use crate::states::{AccountState, Transmutable};
+
+/// An authority's registry of vaults.
+/// The account's data continues with `count` registry entries, one for each of the authority's
+/// open vaults. The account is resized as vaults are created and closed.
+#[repr(C)]
+pub struct RegistryData {
+    pub discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub authority: Pubkey,
+    next_index: [u8; size_of::<u64>()],
+    count: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for RegistryData {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for RegistryData {
+    const DISCRIMINATOR: u8 = 4;
+    const VERSION: u64 = 1;
+}
+
+impl RegistryData {
+    pub const REGISTRY_SEED: &[u8] = b"registry";
+
+    pub fn new(authority: Pubkey) -> Self {
+        Self {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION.to_le_bytes(),
+            authority,
+            next_index: 0u64.to_le_bytes(),
+            count: 0u64.to_le_bytes(),
+        }
+    }
+
+    /// Account size needed to store `count` entries.
+    pub fn space(count: usize) -> usize {
+        Self::LEN + count * RegistryEntry::LEN
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    /// The lowest vault index which is higher than any index the authority has used.
+    pub fn next_index(&self) -> u64 {
+        u64::from_le_bytes(self.next_index)
+    }
+
+    pub(crate) fn set_next_index(&mut self, val: u64) {
+        self.next_index = val.to_le_bytes();
+    }
+
+    /// Number of entries in the registry.
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+
+    pub(crate) fn set_count(&mut self, val: u64) {
+        self.count = val.to_le_bytes();
+    }
+
+    /// Get the registry's PDA.
+    pub fn get_registry_pda(authority: &Pubkey) -> (Pubkey, u8) {
+        let seeds: &[&[u8]] = &[
+            Self::REGISTRY_SEED,
+            authority,
+        ];
+        find_program_address(seeds, &crate::ID)
+    }
+    pub fn get_registry_signer_seeds<'a>(authority: &'a Pubkey, bump: &'a [u8]) -> [Seed<'a>; 3] {
+        seeds!(
+            Self::REGISTRY_SEED,
+            authority,
+            bump
+        )
+    }
+}
+
+/// A vault in an authority's registry.
+#[repr(C)]
+pub struct RegistryEntry {
+    vault_index: [u8; size_of::<u64>()],
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    status: u8,
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for RegistryEntry {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl RegistryEntry {
+    pub fn new(vault_index: u64, mint: Pubkey, token_program: Pubkey, status: VaultStatus) -> Self {
+        Self {
+            vault_index: vault_index.to_le_bytes(),
+            mint,
+            token_program,
+            status: status as u8,
+        }
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    /// If the entry refers to the given vault.
+    pub fn is_vault(&self, vault_index: u64, mint: &Pubkey, token_program: &Pubkey) -> bool {
+        self.vault_index() == vault_index && self.mint == *mint && self.token_program == *token_program
+    }
+}
+
+/// Status of a registered vault.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VaultStatus {
+    Active = 1,
+}
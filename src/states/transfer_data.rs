@@ -1,6 +1,6 @@
-use pinocchio::{instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, find_program_address}, seeds, sysvars::{Sysvar, clock::{Clock, Epoch, UnixTimestamp}}};
+use pinocchio::{account_info::AccountInfo, instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, create_program_address, find_program_address}, seeds, sysvars::{Sysvar, clock::{Clock, Epoch, UnixTimestamp}}};
 
-use crate::states::Transmutable;
+use crate::{errors::PimeError, states::{AccountState, Transmutable, hash_lock::{HASH_LEN, HashLock, Preimage}, load, memo::Memo}};
 
 #[repr(C)]
 pub struct TransferData {
@@ -8,31 +8,149 @@ pub struct TransferData {
     version: [u8; size_of::<u64>()],
     pub vault_data: Pubkey,
     pub destination: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    vault_index: [u8; size_of::<u64>()],
+    transfer_index: [u8; size_of::<u64>()],
+    transfer_bump: u8,
+    deposit_bump: u8,
     amount: [u8; size_of::<UnixTimestamp>()],
     created: [u8; size_of::<UnixTimestamp>()],
     created_epoch: [u8; size_of::<Epoch>()],
     warmup: [u8; size_of::<UnixTimestamp>()],
     validity: [u8; size_of::<UnixTimestamp>()],
+    allows_crank: u8,
+    crank_tip: [u8; size_of::<u64>()],
+    allows_partial: u8,
+    stream: [u8; size_of::<UnixTimestamp>()],
+    drawn: [u8; size_of::<u64>()],
+    /// Destination the transfer was booked with, which its PDAs are derived from.
+    /// Differs from `destination` once the destination has been amended.
+    pub booked_destination: Pubkey,
+    memo: Memo,
+    claim_link: u8,
+    hash_lock: u8,
+    hash: [u8; HASH_LEN],
+    swap: Pubkey,
+    swap_amount: [u8; size_of::<u64>()],
 }
 
 impl TransferData {
     pub const TRANSFER_SEED: &[u8] = b"transfer";
     pub const DEPOSIT_SEED: &[u8] = b"deposit";
 
-    pub fn new(vault_data: Pubkey, amount: u64, destination: Pubkey, warmup: UnixTimestamp, validity: UnixTimestamp) -> Result<Self, ProgramError> {
+    /// Current layout version.
+    /// Version 0 did not contain the identity fields (mint, token program, indices and bumps).
+    pub const VERSION: u64 = 1;
+    pub const DISCRIMINATOR: u8 = 10;
+
+    pub const VAULT_DATA_OFFSET: usize = core::mem::offset_of!(TransferData, vault_data);
+    pub const DESTINATION_OFFSET: usize = core::mem::offset_of!(TransferData, destination);
+    pub const MINT_OFFSET: usize = core::mem::offset_of!(TransferData, mint);
+    pub const TOKEN_PROGRAM_OFFSET: usize = core::mem::offset_of!(TransferData, token_program);
+    pub const VAULT_INDEX_OFFSET: usize = core::mem::offset_of!(TransferData, vault_index);
+    pub const TRANSFER_INDEX_OFFSET: usize = core::mem::offset_of!(TransferData, transfer_index);
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        vault_data: Pubkey, 
+        amount: u64, 
+        destination: Pubkey, 
+        mint: Pubkey,
+        token_program: Pubkey,
+        vault_index: u64,
+        transfer_index: u64,
+        transfer_bump: u8,
+        deposit_bump: u8,
+        warmup: UnixTimestamp, 
+        validity: UnixTimestamp,
+        allows_crank: bool,
+        crank_tip: u64,
+        allows_partial: bool,
+        stream: UnixTimestamp,
+        memo: Memo,
+        claim_link: bool,
+        hash_lock: HashLock,
+        hash: [u8; HASH_LEN],
+        swap: Pubkey,
+        swap_amount: u64,
+    ) -> Result<Self, ProgramError> {
         let clock = Clock::get()?;
-        Ok(Self { discriminator: 10u8, 
-            version: 0u64.to_le_bytes(),
+        Ok(Self { discriminator: Self::DISCRIMINATOR, 
+            version: Self::VERSION.to_le_bytes(),
             vault_data, 
             amount: amount.to_le_bytes(),
             destination,
+            mint,
+            token_program,
+            vault_index: vault_index.to_le_bytes(),
+            transfer_index: transfer_index.to_le_bytes(),
+            transfer_bump,
+            deposit_bump,
             created: clock.unix_timestamp.to_le_bytes(), 
             created_epoch: clock.epoch.to_le_bytes(),
             warmup: warmup.to_le_bytes(), 
-            validity: validity.to_le_bytes()
+            validity: validity.to_le_bytes(),
+            allows_crank: allows_crank as u8,
+            crank_tip: crank_tip.to_le_bytes(),
+            allows_partial: allows_partial as u8,
+            stream: stream.to_le_bytes(),
+            drawn: 0u64.to_le_bytes(),
+            booked_destination: destination,
+            memo,
+            claim_link: claim_link as u8,
+            hash_lock: hash_lock as u8,
+            hash,
+            swap,
+            swap_amount: swap_amount.to_le_bytes(),
         })
     }
 
+    /// Loads a transfer of any supported version as the current layout. See `load`.
+    /// Version 0 does not store its identity, which is restored from the transfer's seeds.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn load_upgraded(
+        account: &AccountInfo,
+        authority: &Pubkey,
+        destination: &Pubkey,
+        vault_index: u64,
+        transfer_index: u64,
+        mint: &Pubkey,
+        token_program: &Pubkey
+    ) -> Result<TransferData, ProgramError> {
+        if account.data_len() == TransferDataV0::LEN {
+            let transfer_data_v0 = load::<TransferDataV0>(account)?;
+            let transfer_pda = Self::get_transfer_pda(authority, destination, vault_index, transfer_index, mint, token_program);
+            let deposit_pda = Self::get_deposit_pda(authority, destination, vault_index, transfer_index, mint, token_program);
+            return Ok(transfer_data_v0.upgrade(*mint, *token_program, vault_index, transfer_index, transfer_pda.1, deposit_pda.1));
+        }
+        let transfer_data = load::<TransferData>(account)?;
+        // SAFETY: TransferData only consists of u8 based fields, and is read from a valid reference.
+        Ok(unsafe { core::ptr::read(&*transfer_data) })
+    }
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    pub fn transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.transfer_index)
+    }
+
+    /// Bump of the transfer PDA.
+    pub fn transfer_bump(&self) -> u8 {
+        self.transfer_bump
+    }
+
+    /// Bump of the deposit PDA.
+    pub fn deposit_bump(&self) -> u8 {
+        self.deposit_bump
+    }
+
     pub fn amount(&self) -> u64 {
         u64::from_le_bytes(self.amount)
     }
@@ -53,6 +171,131 @@ impl TransferData {
         UnixTimestamp::from_le_bytes(self.validity)
     }
 
+    /// If anyone may execute the transfer within its validity, or refund it once it has expired.
+    pub fn allows_crank(&self) -> bool {
+        self.allows_crank != 0
+    }
+
+    /// Lamports, held by the transfer account on top of its rent, paid to whoever cranks it.
+    /// Returned to the authority if the authority closes the transfer itself.
+    pub fn crank_tip(&self) -> u64 {
+        u64::from_le_bytes(self.crank_tip)
+    }
+
+    /// If the transfer may be drawn in several partial executions.
+    /// Streamed transfers are always drawn partially.
+    pub fn allows_partial(&self) -> bool {
+        self.allows_partial != 0 || self.stream() > 0
+    }
+
+    /// Period after the warm-up over which the amount is released linearly.
+    /// Zero releases the whole amount once warmed up.
+    pub fn stream(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.stream)
+    }
+
+    /// Amount drawn from the deposit so far.
+    pub fn drawn(&self) -> u64 {
+        u64::from_le_bytes(self.drawn)
+    }
+
+    /// Amount released to the destination at `now`, including what has already been drawn.
+    pub fn released(&self, now: UnixTimestamp) -> u64 {
+        let start = self.created().saturating_add(self.warmup());
+        if now < start {
+            return 0;
+        }
+        let stream = self.stream();
+        if stream <= 0 || now - start >= stream {
+            return self.amount();
+        }
+        (self.amount() as u128 * (now - start) as u128 / stream as u128) as u64
+    }
+
+    /// Amount which can be drawn at `now`.
+    pub fn drawable(&self, now: UnixTimestamp) -> u64 {
+        self.released(now).saturating_sub(self.drawn())
+    }
+
+    /// Memo attached to the transaction executing the transfer.
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+
+    /// If the destination is an ephemeral claim key, whose holder claims the transfer to an account of
+    /// their choosing. See `ClaimTransfer`.
+    pub fn is_claim_link(&self) -> bool {
+        self.claim_link != 0
+    }
+
+    /// Hash function the preimage executing the transfer is checked with.
+    pub fn hash_lock(&self) -> Result<HashLock, ProgramError> {
+        HashLock::try_from(self.hash_lock)
+    }
+
+    /// If the transfer can only be executed with the preimage of its hash.
+    pub fn is_hash_locked(&self) -> bool {
+        self.hash_lock != HashLock::None as u8
+    }
+
+    /// Hash whose preimage executes the transfer.
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+
+    /// Checks that the preimage hashes to the transfer's hash. Transfers without a hash lock accept any preimage.
+    pub fn check_preimage(&self, preimage: &Preimage) -> Result<(), ProgramError> {
+        match self.hash_lock()?.hash(preimage.as_slice()?)? {
+            Some(hash) if hash != self.hash => {
+                msg!("Preimage does not match the hash lock.");
+                Err(PimeError::InvalidPreimage.into())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Counterpart transfer of a swap, which has to be settled together with this transfer.
+    /// Zeroed if the transfer is not a swap.
+    pub fn swap(&self) -> &Pubkey {
+        &self.swap
+    }
+
+    /// If the transfer is one side of a swap. See `SettleSwap`.
+    pub fn is_swap(&self) -> bool {
+        self.swap != Pubkey::default()
+    }
+
+    /// Amount the counterpart transfer has to pay in return.
+    pub fn swap_amount(&self) -> u64 {
+        u64::from_le_bytes(self.swap_amount)
+    }
+
+    /// If the transfer swaps with `counterpart`, stored at `counterpart_key`, for its whole amount.
+    pub fn swaps_with(&self, counterpart_key: &Pubkey, counterpart: &TransferData) -> bool {
+        self.is_swap() && self.swap == *counterpart_key && self.swap_amount() == counterpart.amount()
+    }
+
+    /// If the whole amount has been drawn, and the transfer can be closed.
+    pub fn is_drained(&self) -> bool {
+        self.drawn() >= self.amount()
+    }
+
+    pub(crate) fn record_drawn(&mut self, amount: u64) {
+        self.drawn = self.drawn().saturating_add(amount).to_le_bytes();
+    }
+
+    pub(crate) fn set_amount(&mut self, amount: u64) {
+        self.amount = amount.to_le_bytes();
+    }
+
+    pub(crate) fn set_warmup(&mut self, warmup: UnixTimestamp) {
+        self.warmup = warmup.to_le_bytes();
+    }
+
+    pub(crate) fn set_validity(&mut self, validity: UnixTimestamp) {
+        self.validity = validity.to_le_bytes();
+    }
+
     // Get the transfer's PDA.
     // Derived from the vault_data
     pub fn get_transfer_pda(authority: &Pubkey, destination: &Pubkey, vault_index: u64, transfer_index: u64, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
@@ -67,6 +310,19 @@ impl TransferData {
         ];
         find_program_address(seeds, &crate::ID)
     }
+    /// Get the transfer PDA from its stored bump.
+    pub fn create_transfer_pda(authority: &Pubkey, destination: &Pubkey, vault_index: u64, transfer_index: u64, mint: &Pubkey, token_program: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[
+            Self::TRANSFER_SEED,
+            &vault_index.to_le_bytes(),
+            &transfer_index.to_le_bytes(),
+            authority,
+            destination,
+            mint,
+            token_program,
+            &[bump],
+        ], &crate::ID).map_err(|_| PimeError::IncorrectPDA.into())
+    }
     pub fn get_transfer_signer_seeds<'a>(
         authority: &'a Pubkey, 
         destination: &'a Pubkey, 
@@ -101,6 +357,19 @@ impl TransferData {
         ];
         find_program_address(seeds, &crate::ID)
     }
+    /// Get the deposit PDA from its stored bump.
+    pub fn create_deposit_pda(authority: &Pubkey, destination: &Pubkey, vault_index: u64, transfer_index: u64, mint: &Pubkey, token_program: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[
+            Self::DEPOSIT_SEED,
+            &vault_index.to_le_bytes(),
+            &transfer_index.to_le_bytes(),
+            authority,
+            destination,
+            mint,
+            token_program,
+            &[bump],
+        ], &crate::ID).map_err(|_| PimeError::IncorrectPDA.into())
+    }
     pub fn get_deposit_signer_seeds<'a>(
         authority: &'a Pubkey, 
         destination: &'a Pubkey, 
@@ -127,3 +396,83 @@ impl TransferData {
 unsafe impl Transmutable for TransferData {
     const LEN: usize = size_of::<Self>();
 }
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for TransferData {
+    const DISCRIMINATOR: u8 = TransferData::DISCRIMINATOR;
+    const VERSION: u64 = TransferData::VERSION;
+}
+
+/// The version 0 layout of a transfer, which did not contain the identity fields.
+/// Only read to settle transfers booked with it.
+#[repr(C)]
+pub struct TransferDataV0 {
+    discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub vault_data: Pubkey,
+    pub destination: Pubkey,
+    amount: [u8; size_of::<u64>()],
+    created: [u8; size_of::<UnixTimestamp>()],
+    created_epoch: [u8; size_of::<Epoch>()],
+    warmup: [u8; size_of::<UnixTimestamp>()],
+    validity: [u8; size_of::<UnixTimestamp>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for TransferDataV0 {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for TransferDataV0 {
+    const DISCRIMINATOR: u8 = TransferData::DISCRIMINATOR;
+    const VERSION: u64 = TransferDataV0::VERSION;
+}
+
+impl TransferDataV0 {
+    pub const VERSION: u64 = 0;
+
+    /// The current layout of this transfer, with its identity supplied by the caller.
+    pub(crate) fn upgrade(
+        &self,
+        mint: Pubkey,
+        token_program: Pubkey,
+        vault_index: u64,
+        transfer_index: u64,
+        transfer_bump: u8,
+        deposit_bump: u8,
+    ) -> TransferData {
+        TransferData {
+            discriminator: self.discriminator,
+            version: TransferData::VERSION.to_le_bytes(),
+            vault_data: self.vault_data,
+            destination: self.destination,
+            mint,
+            token_program,
+            vault_index: vault_index.to_le_bytes(),
+            transfer_index: transfer_index.to_le_bytes(),
+            transfer_bump,
+            deposit_bump,
+            amount: self.amount,
+            created: self.created,
+            created_epoch: self.created_epoch,
+            warmup: self.warmup,
+            validity: self.validity,
+            allows_crank: 0,
+            crank_tip: 0u64.to_le_bytes(),
+            allows_partial: 0,
+            stream: 0i64.to_le_bytes(),
+            drawn: 0u64.to_le_bytes(),
+            booked_destination: self.destination,
+            memo: Memo::empty(),
+            claim_link: 0,
+            hash_lock: HashLock::None as u8,
+            hash: [0; HASH_LEN],
+            swap: Pubkey::default(),
+            swap_amount: 0u64.to_le_bytes(),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey, sysvars::clock::UnixTimestamp};
+use crate::{errors::PimeError, states::Transmutable};
+
+/// Number of open transfers a vault can register.
+pub const MAX_OPEN_TRANSFERS: usize = 16;
+
+/// A vault's registry of open transfers, stored after its `WithdrawHistory`.
+///
+/// Lists the vault's pending bookings, so that clients can find them without remembering the
+/// parameters of each transfer's PDA. Only the first `count` entries are in use.
+/// Transfers booked before the registry existed are not registered.
+#[repr(C)]
+pub struct TransferRegistry {
+    count: [u8; size_of::<u64>()],
+    entries: [TransferEntry; MAX_OPEN_TRANSFERS],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for TransferRegistry {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl TransferRegistry {
+
+    pub const fn new() -> Self {
+        Self {
+            count: 0u64.to_le_bytes(),
+            entries: [const { TransferEntry::empty() }; MAX_OPEN_TRANSFERS],
+        }
+    }
+
+    /// Number of registered transfers.
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+
+    /// The registered transfers.
+    pub fn entries(&self) -> &[TransferEntry] {
+        &self.entries[..(self.count() as usize).min(MAX_OPEN_TRANSFERS)]
+    }
+
+    /// The registered transfer with the given index.
+    pub(crate) fn entry_mut(&mut self, transfer_index: u64) -> Option<&mut TransferEntry> {
+        let count = (self.count() as usize).min(MAX_OPEN_TRANSFERS);
+        self.entries[..count].iter_mut().find(|e| e.transfer_index() == transfer_index)
+    }
+
+    /// Registers a transfer. Fails if the registry is full.
+    pub(crate) fn register(&mut self, entry: TransferEntry) -> Result<(), ProgramError> {
+        let count = self.count() as usize;
+        if count >= MAX_OPEN_TRANSFERS {
+            msg!("Vault has too many open transfers.");
+            return Err(PimeError::TransferRegistryFull.into());
+        }
+        self.entries[count] = entry;
+        self.count = (count as u64 + 1).to_le_bytes();
+        Ok(())
+    }
+
+    /// Removes a transfer from the registry. Unregistered transfers are ignored.
+    pub(crate) fn unregister(&mut self, transfer_index: u64) {
+        let count = (self.count() as usize).min(MAX_OPEN_TRANSFERS);
+        let Some(position) = self.entries[..count].iter().position(|e| e.transfer_index() == transfer_index) else {
+            return;
+        };
+        // Move the last entry into the removed entry's place.
+        self.entries.swap(position, count - 1);
+        self.entries[count - 1] = TransferEntry::empty();
+        self.count = (count as u64 - 1).to_le_bytes();
+    }
+}
+
+impl Default for TransferRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An open transfer in a vault's registry.
+#[repr(C)]
+pub struct TransferEntry {
+    transfer_index: [u8; size_of::<u64>()],
+    /// Destination the transfer was booked with, from which its PDAs are derived.
+    pub booked_destination: Pubkey,
+    /// Destination the transfer pays, which differs from the booked one once amended.
+    pub destination: Pubkey,
+    amount: [u8; size_of::<u64>()],
+    warmup_end: [u8; size_of::<UnixTimestamp>()],
+    expiry: [u8; size_of::<UnixTimestamp>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for TransferEntry {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl TransferEntry {
+
+    pub fn new(transfer_index: u64, booked_destination: Pubkey, amount: u64, warmup_end: UnixTimestamp, expiry: UnixTimestamp) -> Self {
+        Self {
+            transfer_index: transfer_index.to_le_bytes(),
+            booked_destination,
+            destination: booked_destination,
+            amount: amount.to_le_bytes(),
+            warmup_end: warmup_end.to_le_bytes(),
+            expiry: expiry.to_le_bytes(),
+        }
+    }
+
+    const fn empty() -> Self {
+        Self {
+            transfer_index: [0; size_of::<u64>()],
+            booked_destination: [0; size_of::<Pubkey>()],
+            destination: [0; size_of::<Pubkey>()],
+            amount: [0; size_of::<u64>()],
+            warmup_end: [0; size_of::<UnixTimestamp>()],
+            expiry: [0; size_of::<UnixTimestamp>()],
+        }
+    }
+
+    pub fn transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.transfer_index)
+    }
+
+    /// Amount the transfer has yet to pay out.
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// Timestamp from which the transfer can be executed.
+    pub fn warmup_end(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.warmup_end)
+    }
+
+    /// Timestamp after which the transfer can only be refunded.
+    pub fn expiry(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.expiry)
+    }
+
+    pub(crate) fn amend(&mut self, amount: u64, destination: Pubkey, warmup_end: UnixTimestamp, expiry: UnixTimestamp) {
+        self.amount = amount.to_le_bytes();
+        self.destination = destination;
+        self.warmup_end = warmup_end.to_le_bytes();
+        self.expiry = expiry.to_le_bytes();
+    }
+
+    /// Lowers the amount by a partial payout.
+    pub(crate) fn record_drawn(&mut self, amount: u64) {
+        self.amount = self.amount().saturating_sub(amount).to_le_bytes();
+    }
+}
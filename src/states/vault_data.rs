@@ -1,11 +1,22 @@
-use pinocchio::{instruction::Seed, msg, program_error::ProgramError, pubkey::{Pubkey, find_program_address}, seeds, sysvars::clock::UnixTimestamp};
-use crate::{errors::PimeError, states::Transmutable};
-
+use pinocchio::{instruction::Seed, program_error::ProgramError, pubkey::{Pubkey, create_program_address, find_program_address}, seeds, sysvars::clock::UnixTimestamp};
+use crate::{errors::PimeError, states::{AccountState, Transmutable, lot_schedule::LotSchedule, transfer_registry::TransferRegistry, withdraw_history::WithdrawHistory}};
+
+/// A vault's settings and state.
+/// The account's data continues with the vault's `WithdrawHistory`, followed by its `TransferRegistry`
+/// and its `LotSchedule`.
+///
+/// The identity fields are placed first, at fixed offsets, so that vaults can be found through
+/// `getProgramAccounts` memcmp filters. See the `*_OFFSET` constants.
 #[repr(C)]
 pub struct VaultData {
     pub(crate) discriminator: u8,
     version: [u8; size_of::<u64>()],
     pub(crate) authority: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    vault_index: [u8; size_of::<u64>()],
+    vault_data_bump: u8,
+    vault_bump: u8,
     timeframe: [u8; size_of::<UnixTimestamp>()],
     max_amount: [u8; size_of::<u64>()],
     max_transactions: [u8; size_of::<u64>()],
@@ -14,31 +25,164 @@ pub struct VaultData {
     transfer_max_window: [u8; size_of::<UnixTimestamp>()],
     open_transfers: [u8; size_of::<u64>()],
     transaction_index: [u8; size_of::<u64>()],
+    withdraw_cooldown: [u8; size_of::<UnixTimestamp>()],
+    withdraw_warmup: [u8; size_of::<UnixTimestamp>()],
+    withdraw_warmup_end: [u8; size_of::<UnixTimestamp>()],
+    deposit_policy: u8,
+    min_deposit: [u8; size_of::<u64>()],
+    total_deposited: [u8; size_of::<u64>()],
+    total_withdrawn: [u8; size_of::<u64>()],
+    in_flight_amount: [u8; size_of::<u64>()],
+    total_executed: [u8; size_of::<u64>()],
+    total_unbooked: [u8; size_of::<u64>()],
+    last_activity: [u8; size_of::<UnixTimestamp>()],
+    has_metadata: u8,
+    pending_max_transactions: [u8; size_of::<u64>()],
+    pending_max_transactions_at: [u8; size_of::<UnixTimestamp>()],
+    next_transfer_index: [u8; size_of::<u64>()],
 }
 
 unsafe impl Transmutable for VaultData { 
     const LEN: usize = size_of::<Self>();
 }
 
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for VaultData {
+    const DISCRIMINATOR: u8 = VaultData::DISCRIMINATOR;
+    const VERSION: u64 = VaultData::VERSION;
+}
+
+/// A vault data account together with its withdraw history, transfer registry and lot schedule.
+#[repr(C)]
+pub struct VaultAccount {
+    pub data: VaultData,
+    pub history: WithdrawHistory,
+    pub transfers: TransferRegistry,
+    pub lots: LotSchedule,
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for VaultAccount {
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its vault data, and therefore its discriminator, followed by its version.
+unsafe impl AccountState for VaultAccount {
+    const DISCRIMINATOR: u8 = VaultData::DISCRIMINATOR;
+    const VERSION: u64 = VaultData::VERSION;
+}
+
+/// The version 1 layout of a vault's data, which did not contain the identity fields and ended at
+/// its transaction index. It is followed by `max_transactions` `VaultHistory` entries. Only read
+/// when migrating a vault.
+#[repr(C)]
+pub struct VaultDataV1 {
+    discriminator: u8,
+    version: [u8; size_of::<u64>()],
+    pub authority: Pubkey,
+    timeframe: [u8; size_of::<UnixTimestamp>()],
+    max_amount: [u8; size_of::<u64>()],
+    max_transactions: [u8; size_of::<u64>()],
+    allows_transfers: u8,
+    transfer_min_warmup: [u8; size_of::<UnixTimestamp>()],
+    transfer_max_window: [u8; size_of::<UnixTimestamp>()],
+    open_transfers: [u8; size_of::<u64>()],
+    transaction_index: [u8; size_of::<u64>()],
+}
+
+unsafe impl Transmutable for VaultDataV1 { 
+    const LEN: usize = size_of::<Self>();
+}
+
+/// # SAFETY
+/// Struct starts with its discriminator, followed by its version.
+unsafe impl AccountState for VaultDataV1 {
+    const DISCRIMINATOR: u8 = VaultData::DISCRIMINATOR;
+    const VERSION: u64 = VaultDataV1::VERSION;
+}
+
+impl VaultDataV1 {
+    pub const VERSION: u64 = 1;
+
+    pub fn version(&self) -> u64 {
+        u64::from_le_bytes(self.version)
+    }
+
+    pub fn max_transactions(&self) -> u64 {
+        u64::from_le_bytes(self.max_transactions)
+    }
+
+    /// The current layout of this vault data, with its identity fields filled in. Its settings and
+    /// open transfers are carried over, and the withdraw cool-down and warm-up start out as none.
+    pub(crate) fn upgrade(&self, mint: Pubkey, token_program: Pubkey, vault_index: u64, vault_data_bump: u8, vault_bump: u8) -> VaultData {
+        let mut vault_data = VaultData::new(
+            self.authority,
+            mint,
+            token_program,
+            vault_index,
+            vault_data_bump,
+            vault_bump,
+            UnixTimestamp::from_le_bytes(self.timeframe),
+            u64::from_le_bytes(self.max_amount),
+            self.max_transactions(),
+            self.allows_transfers,
+            UnixTimestamp::from_le_bytes(self.transfer_min_warmup),
+            UnixTimestamp::from_le_bytes(self.transfer_max_window),
+            /* withdraw cooldown */ 0,
+            /* withdraw warmup */ 0,
+        );
+        vault_data.open_transfers = self.open_transfers;
+        vault_data
+    }
+}
+
 #[allow(dead_code)]
 impl VaultData {
     pub const VAULT_SEED: &[u8] = b"vault";
     pub const VAULT_DATA_SEED: &[u8] = b"vault_data";
     pub const VAULT_STAKE_SEED: &[u8] = b"vault_stake";
-
+    /// The current layout version. Version 1 did not contain the identity fields (mint, token
+    /// program, vault index and bumps), ended at the transaction index and was followed by a ring
+    /// of `max_transactions` `VaultHistory` entries, see `VaultDataV1`.
+    pub const VERSION: u64 = 2;
+    /// Space of a vault data account, including its withdraw history, transfer registry and lot schedule.
+    pub const SPACE: usize = VaultData::LEN + WithdrawHistory::LEN + TransferRegistry::LEN + LotSchedule::LEN;
+    pub const DISCRIMINATOR: u8 = 0;
+
+    pub const AUTHORITY_OFFSET: usize = core::mem::offset_of!(VaultData, authority);
+    pub const MINT_OFFSET: usize = core::mem::offset_of!(VaultData, mint);
+    pub const TOKEN_PROGRAM_OFFSET: usize = core::mem::offset_of!(VaultData, token_program);
+    pub const VAULT_INDEX_OFFSET: usize = core::mem::offset_of!(VaultData, vault_index);
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         authority: Pubkey, 
+        mint: Pubkey,
+        token_program: Pubkey,
+        vault_index: u64,
+        vault_data_bump: u8,
+        vault_bump: u8,
         timeframe: i64, 
         max_amount: u64, 
         max_transactions: u64, 
         allows_transfers: u8, 
         transfer_min_warmup: UnixTimestamp, 
-        transfer_max_window: UnixTimestamp
+        transfer_max_window: UnixTimestamp,
+        withdraw_cooldown: UnixTimestamp,
+        withdraw_warmup: UnixTimestamp,
     ) -> Self {
         Self { 
-            discriminator: 0u8, 
-            version: 1u64.to_le_bytes(), 
+            discriminator: Self::DISCRIMINATOR, 
+            version: Self::VERSION.to_le_bytes(), 
             authority, 
+            mint,
+            token_program,
+            vault_index: vault_index.to_le_bytes(),
+            vault_data_bump,
+            vault_bump,
             timeframe: timeframe.to_le_bytes(), 
             max_amount: max_amount.to_le_bytes(),
             max_transactions: max_transactions.to_le_bytes(),
@@ -46,7 +190,22 @@ impl VaultData {
             transfer_max_window: transfer_max_window.to_le_bytes(),
             allows_transfers,
             open_transfers: 0u64.to_le_bytes(),
-            transaction_index: 0u64.to_le_bytes()
+            transaction_index: 0u64.to_le_bytes(),
+            withdraw_cooldown: withdraw_cooldown.to_le_bytes(),
+            withdraw_warmup: withdraw_warmup.to_le_bytes(),
+            withdraw_warmup_end: UnixTimestamp::MIN.to_le_bytes(),
+            deposit_policy: DepositPolicy::Anyone as u8,
+            min_deposit: 0u64.to_le_bytes(),
+            total_deposited: 0u64.to_le_bytes(),
+            total_withdrawn: 0u64.to_le_bytes(),
+            in_flight_amount: 0u64.to_le_bytes(),
+            total_executed: 0u64.to_le_bytes(),
+            total_unbooked: 0u64.to_le_bytes(),
+            last_activity: UnixTimestamp::MIN.to_le_bytes(),
+            has_metadata: 0u8,
+            pending_max_transactions: 0u64.to_le_bytes(),
+            pending_max_transactions_at: UnixTimestamp::MIN.to_le_bytes(),
+            next_transfer_index: 0u64.to_le_bytes(),
         }
     }
 
@@ -58,6 +217,24 @@ impl VaultData {
         self.version = version.to_le_bytes();
     }
 
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    pub fn vault_index(&self) -> u64 {
+        u64::from_le_bytes(self.vault_index)
+    }
+
+    /// Bump of the vault data PDA.
+    pub fn vault_data_bump(&self) -> u8 {
+        self.vault_data_bump
+    }
+
+    /// Bump of the vault PDA.
+    pub fn vault_bump(&self) -> u8 {
+        self.vault_bump
+    }
+
     pub fn timeframe(&self) -> UnixTimestamp {
         UnixTimestamp::from_le_bytes(self.timeframe)
     }
@@ -74,6 +251,45 @@ impl VaultData {
         self.max_transactions = val.to_le_bytes();
     }
 
+    /// Raised max transactions waiting for `pending_max_transactions_at`. 0 if none is pending.
+    pub fn pending_max_transactions(&self) -> u64 {
+        u64::from_le_bytes(self.pending_max_transactions)
+    }
+
+    /// Timestamp from which the pending max transactions applies.
+    pub fn pending_max_transactions_at(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.pending_max_transactions_at)
+    }
+
+    fn clear_pending_max_transactions(&mut self) {
+        self.pending_max_transactions = 0u64.to_le_bytes();
+        self.pending_max_transactions_at = UnixTimestamp::MIN.to_le_bytes();
+    }
+
+    /// Applies the pending max transactions, if its delay has passed by `now`.
+    pub(crate) fn apply_pending_max_transactions(&mut self, now: UnixTimestamp) {
+        if self.pending_max_transactions() != 0 && now >= self.pending_max_transactions_at() {
+            self.max_transactions = self.pending_max_transactions;
+            self.clear_pending_max_transactions();
+        }
+    }
+
+    /// Changes the number of withdraws allowed within a timeframe.
+    ///
+    /// Lowering it tightens the limits, and applies right away. Raising it loosens the limits,
+    /// and only applies once a timeframe has passed. Either replaces a pending change.
+    pub(crate) fn change_max_transactions(&mut self, val: u64, now: UnixTimestamp) {
+        self.apply_pending_max_transactions(now);
+        if val <= self.max_transactions() {
+            self.set_max_transactions(&val);
+            self.clear_pending_max_transactions();
+        }
+        else {
+            self.pending_max_transactions = val.to_le_bytes();
+            self.pending_max_transactions_at = now.saturating_add(self.timeframe()).to_le_bytes();
+        }
+    }
+
     pub fn max_amount(&self) -> u64 {
         u64::from_le_bytes(self.max_amount)
     }
@@ -82,6 +298,20 @@ impl VaultData {
         self.max_amount = val.to_le_bytes();
     }
 
+    /// The transfer index of the vault's next booking, one above the last booked index.
+    pub fn next_transfer_index(&self) -> u64 {
+        u64::from_le_bytes(self.next_transfer_index)
+    }
+
+    /// Assigns the next transfer index to a booking, and moves the counter on, so that indices are
+    /// never reused within the vault.
+    pub(crate) fn take_transfer_index(&mut self) -> Result<u64, ProgramError> {
+        let transfer_index = self.next_transfer_index();
+        self.next_transfer_index = transfer_index.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        Ok(transfer_index)
+    }
+
+    /// Index of the newest entry of the version 1 history ring. Unused by the bucketed `WithdrawHistory`.
     pub fn transaction_index(&self) -> u64 {
         u64::from_le_bytes(self.transaction_index)
     }
@@ -118,6 +348,122 @@ impl VaultData {
         self.open_transfers = val.to_le_bytes();
     }
 
+    /// Minimum time between two consecutive withdraws.
+    pub fn withdraw_cooldown(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.withdraw_cooldown)
+    }
+
+    pub(crate) fn set_withdraw_cooldown(&mut self, val: &UnixTimestamp) {
+        self.withdraw_cooldown = val.to_le_bytes();
+    }
+
+    /// Time a newly created, or newly funded, vault has to wait before its first withdraw.
+    pub fn withdraw_warmup(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.withdraw_warmup)
+    }
+
+    pub(crate) fn set_withdraw_warmup(&mut self, val: &UnixTimestamp) {
+        self.withdraw_warmup = val.to_le_bytes();
+    }
+
+    /// Timestamp from which withdraws are allowed.
+    pub fn withdraw_warmup_end(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.withdraw_warmup_end)
+    }
+
+    pub(crate) fn set_withdraw_warmup_end(&mut self, val: &UnixTimestamp) {
+        self.withdraw_warmup_end = val.to_le_bytes();
+    }
+
+    /// Who may deposit into the vault.
+    pub fn deposit_policy(&self) -> Result<DepositPolicy, ProgramError> {
+        DepositPolicy::try_from(self.deposit_policy)
+    }
+
+    pub(crate) fn set_deposit_policy(&mut self, val: DepositPolicy) {
+        self.deposit_policy = val as u8;
+    }
+
+    /// Minimum amount of a single deposit.
+    pub fn min_deposit(&self) -> u64 {
+        u64::from_le_bytes(self.min_deposit)
+    }
+
+    pub(crate) fn set_min_deposit(&mut self, val: &u64) {
+        self.min_deposit = val.to_le_bytes();
+    }
+
+    /// Total amount ever deposited into the vault.
+    pub fn total_deposited(&self) -> u64 {
+        u64::from_le_bytes(self.total_deposited)
+    }
+
+    /// Total amount ever withdrawn from the vault.
+    pub fn total_withdrawn(&self) -> u64 {
+        u64::from_le_bytes(self.total_withdrawn)
+    }
+
+    /// Amount currently booked in transfers which are neither executed nor unbooked.
+    pub fn in_flight_amount(&self) -> u64 {
+        u64::from_le_bytes(self.in_flight_amount)
+    }
+
+    /// Total amount ever sent through executed transfers.
+    pub fn total_executed(&self) -> u64 {
+        u64::from_le_bytes(self.total_executed)
+    }
+
+    /// Total amount ever returned to the vault by unbooked transfers.
+    pub fn total_unbooked(&self) -> u64 {
+        u64::from_le_bytes(self.total_unbooked)
+    }
+
+    /// Timestamp of the vault's last deposit, withdraw or transfer.
+    pub fn last_activity(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.last_activity)
+    }
+
+    /// If the vault has a metadata account, which has to be closed along with the vault.
+    pub fn has_metadata(&self) -> bool {
+        self.has_metadata != 0
+    }
+
+    pub(crate) fn set_has_metadata(&mut self, val: bool) {
+        self.has_metadata = val as u8;
+    }
+
+    pub(crate) fn record_deposit(&mut self, amount: u64, now: UnixTimestamp) {
+        self.total_deposited = self.total_deposited().saturating_add(amount).to_le_bytes();
+        self.last_activity = now.to_le_bytes();
+    }
+
+    pub(crate) fn record_withdraw(&mut self, amount: u64, now: UnixTimestamp) {
+        self.total_withdrawn = self.total_withdrawn().saturating_add(amount).to_le_bytes();
+        self.last_activity = now.to_le_bytes();
+    }
+
+    pub(crate) fn record_booked(&mut self, amount: u64, now: UnixTimestamp) {
+        self.in_flight_amount = self.in_flight_amount().saturating_add(amount).to_le_bytes();
+        self.last_activity = now.to_le_bytes();
+    }
+
+    pub(crate) fn record_executed(&mut self, amount: u64, now: UnixTimestamp) {
+        self.in_flight_amount = self.in_flight_amount().saturating_sub(amount).to_le_bytes();
+        self.total_executed = self.total_executed().saturating_add(amount).to_le_bytes();
+        self.last_activity = now.to_le_bytes();
+    }
+
+    pub(crate) fn record_unbooked(&mut self, amount: u64, now: UnixTimestamp) {
+        self.in_flight_amount = self.in_flight_amount().saturating_sub(amount).to_le_bytes();
+        self.total_unbooked = self.total_unbooked().saturating_add(amount).to_le_bytes();
+        self.last_activity = now.to_le_bytes();
+    }
+
+    /// Restarts the withdraw warm-up from `now`.
+    pub(crate) fn start_withdraw_warmup(&mut self, now: UnixTimestamp) {
+        self.set_withdraw_warmup_end(&now.saturating_add(self.withdraw_warmup()));
+    }
+
     /// Calculates the vault data PDA with bump.
     /// If the vault is storing native token (SOL), do not provide mint and token program.
     /// If the vault is storing SPL tokens, provide the corresponding mint and token program.
@@ -145,6 +491,19 @@ impl VaultData {
         )
     }
     
+    /// Get the vault data PDA from its stored bump.
+    /// Much cheaper than `get_vault_data_pda`, as no bump has to be searched for.
+    pub fn create_vault_data_pda(authority: &Pubkey, vault_index: u64, mint: &Pubkey, token_program: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[
+            VaultData::VAULT_DATA_SEED,
+            &vault_index.to_le_bytes(),
+            authority,
+            mint,
+            token_program,
+            &[bump],
+        ], &crate::ID).map_err(|_| PimeError::IncorrectPDA.into())
+    }
+
     /// Get the Vault PDA
     pub fn get_vault_pda(authority: &Pubkey, index: u64, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
         let seeds: &[&[u8]] = &[
@@ -156,6 +515,17 @@ impl VaultData {
         ];
         find_program_address(seeds, &crate::ID)
     }
+    /// Get the vault PDA from its stored bump.
+    pub fn create_vault_pda(authority: &Pubkey, vault_index: u64, mint: &Pubkey, token_program: &Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+        create_program_address(&[
+            VaultData::VAULT_SEED,
+            &vault_index.to_le_bytes(),
+            authority,
+            mint,
+            token_program,
+            &[bump],
+        ], &crate::ID).map_err(|_| PimeError::IncorrectPDA.into())
+    }
     pub fn get_vault_signer_seeds<'a>(authority: &'a Pubkey, vault_index: &'a [u8; size_of::<u64>()], mint: &'a Pubkey, token_program: &'a Pubkey, bump: &'a [u8]) -> [Seed<'a>; 6] {
         seeds!(
             VaultData::VAULT_SEED,
@@ -180,43 +550,58 @@ impl VaultData {
         &*(vault_bytes.as_ptr() as *const VaultData)
     }
 
-    /// Try to get the next withdraw index in the ptr data.
+    /// Checks that the vault's withdraw warm-up has passed, and that the newest withdraw
+    /// is older than the vault's cool-down.
+    pub fn check_withdraw_timing(&self, last_withdraw: UnixTimestamp, now: UnixTimestamp) -> Result<(), ProgramError> {
+        if now < self.withdraw_warmup_end() {
+            msg!("Vault is warming up.");
+            return Err(PimeError::VaultWarmingUp.into());
+        }
+        // A vault which has not been withdrawn from is stamped with UnixTimestamp::MIN.
+        if last_withdraw != UnixTimestamp::MIN 
+            && now < last_withdraw.saturating_add(self.withdraw_cooldown()) {
+            msg!("Vault withdraw is cooling down.");
+            return Err(PimeError::WithdrawCoolingDown.into());
+        }
+        Ok(())
+    }
+
+    /// Get the version 1 history entry at `index`.
     ///
     /// # SAFETY
-    /// ptr is a valid representation of an array of VaultHistory byte array.
-    pub unsafe fn can_withdraw(ptr: *const u8, now: i64, last_index: u64, amount: u64, max_transactions: u64, max_amount: u64, timeframe: UnixTimestamp) -> Result<VaultHistory, ProgramError> {
-        let mut tot_amount: u64 = 0;
-        let mut index: u64 = last_index;
-        const LEN: u64 = VaultHistory::LEN as u64;
-        let mut history;
-
-        // Loop all history
-        for _ in 0..max_transactions {
-            history = unsafe { &*(ptr.add((index * LEN) as usize) as *const VaultHistory) };
-
-            // If now is past the history time stamp and vault time frame.
-            if history.timestamp() + timeframe < now {
-                if tot_amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)? > max_amount {
-                    msg!("Vault withdraw amount limit reached.");
-                    return Err(PimeError::WithdrawLimitReachedAmount.into());
-                }
-                return Ok(VaultHistory::new(now, amount));
-            }
-            else {
-                tot_amount = tot_amount.checked_add(history.amount()).ok_or(ProgramError::ArithmeticOverflow)?; 
-
-                // Get previous timestamp
-                index -= 1;
-                if index == 0 {
-                    index = max_transactions - 1;
-                }
-            }
+    /// ptr is a valid representation of an array of VaultHistory byte array, 
+    /// and index is within its bounds.
+    pub unsafe fn history_at<'a>(ptr: *const u8, index: u64) -> &'a VaultHistory {
+        &*(ptr.add(index as usize * VaultHistory::LEN) as *const VaultHistory)
+    }
+}
+
+/// Who may deposit into a vault.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DepositPolicy {
+    /// Anyone may deposit. Third party deposits are recorded.
+    Anyone = 0,
+    /// Only the vault's authority may deposit.
+    AuthorityOnly = 1,
+    /// The authority, and depositors allowed by the authority, may deposit.
+    Allowlist = 2,
+}
+
+impl TryFrom<u8> for DepositPolicy {
+    type Error = ProgramError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DepositPolicy::Anyone),
+            1 => Ok(DepositPolicy::AuthorityOnly),
+            2 => Ok(DepositPolicy::Allowlist),
+            _ => Err(PimeError::InvalidDepositPolicy.into()),
         }
-        msg!("Vault withdraw transaction limit reached.");
-        Err(PimeError::WithdrawLimitReachedTransactions.into())
     }
 }
 
+/// A withdraw entry of the version 1 history, which stored `max_transactions` entries in a ring.
+/// Only read when migrating a vault to the bucketed `WithdrawHistory`.
 #[repr(C)]
 pub struct VaultHistory {
     timestamp: [u8; size_of::<UnixTimestamp>()],
@@ -0,0 +1,165 @@
+use pinocchio::{program_error::ProgramError, sysvars::clock::UnixTimestamp};
+use crate::{errors::PimeError, states::Transmutable};
+
+/// Number of time buckets a vault's timeframe is split into.
+pub const HISTORY_BUCKETS: usize = 24;
+
+/// A vault's withdraw history, stored directly after its `VaultData`.
+///
+/// The vault's timeframe is split into `HISTORY_BUCKETS` buckets, each summing the amount and
+/// count of the withdraws made within it. The sums of the buckets within the timeframe are kept
+/// as running totals, so checking a withdraw costs the same regardless of `max_transactions`.
+///
+/// Withdraws are tracked with the resolution of one bucket. A withdraw counts towards the limits
+/// for at least the timeframe, and at most `HISTORY_BUCKETS` bucket widths.
+#[repr(C)]
+pub struct WithdrawHistory {
+    bucket_width: [u8; size_of::<UnixTimestamp>()],
+    newest_bucket: [u8; size_of::<i64>()],
+    last_withdraw: [u8; size_of::<UnixTimestamp>()],
+    window_amount: [u8; size_of::<u64>()],
+    window_count: [u8; size_of::<u64>()],
+    buckets: [HistoryBucket; HISTORY_BUCKETS],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for WithdrawHistory {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl WithdrawHistory {
+
+    pub fn new(timeframe: UnixTimestamp) -> Self {
+        // One bucket is left for the partially passed bucket, so that the window always spans
+        // at least the timeframe.
+        let spans = HISTORY_BUCKETS as i64 - 1;
+        let bucket_width = (timeframe.saturating_add(spans - 1) / spans).max(1);
+        Self {
+            bucket_width: bucket_width.to_le_bytes(),
+            newest_bucket: 0i64.to_le_bytes(),
+            last_withdraw: UnixTimestamp::MIN.to_le_bytes(),
+            window_amount: 0u64.to_le_bytes(),
+            window_count: 0u64.to_le_bytes(),
+            buckets: [const { HistoryBucket::empty() }; HISTORY_BUCKETS],
+        }
+    }
+
+    /// Time covered by each bucket.
+    pub fn bucket_width(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.bucket_width)
+    }
+
+    /// Id, `timestamp / bucket_width`, of the newest bucket in the window.
+    pub fn newest_bucket(&self) -> i64 {
+        i64::from_le_bytes(self.newest_bucket)
+    }
+
+    /// Timestamp of the newest withdraw. `UnixTimestamp::MIN` if the vault has not been withdrawn from.
+    pub fn last_withdraw(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.last_withdraw)
+    }
+
+    /// Amount withdrawn within the window.
+    pub fn window_amount(&self) -> u64 {
+        u64::from_le_bytes(self.window_amount)
+    }
+
+    /// Number of withdraws within the window.
+    pub fn window_count(&self) -> u64 {
+        u64::from_le_bytes(self.window_count)
+    }
+
+    pub fn bucket(&self, index: usize) -> &HistoryBucket {
+        &self.buckets[index]
+    }
+
+    fn bucket_id(&self, timestamp: UnixTimestamp) -> i64 {
+        timestamp.div_euclid(self.bucket_width())
+    }
+
+    /// Moves the window forward to `now`, dropping the buckets which have left it from the totals.
+    pub(crate) fn roll(&mut self, now: UnixTimestamp) {
+        let current = self.bucket_id(now);
+        let newest = self.newest_bucket();
+        if current <= newest {
+            return;
+        }
+
+        if current.saturating_sub(newest) >= HISTORY_BUCKETS as i64 {
+            // Every bucket has left the window.
+            self.buckets = [const { HistoryBucket::empty() }; HISTORY_BUCKETS];
+            self.window_amount = 0u64.to_le_bytes();
+            self.window_count = 0u64.to_le_bytes();
+        }
+        else {
+            for id in (newest + 1)..=current {
+                // The slot holds the bucket `HISTORY_BUCKETS` ids older, which now left the window.
+                let bucket = &mut self.buckets[id.rem_euclid(HISTORY_BUCKETS as i64) as usize];
+                let (amount, count) = (bucket.amount(), bucket.count());
+                *bucket = HistoryBucket::empty();
+                self.window_amount = self.window_amount().saturating_sub(amount).to_le_bytes();
+                self.window_count = self.window_count().saturating_sub(count).to_le_bytes();
+            }
+        }
+        self.newest_bucket = current.to_le_bytes();
+    }
+
+    /// Checks that one more withdraw of `amount` stays within the vault's limits.
+    /// The history must have been rolled to the current time.
+    pub fn can_withdraw(&self, amount: u64, max_amount: u64, max_transactions: u64) -> Result<(), ProgramError> {
+        if self.window_count() >= max_transactions {
+            msg!("Vault withdraw transaction limit reached.");
+            return Err(PimeError::WithdrawLimitReachedTransactions.into());
+        }
+        if self.window_amount().checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)? > max_amount {
+            msg!("Vault withdraw amount limit reached.");
+            return Err(PimeError::WithdrawLimitReachedAmount.into());
+        }
+        Ok(())
+    }
+
+    /// Adds a withdraw to the bucket of `timestamp`.
+    /// The history must have been rolled to, or past, `timestamp`, and the bucket must still be within the window.
+    pub(crate) fn record(&mut self, timestamp: UnixTimestamp, amount: u64) -> Result<(), ProgramError> {
+        let index = self.bucket_id(timestamp).rem_euclid(HISTORY_BUCKETS as i64) as usize;
+        let bucket = &mut self.buckets[index];
+        bucket.amount = bucket.amount().checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        bucket.count = bucket.count().saturating_add(1).to_le_bytes();
+
+        self.window_amount = self.window_amount().checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        self.window_count = self.window_count().saturating_add(1).to_le_bytes();
+        if timestamp > self.last_withdraw() {
+            self.last_withdraw = timestamp.to_le_bytes();
+        }
+        Ok(())
+    }
+}
+
+/// The sums of the withdraws made within one bucket width.
+#[repr(C)]
+pub struct HistoryBucket {
+    amount: [u8; size_of::<u64>()],
+    count: [u8; size_of::<u64>()],
+}
+
+/// # SAFETY
+/// Struct does not contain padding.
+unsafe impl Transmutable for HistoryBucket {
+    const LEN: usize = size_of::<Self>();
+}
+
+impl HistoryBucket {
+
+    pub const fn empty() -> Self {
+        Self { amount: [0; size_of::<u64>()], count: [0; size_of::<u64>()] }
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+}
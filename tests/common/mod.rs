@@ -1,23 +1,56 @@
 // Helpers 
+#![allow(dead_code)]
 
     use std::env::current_dir;
     use std::path::Path;
 
     use litesvm::LiteSVM;
-    use litesvm::types::TransactionResult;
+    use pime::errors::PimeError;
+    use pime::interface::instructions::amend_transfer_instruction::AmendTransferInstructionData;
     use pime::interface::instructions::book_transfer::BookTransferInstructionData;
+    use pime::interface::instructions::claim_transfer_instruction::ClaimTransferInstructionData;
     use pime::interface::instructions::close_vault_instruction::CloseVaultInstructionData;
+    use pime::interface::instructions::create_campaign_instruction::CreateCampaignInstructionData;
+    use pime::interface::instructions::crank_transfer_instruction::CrankTransferInstructionData;
     use pime::interface::instructions::create_vault_instruction::CreateVaultInstructionData;
     use pime::interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData;
+    use pime::interface::instructions::draw_transfer_instruction::DrawTransferInstructionData;
     use pime::interface::instructions::execute_transfer::ExecuteTransferInstructionData;
+    use pime::interface::instructions::finalize_campaign_instruction::FinalizeCampaignInstructionData;
+    use pime::interface::instructions::migrate_account_instruction::MigrateAccountInstructionData;
+    use pime::interface::instructions::pledge_to_campaign_instruction::PledgeToCampaignInstructionData;
+    use pime::interface::instructions::release_lot_instruction::ReleaseLotInstructionData;
+    use pime::interface::instructions::return_deposit_instruction::ReturnDepositInstructionData;
+    use pime::interface::instructions::set_deposit_policy_instruction::SetDepositPolicyInstructionData;
+    use pime::interface::instructions::set_depositor_instruction::SetDepositorInstructionData;
+    use pime::interface::instructions::set_max_transactions_instruction::SetMaxTransactionsInstructionData;
+    use pime::interface::instructions::set_vault_metadata_instruction::SetVaultMetadataInstructionData;
+    use pime::interface::instructions::settle_pledge_instruction::SettlePledgeInstructionData;
+    use pime::interface::instructions::settle_swap_instruction::SettleSwapInstructionData;
     use pime::interface::instructions::unbook_transfer_instruction::UnbookTransferInstructionData;
     use pime::interface::instructions::withdraw_from_vault::WithdrawFromVaultInstructionData;
+    use pime::interface::instructions::withdraw_pledge_instruction::WithdrawPledgeInstructionData;
+    use pime::interface::parser::InstructionData;
+    use pime::states::campaign_data::CampaignData;
+    use pime::states::depositor_data::DepositorData;
+    use pime::states::lot_data::LotData;
+    use pime::states::memo::{MEMO_PROGRAM_ID, Memo};
+    use pime::states::metadata_data::MetadataData;
+    use pime::states::pledge_data::PledgeData;
+    use pime::states::registry_data::{RegistryData, RegistryEntry};
     use pime::states::transfer_data::TransferData;
-    use pime::states::{Transmutable, VaultData, VaultHistory, as_bytes, from_bytes};
+    use pime::states::transfer_registry::TransferRegistry;
+    use pime::states::lot_schedule::LotSchedule;
+    use pime::states::withdraw_history::{HISTORY_BUCKETS, WithdrawHistory};
+    use pime::states::{Transmutable, VaultData, VaultHistory, from_bytes};
     use pinocchio::sysvars::clock::UnixTimestamp;
+    use solana_sdk::clock::Clock;
+    use solana_sdk::instruction::InstructionError;
     use solana_sdk::message::{AccountMeta, Instruction};
+    use solana_sdk::transaction::TransactionError;
     use solana_sdk::pubkey::PUBKEY_BYTES;
     use solana_sdk::{message::Message, program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+    use spl_associated_token_account_interface::address::get_associated_token_address;
     use spl_associated_token_account_interface::instruction::create_associated_token_account_idempotent;
     use spl_token_interface::state::Mint;
     use spl_token_interface::state::Account as TokenAccount;
@@ -34,7 +67,7 @@ pub fn create_svm() -> LiteSVM {
     svm
 }
 
-pub fn initialize_mint(svm: &mut LiteSVM, authority: &Pubkey, payer: &Keypair, mint: &Keypair, token_program: &Pubkey) -> TransactionResult {
+pub fn initialize_mint(svm: &mut LiteSVM, authority: &Pubkey, payer: &Keypair, mint: &Keypair, token_program: &Pubkey) {
 
     let create_mint_account_inst = solana_system_interface::instruction::create_account(
         /* from */ &payer.pubkey(), 
@@ -64,9 +97,12 @@ pub fn initialize_mint(svm: &mut LiteSVM, authority: &Pubkey, payer: &Keypair, m
         svm.latest_blockhash()
     );
 
-    svm.send_transaction(tx)
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to initialize mint: {:#?}", e);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn mint_to(
     svm: &mut LiteSVM, 
     amount: u64, 
@@ -127,7 +163,10 @@ pub fn create_new_vault(
         TOKEN_PROGRAM.as_array()
     );
 
-    let data = as_bytes(inst_data);
+    let registry = find_registry_pda(authority.pubkey().as_array());
+    let registry_pre_count = registry_entries(svm, &registry.0).len();
+
+    let data = inst_data.to_bytes();
 
     let create_vault_inst = Instruction::new_with_bytes(
         /* program id*/     PIME_ID, 
@@ -139,6 +178,7 @@ pub fn create_new_vault(
             AccountMeta::new_readonly(*mint, false),
             AccountMeta::new_readonly(TOKEN_PROGRAM, false),
             AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+            AccountMeta::new(registry.0, false),
         ].to_vec()
     );
 
@@ -153,45 +193,138 @@ pub fn create_new_vault(
     };
 
     let vault_data_bytes = svm.get_account(&vault_data.0).unwrap().data;
-    assert_eq!(vault_data_bytes.len(), size_of::<VaultData>() + size_of::<VaultHistory>() * inst_data.max_transactions() as usize);
+    assert_eq!(vault_data_bytes.len(), VaultData::SPACE);
     // # SAFETY Data bytes are of type Vault
     let vault_acc = from_bytes::<VaultData>(&vault_data_bytes[0.. size_of::<VaultData>()]).unwrap();
     assert_eq!(vault_acc.timeframe(), inst_data.timeframe());
     assert_eq!(vault_acc.max_transactions(), inst_data.max_transactions());
     assert_eq!(vault_acc.max_amount(), inst_data.max_amount());
     assert_eq!(vault_acc.open_transfers(), 0);
+    assert_eq!(vault_acc.withdraw_cooldown(), inst_data.withdraw_cooldown());
+    assert_eq!(vault_acc.withdraw_warmup(), inst_data.withdraw_warmup());
+    assert_eq!(vault_acc.version(), VaultData::VERSION);
+    assert_eq!(vault_acc.vault_index(), inst_data.vault_index());
+    assert_eq!(vault_acc.vault_data_bump(), vault_data.1);
+    assert_eq!(vault_acc.vault_bump(), vault.1);
+    // Identity fields can be used in memcmp filters.
+    assert_eq!(&vault_data_bytes[VaultData::AUTHORITY_OFFSET..VaultData::AUTHORITY_OFFSET + PUBKEY_BYTES], authority.pubkey().as_array());
+    assert_eq!(&vault_data_bytes[VaultData::MINT_OFFSET..VaultData::MINT_OFFSET + PUBKEY_BYTES], mint.as_array());
+    assert_eq!(&vault_data_bytes[VaultData::TOKEN_PROGRAM_OFFSET..VaultData::TOKEN_PROGRAM_OFFSET + PUBKEY_BYTES], TOKEN_PROGRAM.as_array());
+    assert_eq!(&vault_data_bytes[VaultData::VAULT_INDEX_OFFSET..VaultData::VAULT_INDEX_OFFSET + size_of::<u64>()], &inst_data.vault_index().to_le_bytes());
+
+    let history = from_bytes::<WithdrawHistory>(&vault_data_bytes[VaultData::LEN..VaultData::LEN + WithdrawHistory::LEN]).unwrap();
+    assert_eq!(history.last_withdraw(), UnixTimestamp::MIN);
+    assert_eq!(history.window_amount(), 0);
+    assert_eq!(history.window_count(), 0);
+    assert!(history.bucket_width() * (HISTORY_BUCKETS as i64 - 1) >= inst_data.timeframe());
+    assert!(transfer_entries(svm, &vault_data.0).is_empty());
+
+    // The vault is registered under its authority.
+    let entries = registry_entries(svm, &registry.0);
+    assert_eq!(entries.len(), registry_pre_count + 1);
+    assert!(entries.contains(&(inst_data.vault_index(), *mint)));
+    let registry_acc = svm.get_account(&registry.0).unwrap();
+    let registry_data = from_bytes::<RegistryData>(&registry_acc.data[..RegistryData::LEN]).unwrap();
+    assert!(registry_data.next_index() > inst_data.vault_index());
+}
 
-    for i in 0.. (inst_data.max_transactions() as usize) {
-        let range = VaultData::LEN + i * VaultHistory::LEN;
-        let history = from_bytes::<VaultHistory>(&vault_data_bytes[range .. range + VaultHistory::LEN]).unwrap();
-        assert_eq!(history.timestamp(), UnixTimestamp::MIN);
-        assert_eq!(history.amount(), u64::MIN);
-    }
+/// Returns the transfer index, booked destination, destination and outstanding amount of every transfer
+/// in the vault's transfer registry.
+pub fn transfer_entries(svm: &LiteSVM, vault_data: &Pubkey) -> Vec<(u64, Pubkey, Pubkey, u64)> {
+    let vault_data_acc = svm.get_account(vault_data).unwrap();
+    let start = VaultData::LEN + WithdrawHistory::LEN;
+    let registry = from_bytes::<TransferRegistry>(&vault_data_acc.data[start..start + TransferRegistry::LEN]).unwrap();
+    registry.entries()
+        .iter()
+        .map(|e| (e.transfer_index(), Pubkey::new_from_array(e.booked_destination), Pubkey::new_from_array(e.destination), e.amount()))
+        .collect()
+}
+
+/// Returns the outstanding amount of the registered transfer.
+pub fn transfer_entry_amount(svm: &LiteSVM, vault_data: &Pubkey, transfer_index: u64) -> u64 {
+    transfer_entries(svm, vault_data).iter().find(|e| e.0 == transfer_index).unwrap().3
+}
+
+/// Returns the lot index, amount and maturity of every lot in the vault's lot schedule.
+pub fn lot_entries(svm: &LiteSVM, vault_data: &Pubkey) -> Vec<(u64, u64, i64)> {
+    let vault_data_acc = svm.get_account(vault_data).unwrap();
+    let start = VaultData::LEN + WithdrawHistory::LEN + TransferRegistry::LEN;
+    let schedule = from_bytes::<LotSchedule>(&vault_data_acc.data[start..start + LotSchedule::LEN]).unwrap();
+    schedule.entries()
+        .iter()
+        .map(|e| (e.lot_index(), e.amount(), e.maturity()))
+        .collect()
+}
+
+/// Returns the vault index and mint of every vault in the registry.
+pub fn registry_entries(svm: &LiteSVM, registry: &Pubkey) -> Vec<(u64, Pubkey)> {
+    let Some(registry_acc) = svm.get_account(registry) else {
+        return Vec::new();
+    };
+    let registry_data = from_bytes::<RegistryData>(&registry_acc.data[..RegistryData::LEN]).unwrap();
+    assert_eq!(registry_acc.data.len(), RegistryData::space(registry_data.count() as usize));
+    (0..registry_data.count() as usize)
+        .map(|i| {
+            let start = RegistryData::space(i);
+            let entry = from_bytes::<RegistryEntry>(&registry_acc.data[start..start + RegistryEntry::LEN]).unwrap();
+            (entry.vault_index(), Pubkey::new_from_array(entry.mint))
+        })
+        .collect()
+}
+
+/// Creates a mint, mints to the authority's ATA, creates a vault, and deposits into it.
+/// Returns the mint and the authority's ATA.
+pub fn create_funded_vault(svm: &mut LiteSVM, authority: &Keypair, inst_data: &CreateVaultInstructionData, mint_amount: u64, deposit_amount: u64) -> (Keypair, Pubkey) {
+    let mint = Keypair::new();
+    let authority_ata = get_associated_token_address(&authority.pubkey(), &mint.pubkey());
+    initialize_mint(svm, &authority.pubkey(), authority, &mint, &TOKEN_PROGRAM);
+    mint_to(svm, mint_amount, authority, &authority.pubkey(), &authority_ata, authority, &mint.pubkey(), &TOKEN_PROGRAM);
+    create_new_vault(svm, authority, inst_data, &mint.pubkey());
+    deposit_to_vault(
+        svm, 
+        &authority_ata, 
+        authority, 
+        &mint.pubkey(), 
+        &DepositToVaultInstructionData::new(authority.pubkey().to_bytes(), inst_data.vault_index(), deposit_amount));
+    (mint, authority_ata)
 }
 
 pub fn deposit_to_vault(svm: &mut LiteSVM, from_acc: &Pubkey, from_authority: &Keypair, mint: &Pubkey, inst_data: &DepositToVaultInstructionData) {
-    let buf = as_bytes(inst_data);
+    let buf = inst_data.to_bytes();
     println!("deposit instruction inst index: {}, amount: {}", inst_data.vault_index(), inst_data.amount());
     println!("deposit instruction bytes {:?}", buf);
 
+    let vault_owner = inst_data.vault_owner();
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        &vault_owner, 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
     let vault = find_vault_pda(
         inst_data.vault_index(), 
-        from_authority.pubkey().as_array(), 
+        &vault_owner, 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let lot = find_lot_pda(
+        inst_data.vault_index(), 
+        inst_data.lot_index(), 
+        &vault_owner, 
         mint.as_array(),
         TOKEN_PROGRAM.as_array()
     );
 
-    let deposit_inst = Instruction::new_with_bytes(
-        PIME_ID,
-        buf, 
-        [
-            AccountMeta::new(from_authority.pubkey(), true),
-            AccountMeta::new(*from_acc, false),
-            AccountMeta::new(vault.0, false),
-            AccountMeta::new_readonly(*mint, false),
-            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
-            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
-        ].to_vec());
+    let vault_pre_amount = if let Some(a) = &svm.get_account(&vault.0) {
+        TokenAccount::unpack(&a.data).unwrap().amount
+    }
+    else { 0 };
+    let deposited_pre_amount = if let Some(a) = &svm.get_account(&vault_data.0) {
+        from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap().total_deposited()
+    }
+    else { 0 };
+
+    let deposit_inst = deposit_to_vault_instruction(&from_authority.pubkey(), from_acc, mint, inst_data);
 
     let tx = Transaction::new(
         &[&from_authority], 
@@ -203,252 +336,1204 @@ pub fn deposit_to_vault(svm: &mut LiteSVM, from_acc: &Pubkey, from_authority: &K
 
     let vault_acc = svm.get_account(&vault.0).unwrap();
     let vault_token = TokenAccount::unpack(&vault_acc.data).unwrap();
-    assert_eq!(vault_token.amount, inst_data.amount());
+    assert_eq!(vault_token.amount, vault_pre_amount + inst_data.amount());
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    assert_eq!(
+        from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap().total_deposited(),
+        deposited_pre_amount + inst_data.amount());
+
+    // Check that a locked deposit created its lot.
+    if inst_data.maturity() != 0 {
+        let lot_acc = svm.get_account(&lot.0).unwrap();
+        let lot_data = from_bytes::<LotData>(&lot_acc.data).unwrap();
+        assert_eq!(lot_data.amount(), inst_data.amount());
+        assert_eq!(lot_data.maturity(), inst_data.maturity());
+        assert!(lot_entries(svm, &vault_data.0).contains(&(inst_data.lot_index(), inst_data.amount(), inst_data.maturity())));
+    }
 }
 
-pub fn withdraw_from_vault(
-    svm: &mut LiteSVM, 
-    authority: &Keypair, 
-    to: &Pubkey,
-    mint: &Pubkey, 
-    token_program: &Pubkey, 
-    inst_data: &WithdrawFromVaultInstructionData) {
-
-    let inst_bytes = as_bytes(inst_data);
-
+pub fn deposit_to_vault_instruction(from_authority: &Pubkey, from_acc: &Pubkey, mint: &Pubkey, inst_data: &DepositToVaultInstructionData) -> Instruction {
+    let vault_owner = inst_data.vault_owner();
     let vault_data = find_vault_data_pda(
         inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
+        &vault_owner, 
         mint.as_array(),
         TOKEN_PROGRAM.as_array()
     );
     let vault = find_vault_pda(
         inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
+        &vault_owner, 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let lot = find_lot_pda(
+        inst_data.vault_index(), 
+        inst_data.lot_index(), 
+        &vault_owner, 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let depositor = find_depositor_pda(
+        inst_data.vault_index(), 
+        &vault_owner, 
+        from_authority.as_array(), 
         mint.as_array(),
         TOKEN_PROGRAM.as_array()
     );
 
-    let to_ata_account = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap();
-    let to_pre_amount = to_ata_account.amount;
-    let vault_account = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
-    let vault_pre_amount = vault_account.amount;
-
+    Instruction::new_with_bytes(
+        PIME_ID,
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*from_authority, true),
+            AccountMeta::new(*from_acc, false),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+            AccountMeta::new(lot.0, false),
+            AccountMeta::new(depositor.0, false),
+        ].to_vec())
+}
 
-    let withdraw_inst = Instruction::new_with_bytes(
+pub fn set_deposit_policy(svm: &mut LiteSVM, inst_data: &SetDepositPolicyInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    let inst = Instruction::new_with_bytes(
         PIME_ID, 
-        inst_bytes, 
+        inst_data.to_bytes(), 
         [
             AccountMeta::new(authority.pubkey(), true),
             AccountMeta::new(vault_data.0, false),
-            AccountMeta::new(vault.0, false),
-            AccountMeta::new(*to, false),
             AccountMeta::new_readonly(*mint, false),
             AccountMeta::new_readonly(*token_program, false),
         ].to_vec());
 
     let tx = Transaction::new(
         &[authority], 
-        Message::new(
-            &[withdraw_inst], 
-            Some(&authority.pubkey())
-        ), 
-        svm.latest_blockhash());
-
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
     if let Err(e) = svm.send_transaction(tx) {
-        panic!("Failed to withdraw: {:#?}", e);
+        panic!("Failed to set deposit policy: {:#?}", e);
     }
 
-    let to_ata_account = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap();
-    let vault_account = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.deposit_policy().unwrap() as u8, inst_data.deposit_policy());
+    assert_eq!(vault_data_account.min_deposit(), inst_data.min_deposit());
+}
 
-    assert_eq!(to_pre_amount + inst_data.amount(), to_ata_account.amount);
-    assert_eq!(vault_account.amount, vault_pre_amount - inst_data.amount());
+pub fn set_max_transactions_instruction(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &SetMaxTransactionsInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
 }
 
-pub fn close_vault(svm: &mut LiteSVM, inst_data: &CloseVaultInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
-    let inst_bytes = as_bytes(inst_data);
+pub fn set_max_transactions(svm: &mut LiteSVM, inst_data: &SetMaxTransactionsInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    let pre_vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    let pre_vault_data_account = from_bytes::<VaultData>(&pre_vault_data_acc.data[..VaultData::LEN]).unwrap();
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    // A pending raise which has passed its delay is applied first.
+    let pre_max_transactions = if pre_vault_data_account.pending_max_transactions() != 0 
+        && now >= pre_vault_data_account.pending_max_transactions_at() {
+        pre_vault_data_account.pending_max_transactions()
+    } else {
+        pre_vault_data_account.max_transactions()
+    };
+
+    let inst = set_max_transactions_instruction(&authority.pubkey(), mint, token_program, inst_data);
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to set max transactions: {:#?}", e);
+    }
+
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+    if inst_data.max_transactions() <= pre_max_transactions {
+        // Lowering applies right away.
+        assert_eq!(vault_data_account.max_transactions(), inst_data.max_transactions());
+        assert_eq!(vault_data_account.pending_max_transactions(), 0);
+    }
+    else {
+        // Raising waits for a timeframe.
+        assert_eq!(vault_data_account.max_transactions(), pre_max_transactions);
+        assert_eq!(vault_data_account.pending_max_transactions(), inst_data.max_transactions());
+        assert_eq!(vault_data_account.pending_max_transactions_at(), now + vault_data_account.timeframe());
+    }
+}
 
+pub fn set_depositor(svm: &mut LiteSVM, inst_data: &SetDepositorInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
     let vault_data = find_vault_data_pda(
         inst_data.vault_index(), 
         authority.pubkey().as_array(), 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
-    let vault = find_vault_pda(
+    let depositor = find_depositor_pda(
         inst_data.vault_index(), 
         authority.pubkey().as_array(), 
+        &inst_data.depositor, 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
-
     let inst = Instruction::new_with_bytes(
         PIME_ID, 
-        inst_bytes, 
+        inst_data.to_bytes(), 
         [
             AccountMeta::new(authority.pubkey(), true),
-            AccountMeta::new(vault.0, false),
-            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new_readonly(vault_data.0, false),
+            AccountMeta::new(depositor.0, false),
             AccountMeta::new_readonly(*mint, false),
-            AccountMeta::new_readonly(*token_program, false)
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
         ].to_vec());
 
     let tx = Transaction::new(
         &[authority], 
-        Message::new(
-            &[inst], 
-            Some(&authority.pubkey())
-        ), 
+        Message::new(&[inst], Some(&authority.pubkey())), 
         svm.latest_blockhash()
     );
-
     if let Err(e) = svm.send_transaction(tx) {
-        panic!("Failed to close vault: {:#?}", e);
+        panic!("Failed to set depositor: {:#?}", e);
     }
 
-    assert_eq!(svm.get_account(&vault.0), None);
-    assert_eq!(svm.get_account(&vault_data.0), None);
+    let depositor_acc = svm.get_account(&depositor.0).unwrap();
+    let depositor_data = from_bytes::<DepositorData>(&depositor_acc.data).unwrap();
+    assert_eq!(depositor_data.allowed(), inst_data.allowed());
 }
 
-pub fn book_transfer(svm: &mut LiteSVM, inst_data: &BookTransferInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey ) {
-    let inst_bytes = as_bytes(inst_data);
-
-    let vault_data = find_vault_data_pda(
+pub fn return_deposit(svm: &mut LiteSVM, inst_data: &ReturnDepositInstructionData, authority: &Keypair, to: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let inst = return_deposit_instruction(&authority.pubkey(), to, mint, token_program, inst_data);
+    let depositor = find_depositor_pda(
         inst_data.vault_index(), 
         authority.pubkey().as_array(), 
+        &inst_data.depositor, 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
-    let vault = find_vault_pda(
+    let returned_pre = from_bytes::<DepositorData>(&svm.get_account(&depositor.0).unwrap().data).unwrap().returned();
+    let to_pre_amount = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap().amount;
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to return deposit: {:#?}", e);
+    }
+
+    let returned = from_bytes::<DepositorData>(&svm.get_account(&depositor.0).unwrap().data).unwrap().returned();
+    assert_eq!(returned, returned_pre + inst_data.amount());
+    let to_amount = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap().amount;
+    assert_eq!(to_amount, to_pre_amount + inst_data.amount());
+}
+
+pub fn return_deposit_instruction(authority: &Pubkey, to: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &ReturnDepositInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(
         inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
+        authority.as_array(), 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
-    let transfer = find_transfer_pda(
+    let vault = find_vault_pda(
         inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        &inst_data.destination,
+        authority.as_array(), 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
-    let deposit = find_deposit_pda(
+    let depositor = find_depositor_pda(
         inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        &inst_data.destination,
+        authority.as_array(), 
+        &inst_data.depositor, 
         mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+        token_program.as_array()
     );
 
-    let vault_data_open_transfers_pre = if let Some(a) = &svm.get_account(&vault_data.0) {
-        from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap().open_transfers()
-    }
-    else { 0 };
-
-    let vault_acc_pre_val = if let Some(a) = &svm.get_account(&vault.0) {
-        TokenAccount::unpack(&a.data).unwrap().amount
-    }
-    else {
-        0
-    };
-    let deposit_acc_pre_val = if let Some(a) = &svm.get_account(&deposit.0) {
-        TokenAccount::unpack(&a.data).unwrap().amount
-    }
-    else {
-        0
-    };
-
-    let inst = Instruction::new_with_bytes(
+    Instruction::new_with_bytes(
         PIME_ID, 
-        inst_bytes, 
+        inst_data.to_bytes(), 
         [
-            AccountMeta::new(authority.pubkey(), true),
-            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(vault_data.0, false),
             AccountMeta::new(vault.0, false),
-            AccountMeta::new(transfer.0, false),
-            AccountMeta::new(deposit.0, false),
+            AccountMeta::new(depositor.0, false),
+            AccountMeta::new(*to, false),
             AccountMeta::new_readonly(*mint, false),
             AccountMeta::new_readonly(*token_program, false),
-            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
-        ].to_vec()
+        ].to_vec())
+}
+
+pub fn release_lot(svm: &mut LiteSVM, inst_data: &ReleaseLotInstructionData, authority: &Keypair, payer: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let inst = release_lot_instruction(&authority.pubkey(), payer, mint, token_program, inst_data);
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let lot = find_lot_pda(
+        inst_data.vault_index(), 
+        inst_data.lot_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
     );
+    let lot_lamports = svm.get_account(&lot.0).unwrap().lamports;
+    let payer_pre_lamports = svm.get_account(payer).map(|a| a.lamports).unwrap_or(0);
 
     let tx = Transaction::new(
-        /* from keypairs */ &[authority], 
-        /* message */ Message::new(&[inst], Some(&authority.pubkey())), 
-        /* recent_blockhash */ svm.latest_blockhash());
-
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
     if let Err(e) = svm.send_transaction(tx) {
-        panic!("Failed to book transfer: {:#?}", e);
+        panic!("Failed to release lot: {:#?}", e);
     }
 
-    let vault_acc = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
-    let deposit_acc = TokenAccount::unpack(&svm.get_account(&deposit.0).unwrap().data).unwrap();
-    let t = svm.get_account(&transfer.0).unwrap();
-    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
-
-    assert_eq!(transfer_acc.amount(), inst_data.amount());
-
-    // Check that the tokens are transferred out of the vault.
-    assert_eq!(vault_acc.amount, vault_acc_pre_val - inst_data.amount()); 
-
-    // Check that the tokens has been sent to the deposit account.
-    assert_eq!(deposit_acc.amount, deposit_acc_pre_val + inst_data.amount());
-
-    // Verify that Vault data incremented the newly added transfer
-    if let Some(a) = svm.get_account(&vault_data.0) {
-        assert_eq!(
-            from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap().open_transfers(),
-            vault_data_open_transfers_pre + 1);
+    assert_eq!(svm.get_account(&lot.0), None);
+    assert!(lot_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.lot_index()));
+    // The authority pays the transaction fee, so the refund is only exact for other payers.
+    if *payer != authority.pubkey() {
+        assert_eq!(svm.get_account(payer).unwrap().lamports, payer_pre_lamports + lot_lamports);
     }
 }
 
-pub fn execute_transfer(svm: &mut LiteSVM, inst_data: &ExecuteTransferInstructionData, authority: &Keypair, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
-    let inst_bytes = as_bytes(inst_data);
-
+pub fn release_lot_instruction(authority: &Pubkey, payer: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &ReleaseLotInstructionData) -> Instruction {
     let vault_data = find_vault_data_pda(
         inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
-        mint.as_array(),
-        TOKEN_PROGRAM.as_array()
-    );
-    let transfer = find_transfer_pda(
-        inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        destination.as_array(),
+        authority.as_array(), 
         mint.as_array(),
         TOKEN_PROGRAM.as_array()
     );
-    let deposit = find_deposit_pda(
+    let lot = find_lot_pda(
         inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        destination.as_array(),
+        inst_data.lot_index(), 
+        authority.as_array(), 
         mint.as_array(),
         TOKEN_PROGRAM.as_array()
     );
 
-    let deposit_acc_pre_val = if let Some(a) = &svm.get_account(&deposit.0) {
-        TokenAccount::unpack(&a.data).unwrap().amount
-    }
-    else { 0 };
-    let destination_acc_pre_val = if let Some(a) = &svm.get_account(destination) {
-        TokenAccount::unpack(&a.data).unwrap().amount
-    }
-    else { 0 };
-    let pre_open_transfers = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().open_transfers();
-    let tda = svm.get_account(&transfer.0).unwrap();
-    let transfer_acc = from_bytes::<TransferData>(&tda.data).unwrap();
-    let transfer_amount = transfer_acc.amount();
-
-    let inst = Instruction::new_with_bytes(
+    Instruction::new_with_bytes(
         PIME_ID, 
-        inst_bytes, 
+        inst_data.to_bytes(), 
         [
-            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(lot.0, false),
+            AccountMeta::new(*payer, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
+}
+
+pub fn withdraw_from_vault_instruction(
+    authority: &Pubkey, 
+    to: &Pubkey,
+    mint: &Pubkey, 
+    token_program: &Pubkey, 
+    inst_data: &WithdrawFromVaultInstructionData) -> Instruction {
+
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let vault = find_vault_pda(
+        inst_data.vault_index(), 
+        authority.as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    let mut accounts = [
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(vault_data.0, false),
+        AccountMeta::new(vault.0, false),
+        AccountMeta::new(*to, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ].to_vec();
+    if !inst_data.memo().is_empty() {
+        accounts.push(AccountMeta::new_readonly(Pubkey::new_from_array(MEMO_PROGRAM_ID), false));
+    }
+
+    Instruction::new_with_bytes(PIME_ID, inst_data.to_bytes(), accounts)
+}
+
+pub fn withdraw_from_vault(
+    svm: &mut LiteSVM, 
+    authority: &Keypair, 
+    to: &Pubkey,
+    mint: &Pubkey, 
+    token_program: &Pubkey, 
+    inst_data: &WithdrawFromVaultInstructionData) {
+
+    let vault = find_vault_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    let to_ata_account = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap();
+    let to_pre_amount = to_ata_account.amount;
+    let vault_account = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
+    let vault_pre_amount = vault_account.amount;
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let withdrawn_pre_amount = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().total_withdrawn();
+    let window_pre_count = from_bytes::<WithdrawHistory>(&svm.get_account(&vault_data.0).unwrap().data[VaultData::LEN..VaultData::LEN + WithdrawHistory::LEN]).unwrap().window_count();
+
+    let withdraw_inst = withdraw_from_vault_instruction(&authority.pubkey(), to, mint, token_program, inst_data);
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(
+            &[withdraw_inst], 
+            Some(&authority.pubkey())
+        ), 
+        svm.latest_blockhash());
+
+    match svm.send_transaction(tx) {
+        Ok(meta) => assert_memo_logged(&meta.logs, inst_data.memo()),
+        Err(e) => panic!("Failed to withdraw: {:#?}", e),
+    }
+
+    let to_ata_account = TokenAccount::unpack(&svm.get_account(to).unwrap().data).unwrap();
+    let vault_account = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
+
+    assert_eq!(to_pre_amount + inst_data.amount(), to_ata_account.amount);
+    assert_eq!(vault_account.amount, vault_pre_amount - inst_data.amount());
+    let withdrawn_amount = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().total_withdrawn();
+    assert_eq!(withdrawn_amount, withdrawn_pre_amount + inst_data.amount());
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    let history = from_bytes::<WithdrawHistory>(&vault_data_acc.data[VaultData::LEN..VaultData::LEN + WithdrawHistory::LEN]).unwrap();
+    assert_eq!(history.last_withdraw(), svm.get_sysvar::<Clock>().unix_timestamp);
+    // The window may have rolled past older withdraws, but never past this one.
+    assert!(history.window_count() >= 1 && history.window_count() <= window_pre_count + 1);
+    assert!(history.window_amount() >= inst_data.amount());
+}
+
+pub fn close_vault_instruction(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &CloseVaultInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let metadata = find_metadata_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let registry = find_registry_pda(authority.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new(registry.0, false),
+            AccountMeta::new(metadata.0, false),
+        ].to_vec())
+}
+
+pub fn close_vault(svm: &mut LiteSVM, inst_data: &CloseVaultInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let vault = find_vault_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let metadata = find_metadata_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let registry = find_registry_pda(authority.pubkey().as_array());
+
+    let inst = close_vault_instruction(&authority.pubkey(), mint, token_program, inst_data);
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(
+            &[inst], 
+            Some(&authority.pubkey())
+        ), 
+        svm.latest_blockhash()
+    );
+
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to close vault: {:#?}", e);
+    }
+
+    assert_eq!(svm.get_account(&vault.0), None);
+    assert_eq!(svm.get_account(&vault_data.0), None);
+    assert_eq!(svm.get_account(&metadata.0), None);
+    assert!(!registry_entries(svm, &registry.0).contains(&(inst_data.vault_index(), *mint)));
+}
+
+pub fn set_vault_metadata(svm: &mut LiteSVM, inst_data: &SetVaultMetadataInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    let metadata = find_metadata_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    let inst = Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(metadata.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ].to_vec());
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to set vault metadata: {:#?}", e);
+    }
+
+    let metadata_acc = svm.get_account(&metadata.0).unwrap();
+    assert_eq!(metadata_acc.data.len(), MetadataData::space(inst_data.name().len(), inst_data.description().len()));
+    assert_eq!(metadata_acc.lamports, svm.minimum_balance_for_rent_exemption(metadata_acc.data.len()));
+    let metadata_data = from_bytes::<MetadataData>(&metadata_acc.data[..MetadataData::LEN]).unwrap();
+    assert_eq!(metadata_data.category(), inst_data.category());
+    assert_eq!(metadata_data.name(&metadata_acc.data), inst_data.name());
+    assert_eq!(metadata_data.description(&metadata_acc.data), inst_data.description());
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    assert!(from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap().has_metadata());
+}
+
+/// The transfer and deposit are derived from `transfer_index`, which the vault assigns from its next transfer index.
+pub fn book_transfer_instruction(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey, transfer_index: u64, inst_data: &BookTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), transfer_index, authority.as_array(), &inst_data.destination, mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), transfer_index, authority.as_array(), &inst_data.destination, mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ].to_vec()
+    )
+}
+
+/// Books the transfer at the vault's next transfer index, and returns that index.
+pub fn book_transfer(svm: &mut LiteSVM, inst_data: &BookTransferInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey ) -> u64 {
+
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let transfer_index = next_transfer_index(svm, &vault_data.0);
+    let vault = find_vault_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let transfer = find_transfer_pda(
+        inst_data.vault_index(), 
+        transfer_index,
+        authority.pubkey().as_array(),
+        &inst_data.destination,
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let deposit = find_deposit_pda(
+        inst_data.vault_index(), 
+        transfer_index,
+        authority.pubkey().as_array(),
+        &inst_data.destination,
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    let (vault_data_open_transfers_pre, in_flight_pre_amount) = if let Some(a) = &svm.get_account(&vault_data.0) {
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.open_transfers(), vault_data_account.in_flight_amount())
+    }
+    else { (0, 0) };
+
+    let vault_acc_pre_val = if let Some(a) = &svm.get_account(&vault.0) {
+        TokenAccount::unpack(&a.data).unwrap().amount
+    }
+    else {
+        0
+    };
+    // A prefunded deposit holds lamports, but is not yet a token account.
+    let deposit_acc_pre_val = if let Some(a) = &svm.get_account(&deposit.0).filter(|a| !a.data.is_empty()) {
+        TokenAccount::unpack(&a.data).unwrap().amount
+    }
+    else {
+        0
+    };
+
+    let inst = book_transfer_instruction(&authority.pubkey(), mint, token_program, transfer_index, inst_data);
+
+    let tx = Transaction::new(
+        /* from keypairs */ &[authority], 
+        /* message */ Message::new(&[inst], Some(&authority.pubkey())), 
+        /* recent_blockhash */ svm.latest_blockhash());
+
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to book transfer: {:#?}", e);
+    }
+
+    let vault_acc = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap();
+    let deposit_acc = TokenAccount::unpack(&svm.get_account(&deposit.0).unwrap().data).unwrap();
+    let t = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+
+    assert_eq!(transfer_acc.amount(), inst_data.amount());
+    assert_eq!(transfer_acc.version(), TransferData::VERSION);
+    assert_eq!(transfer_acc.vault_index(), inst_data.vault_index());
+    assert_eq!(transfer_acc.transfer_index(), transfer_index);
+    assert_eq!(transfer_acc.transfer_bump(), transfer.1);
+    assert_eq!(transfer_acc.deposit_bump(), deposit.1);
+    assert_eq!(&t.data[TransferData::MINT_OFFSET..TransferData::MINT_OFFSET + PUBKEY_BYTES], mint.as_array());
+    assert_eq!(&t.data[TransferData::DESTINATION_OFFSET..TransferData::DESTINATION_OFFSET + PUBKEY_BYTES], &inst_data.destination);
+    assert_eq!(transfer_acc.allows_crank(), inst_data.allows_crank());
+    assert_eq!(transfer_acc.crank_tip(), inst_data.crank_tip());
+    assert_eq!(transfer_acc.stream(), inst_data.stream());
+    assert_eq!(transfer_acc.allows_partial(), inst_data.allows_partial() || inst_data.stream() > 0);
+    assert_eq!(transfer_acc.drawn(), 0);
+    assert_eq!(transfer_acc.memo().as_slice(), inst_data.memo().as_slice());
+    assert_eq!(transfer_acc.is_claim_link(), inst_data.is_claim_link());
+    assert_eq!(transfer_acc.hash_lock().unwrap() as u8, inst_data.hash_lock());
+    assert_eq!(transfer_acc.hash(), inst_data.hash());
+    assert_eq!(transfer_acc.swap(), inst_data.swap());
+    assert_eq!(transfer_acc.swap_amount(), inst_data.swap_amount());
+    // The crank tip is held by the transfer account on top of its rent.
+    assert_eq!(t.lamports, svm.minimum_balance_for_rent_exemption(t.data.len()) + inst_data.crank_tip());
+
+    // Check that the tokens are transferred out of the vault.
+    assert_eq!(vault_acc.amount, vault_acc_pre_val - inst_data.amount()); 
+
+    // Check that the tokens has been sent to the deposit account.
+    assert_eq!(deposit_acc.amount, deposit_acc_pre_val + inst_data.amount());
+
+    // Verify that Vault data incremented the newly added transfer
+    if let Some(a) = svm.get_account(&vault_data.0) {
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        assert_eq!(vault_data_account.open_transfers(), vault_data_open_transfers_pre + 1);
+        assert_eq!(vault_data_account.in_flight_amount(), in_flight_pre_amount + inst_data.amount());
+        assert_eq!(vault_data_account.next_transfer_index(), transfer_index + 1);
+    }
+
+    // The transfer is listed in the vault's transfer registry.
+    assert!(transfer_entries(svm, &vault_data.0).contains(&(
+        transfer_index, 
+        Pubkey::new_from_array(inst_data.destination), 
+        Pubkey::new_from_array(inst_data.destination), 
+        inst_data.amount())));
+    transfer_index
+}
+
+/// The transfer index the vault assigns to its next booking.
+pub fn next_transfer_index(svm: &LiteSVM, vault_data: &Pubkey) -> u64 {
+    let a = svm.get_account(vault_data).unwrap();
+    from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap().next_transfer_index()
+}
+
+/// The transfer is found through the destination it was booked with, which differs from its
+/// destination once amended.
+pub fn execute_transfer_instruction(authority: &Pubkey, booked_destination: &Pubkey, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &ExecuteTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+            AccountMeta::new_readonly(*destination_owner, false),
+            AccountMeta::new_readonly(spl_associated_token_account_interface::program::ID, false),
+        ].to_vec()
+    )
+}
+
+pub fn execute_transfer(svm: &mut LiteSVM, inst_data: &ExecuteTransferInstructionData, authority: &Keypair, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let transfer = find_transfer_pda(
+        inst_data.vault_index(), 
+        inst_data.transfer_index(),
+        authority.pubkey().as_array(),
+        destination.as_array(),
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let deposit = find_deposit_pda(
+        inst_data.vault_index(), 
+        inst_data.transfer_index(),
+        authority.pubkey().as_array(),
+        destination.as_array(),
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    let deposit_acc_pre_val = if let Some(a) = &svm.get_account(&deposit.0) {
+        TokenAccount::unpack(&a.data).unwrap().amount
+    }
+    else { 0 };
+    let destination_acc_pre_val = if let Some(a) = &svm.get_account(destination) {
+        TokenAccount::unpack(&a.data).unwrap().amount
+    }
+    else { 0 };
+    let (pre_open_transfers, pre_in_flight, pre_executed) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.open_transfers(), vault_data_account.in_flight_amount(), vault_data_account.total_executed())
+    };
+    let tda = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&tda.data).unwrap();
+    // Partially drawable transfers only execute what has been released so far.
+    let transfer_amount = transfer_acc.drawable(svm.get_sysvar::<Clock>().unix_timestamp);
+    let drained = transfer_acc.drawn() + transfer_amount == transfer_acc.amount();
+
+    let memo = *transfer_acc.memo();
+
+    let mut inst = execute_transfer_instruction(&authority.pubkey(), destination, destination, destination_owner, mint, token_program, inst_data);
+    if !memo.is_empty() {
+        inst.accounts.push(AccountMeta::new_readonly(Pubkey::new_from_array(MEMO_PROGRAM_ID), false));
+    }
+
+    let tx = Transaction::new(
+        /* from keypairs */ &[authority], 
+        /* message */ Message::new(&[inst], Some(&authority.pubkey())), 
+        /* recent_blockhash */ svm.latest_blockhash());
+
+    match svm.send_transaction(tx) {
+        Ok(meta) => assert_memo_logged(&meta.logs, &memo),
+        Err(e) => panic!("Failed to execute transfer: {:#?}", e),
+    }
+
+    // Check that the deposit account has transferred the tokens out.
+    if let Some(a) = svm.get_account(&deposit.0) { // If account is not closed for some reason.
+        assert_eq!(TokenAccount::unpack(&a.data).unwrap().amount, deposit_acc_pre_val - transfer_amount);
+    }
+
+    // Check that the destination has received its tokens
+    if let Some(a) = svm.get_account(destination) {
+        assert_eq!(TokenAccount::unpack(&a.data).unwrap().amount, destination_acc_pre_val + transfer_amount);
+    }
+
+    // Check that the transfer account is closed once fully drawn.
+    if drained {
+        assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
+    }
+    else {
+        let a = svm.get_account(&transfer.0).unwrap();
+        assert_eq!(from_bytes::<TransferData>(&a.data).unwrap().drawn(), transfer_acc.drawn() + transfer_amount);
+        // The registered entry only holds what is yet to be paid out.
+        assert_eq!(transfer_entry_amount(svm, &vault_data.0, inst_data.transfer_index()), transfer_acc.amount() - transfer_acc.drawn() - transfer_amount);
+    }
+
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - drained as u64);
+    assert_eq!(vault_data_account.in_flight_amount(), pre_in_flight - transfer_amount);
+    assert_eq!(vault_data_account.total_executed(), pre_executed + transfer_amount);
+    if drained {
+        assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
+    }
+}
+
+/// Executes the hash-locked transfer with the preimage in the instruction data, signed by the
+/// executor instead of the authority, and asserts that the destination received the whole amount.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_hash_locked_transfer(svm: &mut LiteSVM, inst_data: &ExecuteTransferInstructionData, executor: &Keypair, authority: &Pubkey, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+
+    let t = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+    assert!(transfer_acc.is_hash_locked());
+    let amount = transfer_acc.amount();
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let destination_pre_amount = token_amount(svm, destination);
+    let pre_executed = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().total_executed();
+
+    // The authority does not sign, as the preimage unlocks the transfer.
+    let mut inst = execute_transfer_instruction(authority, destination, destination, destination_owner, mint, token_program, inst_data);
+    inst.accounts[0] = AccountMeta::new(*authority, false);
+    let tx = Transaction::new(
+        &[executor], 
+        Message::new(&[inst], Some(&executor.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to execute hash-locked transfer: {:#?}", e);
+    }
+
+    assert_eq!(token_amount(svm, destination), destination_pre_amount + amount);
+    assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
+    let executed = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().total_executed();
+    assert_eq!(executed, pre_executed + amount);
+    assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
+}
+
+pub fn unbook_transfer_instruction(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &UnbookTransferInstructionData) -> Instruction {
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), &inst_data.destination, mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), &inst_data.destination, mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*token_program, false),
+        ].to_vec()
+    )
+}
+
+pub fn unbook_transfer(svm: &mut LiteSVM, inst_data: &UnbookTransferInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+
+    let vault = find_vault_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let transfer = find_transfer_pda(
+        inst_data.vault_index(), 
+        inst_data.transfer_index(),
+        authority.pubkey().as_array(),
+        &inst_data.destination,
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let deposit = find_deposit_pda(
+        inst_data.vault_index(), 
+        inst_data.transfer_index(),
+        authority.pubkey().as_array(),
+        &inst_data.destination,
+        mint.as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    let vault_pre_amount = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap().amount;
+    let deposit_amount = TokenAccount::unpack(&svm.get_account(&deposit.0).unwrap().data).unwrap().amount;
+    let (pre_in_flight, pre_unbooked) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.in_flight_amount(), vault_data_account.total_unbooked())
+    };
+
+    let inst = unbook_transfer_instruction(&authority.pubkey(), mint, token_program, inst_data);
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
+
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to unbook transfer: {:#?}", e);
+    }
+
+    if svm.get_account(&transfer.0).is_some() {
+        panic!("Failed to close transfer account");
+    }
+
+    if svm.get_account(&deposit.0).is_some() {
+        panic!("Failed to close deposit account");
+    }
+
+    // The deposit's assets are moved back to the vault.
+    let vault_amount = TokenAccount::unpack(&svm.get_account(&vault.0).unwrap().data).unwrap().amount;
+    assert_eq!(vault_amount, vault_pre_amount + deposit_amount);
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.in_flight_amount(), pre_in_flight - deposit_amount);
+    assert_eq!(vault_data_account.total_unbooked(), pre_unbooked + deposit_amount);
+    assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
+}
+
+/// Length of the version 1 vault data, which is directly followed by its history ring.
+const V1_LEN: usize = 98;
+
+/// Rewrites a vault data account into the version 1 layout, as first deployed, followed by
+/// `entries` as its history ring. The bytes are written out field by field, so that they do not
+/// depend on the program's own description of the layout.
+pub fn set_version_1_vault_data(svm: &mut LiteSVM, vault_data: &Pubkey, open_transfers: u64, entries: &[VaultHistory]) {
+    let mut vault_data_acc = svm.get_account(vault_data).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+    let max_transactions = vault_data_account.max_transactions() as usize;
+    assert!(entries.len() <= max_transactions);
+
+    let mut data = Vec::with_capacity(V1_LEN + max_transactions * VaultHistory::LEN);
+    // Discriminator, version and authority.
+    data.push(0u8);
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.extend_from_slice(&vault_data_acc.data[1 + size_of::<u64>()..1 + size_of::<u64>() + PUBKEY_BYTES]);
+    data.extend_from_slice(&vault_data_account.timeframe().to_le_bytes());
+    data.extend_from_slice(&vault_data_account.max_amount().to_le_bytes());
+    data.extend_from_slice(&vault_data_account.max_transactions().to_le_bytes());
+    data.push(vault_data_account.allows_transfers() as u8);
+    data.extend_from_slice(&vault_data_account.transfer_min_warmup().to_le_bytes());
+    data.extend_from_slice(&vault_data_account.transfer_max_window().to_le_bytes());
+    data.extend_from_slice(&open_transfers.to_le_bytes());
+    // Transaction index, pointing at the newest history entry.
+    data.extend_from_slice(&(entries.len().saturating_sub(1) as u64).to_le_bytes());
+    assert_eq!(data.len(), V1_LEN);
+    for i in 0..max_transactions {
+        let (timestamp, amount) = entries.get(i).map(|e| (e.timestamp(), e.amount())).unwrap_or((UnixTimestamp::MIN, u64::MIN));
+        data.extend_from_slice(&timestamp.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    vault_data_acc.data = data;
+    svm.set_account(*vault_data, vault_data_acc).unwrap();
+}
+
+/// Rewrites a transfer account into the version 0 layout, which did not contain the identity fields.
+/// The bytes are written out field by field, so that they do not depend on the program's own
+/// description of the layout.
+pub fn set_version_0_transfer(svm: &mut LiteSVM, transfer: &Pubkey) {
+    let mut transfer_acc = svm.get_account(transfer).unwrap();
+    let transfer_data = from_bytes::<TransferData>(&transfer_acc.data).unwrap();
+    let mut data = vec![TransferData::DISCRIMINATOR];
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&transfer_data.vault_data);
+    data.extend_from_slice(&transfer_data.destination);
+    data.extend_from_slice(&transfer_data.amount().to_le_bytes());
+    data.extend_from_slice(&transfer_data.created().to_le_bytes());
+    data.extend_from_slice(&transfer_data.created_epoch().to_le_bytes());
+    data.extend_from_slice(&transfer_data.warmup().to_le_bytes());
+    data.extend_from_slice(&transfer_data.validity().to_le_bytes());
+    transfer_acc.data = data;
+    svm.set_account(*transfer, transfer_acc).unwrap();
+}
+
+pub fn migrate_account_instruction(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &MigrateAccountInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ].to_vec())
+}
+
+pub fn migrate_account(svm: &mut LiteSVM, inst_data: &MigrateAccountInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(
+        inst_data.vault_index(), 
+        authority.pubkey().as_array(), 
+        mint.as_array(),
+        token_program.as_array()
+    );
+    let pre_vault_data = svm.get_account(&vault_data.0).unwrap();
+    assert_eq!(u64::from_le_bytes(pre_vault_data.data[1..1 + size_of::<u64>()].try_into().unwrap()), 1);
+
+    let inst = migrate_account_instruction(&authority.pubkey(), mint, token_program, inst_data);
+
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to migrate account: {:#?}", e);
+    }
+
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    assert_eq!(vault_data_acc.data.len(), VaultData::SPACE);
+    assert_eq!(vault_data_acc.lamports, svm.minimum_balance_for_rent_exemption(VaultData::SPACE));
+    let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.version(), VaultData::VERSION);
+    // The settings and identity are in place.
+    assert_eq!(&vault_data_acc.data[VaultData::AUTHORITY_OFFSET..VaultData::AUTHORITY_OFFSET + PUBKEY_BYTES], &pre_vault_data.data[1 + size_of::<u64>()..1 + size_of::<u64>() + PUBKEY_BYTES]);
+    assert_eq!(&vault_data_acc.data[VaultData::MINT_OFFSET..VaultData::MINT_OFFSET + PUBKEY_BYTES], mint.as_array());
+    assert_eq!(vault_data_account.vault_index(), inst_data.vault_index());
+
+    // Open transfers are not registered, and transfer indices start over.
+    assert!(transfer_entries(svm, &vault_data.0).is_empty());
+    assert_eq!(vault_data_account.next_transfer_index(), 0);
+}
+
+pub fn crank_transfer_instruction(crank: &Pubkey, authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &CrankTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*crank, true),
+            AccountMeta::new(*authority, false),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
+}
+
+/// Cranks the transfer, and asserts that it was executed or refunded depending on whether it has expired.
+pub fn crank_transfer(svm: &mut LiteSVM, inst_data: &CrankTransferInstructionData, crank: &Keypair, authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+
+    let t = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let expired = now > transfer_acc.created() + transfer_acc.validity();
+    // An expired transfer refunds its undrawn remainder, otherwise what has been released is executed.
+    let amount = if expired { transfer_acc.amount() - transfer_acc.drawn() } else { transfer_acc.drawable(now) };
+    let closes = expired || transfer_acc.drawn() + amount == transfer_acc.amount();
+    let crank_tip = if closes { transfer_acc.crank_tip() } else { 0 };
+    let d = svm.get_account(&deposit.0).unwrap();
+    let rent = t.lamports - crank_tip + d.lamports;
+    // Only the executed payout carries the memo.
+    let memo = if expired { Memo::empty() } else { *transfer_acc.memo() };
+
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let vault_pre_amount = token_amount(svm, &vault.0);
+    let destination_pre_amount = token_amount(svm, destination);
+    let crank_pre_lamports = svm.get_account(&crank.pubkey()).unwrap().lamports;
+    let authority_pre_lamports = svm.get_account(authority).unwrap().lamports;
+    let (pre_open_transfers, pre_executed, pre_unbooked) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.open_transfers(), vault_data_account.total_executed(), vault_data_account.total_unbooked())
+    };
+
+    let mut inst = crank_transfer_instruction(&crank.pubkey(), authority, destination, mint, token_program, inst_data);
+    if !memo.is_empty() {
+        inst.accounts.push(AccountMeta::new_readonly(Pubkey::new_from_array(MEMO_PROGRAM_ID), false));
+    }
+    let tx = Transaction::new(
+        &[crank], 
+        Message::new(&[inst], Some(&crank.pubkey())), 
+        svm.latest_blockhash()
+    );
+    match svm.send_transaction(tx) {
+        Ok(meta) => assert_memo_logged(&meta.logs, &memo),
+        Err(e) => panic!("Failed to crank transfer: {:#?}", e),
+    }
+
+    if closes {
+        assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
+        assert!(svm.get_account(&deposit.0).is_none_or(|a| a.lamports == 0));
+
+        // The rent goes back to the authority.
+        assert_eq!(svm.get_account(authority).unwrap().lamports, authority_pre_lamports + rent);
+    }
+    // The crank is paid the tip, if the transfer closed, minus its fee.
+    assert_eq!(svm.get_account(&crank.pubkey()).unwrap().lamports, crank_pre_lamports + crank_tip - 5_000);
+
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - closes as u64);
+    if closes {
+        assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
+    }
+    else {
+        assert_eq!(transfer_entry_amount(svm, &vault_data.0, inst_data.transfer_index()), transfer_acc.amount() - transfer_acc.drawn() - amount);
+    }
+    if expired {
+        assert_eq!(token_amount(svm, &vault.0), vault_pre_amount + amount);
+        assert_eq!(token_amount(svm, destination), destination_pre_amount);
+        assert_eq!(vault_data_account.total_unbooked(), pre_unbooked + amount);
+    }
+    else {
+        assert_eq!(token_amount(svm, &vault.0), vault_pre_amount);
+        assert_eq!(token_amount(svm, destination), destination_pre_amount + amount);
+        assert_eq!(vault_data_account.total_executed(), pre_executed + amount);
+    }
+}
+
+pub fn draw_transfer_instruction(recipient: &Pubkey, authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &DrawTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new_readonly(*recipient, true),
+            AccountMeta::new(*authority, false),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
+}
+
+/// Draws from the transfer as the owner of its destination, and asserts that it is closed once fully drawn.
+pub fn draw_transfer(svm: &mut LiteSVM, inst_data: &DrawTransferInstructionData, recipient: &Keypair, authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+
+    let t = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+    let pre_drawn = transfer_acc.drawn();
+    let drained = pre_drawn + inst_data.amount() == transfer_acc.amount();
+    let memo = *transfer_acc.memo();
+
+    let destination_pre_amount = TokenAccount::unpack(&svm.get_account(destination).unwrap().data).unwrap().amount;
+    let (pre_open_transfers, pre_in_flight, pre_executed) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.open_transfers(), vault_data_account.in_flight_amount(), vault_data_account.total_executed())
+    };
+
+    let mut inst = draw_transfer_instruction(&recipient.pubkey(), authority, destination, mint, token_program, inst_data);
+    if !memo.is_empty() {
+        inst.accounts.push(AccountMeta::new_readonly(Pubkey::new_from_array(MEMO_PROGRAM_ID), false));
+    }
+    let tx = Transaction::new(
+        &[recipient], 
+        Message::new(&[inst], Some(&recipient.pubkey())), 
+        svm.latest_blockhash()
+    );
+    match svm.send_transaction(tx) {
+        Ok(meta) => assert_memo_logged(&meta.logs, &memo),
+        Err(e) => panic!("Failed to draw transfer: {:#?}", e),
+    }
+
+    let destination_amount = TokenAccount::unpack(&svm.get_account(destination).unwrap().data).unwrap().amount;
+    assert_eq!(destination_amount, destination_pre_amount + inst_data.amount());
+
+    if drained {
+        assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
+    }
+    else {
+        let a = svm.get_account(&transfer.0).unwrap();
+        assert_eq!(from_bytes::<TransferData>(&a.data).unwrap().drawn(), pre_drawn + inst_data.amount());
+        assert_eq!(transfer_entry_amount(svm, &vault_data.0, inst_data.transfer_index()), transfer_acc.amount() - pre_drawn - inst_data.amount());
+    }
+
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - drained as u64);
+    assert_eq!(vault_data_account.in_flight_amount(), pre_in_flight - inst_data.amount());
+    assert_eq!(vault_data_account.total_executed(), pre_executed + inst_data.amount());
+    if drained {
+        assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim_transfer_instruction(claim: &Pubkey, payer: &Pubkey, authority: &Pubkey, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &ClaimTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), claim.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), claim.as_array(), mint.as_array(), token_program.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new_readonly(*claim, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*authority, false),
             AccountMeta::new(vault_data.0, false),
             AccountMeta::new(transfer.0, false),
             AccountMeta::new(deposit.0, false),
@@ -458,100 +1543,498 @@ pub fn execute_transfer(svm: &mut LiteSVM, inst_data: &ExecuteTransferInstructio
             AccountMeta::new_readonly(solana_system_interface::program::ID, false),
             AccountMeta::new_readonly(*destination_owner, false),
             AccountMeta::new_readonly(spl_associated_token_account_interface::program::ID, false),
-        ].to_vec()
-    );
+        ].to_vec())
+}
 
-    let tx = Transaction::new(
-        /* from keypairs */ &[authority], 
-        /* message */ Message::new(&[inst], Some(&authority.pubkey())), 
-        /* recent_blockhash */ svm.latest_blockhash());
+/// Claims the claim-link transfer with its claim key to the destination, paid for by the payer,
+/// and asserts that the released assets arrived and the transfer is closed once fully claimed.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_transfer(svm: &mut LiteSVM, inst_data: &ClaimTransferInstructionData, claim: &Keypair, payer: &Keypair, authority: &Pubkey, destination: &Pubkey, destination_owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), claim.pubkey().as_array(), mint.as_array(), token_program.as_array());
 
-    if let Err(e) = svm.send_transaction(tx) {
-        panic!("Failed to execute transfer: {:#?}", e);
-    }
+    let t = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+    assert!(transfer_acc.is_claim_link());
+    let amount = transfer_acc.drawable(svm.get_sysvar::<Clock>().unix_timestamp);
+    let drained = transfer_acc.drawn() + amount == transfer_acc.amount();
+    let memo = *transfer_acc.memo();
+
+    let destination_pre_amount = svm.get_account(destination)
+        .map_or(0, |a| TokenAccount::unpack(&a.data).unwrap().amount);
+    let (pre_open_transfers, pre_executed) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.open_transfers(), vault_data_account.total_executed())
+    };
 
-    // Check that the deposit account has transferred the tokens out.
-    if let Some(a) = svm.get_account(&deposit.0) { // If account is not closed for some reason.
-        assert_eq!(TokenAccount::unpack(&a.data).unwrap().amount, deposit_acc_pre_val - transfer_amount);
+    let mut inst = claim_transfer_instruction(&claim.pubkey(), &payer.pubkey(), authority, destination, destination_owner, mint, token_program, inst_data);
+    if !memo.is_empty() {
+        inst.accounts.push(AccountMeta::new_readonly(Pubkey::new_from_array(MEMO_PROGRAM_ID), false));
+    }
+    let tx = Transaction::new(
+        &[claim, payer], 
+        Message::new(&[inst], Some(&payer.pubkey())), 
+        svm.latest_blockhash()
+    );
+    match svm.send_transaction(tx) {
+        Ok(meta) => assert_memo_logged(&meta.logs, &memo),
+        Err(e) => panic!("Failed to claim transfer: {:#?}", e),
     }
 
-    // Check that the destination has received its tokens
-    if let Some(a) = svm.get_account(destination) {
-        assert_eq!(TokenAccount::unpack(&a.data).unwrap().amount, destination_acc_pre_val + transfer_amount);
+    let destination_acc = TokenAccount::unpack(&svm.get_account(destination).unwrap().data).unwrap();
+    assert_eq!(destination_acc.amount, destination_pre_amount + amount);
+    assert_eq!(destination_acc.owner, *destination_owner);
+
+    if drained {
+        assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
     }
 
-    // Check that the transfer account is closed.
-    if let Some(a) = svm.get_account(&transfer.0) {
-        assert_eq!(a.lamports, 0);
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - drained as u64);
+    assert_eq!(vault_data_account.total_executed(), pre_executed + amount);
+    if drained {
+        assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != inst_data.transfer_index()));
     }
+}
+
+pub fn amend_transfer_instruction(authority: &Pubkey, booked_destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &AmendTransferInstructionData) -> Instruction {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
 
-    let post_open_transfers = from_bytes::<VaultData>(&svm.get_account(&vault_data.0).unwrap().data[..VaultData::LEN]).unwrap().open_transfers();
-    assert_eq!(post_open_transfers, pre_open_transfers - 1);
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_data.0, false),
+            AccountMeta::new(vault.0, false),
+            AccountMeta::new(transfer.0, false),
+            AccountMeta::new(deposit.0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
 }
 
-pub fn unbook_transfer(svm: &mut LiteSVM, inst_data: &UnbookTransferInstructionData, authority: &Keypair, mint: &Pubkey, token_program: &Pubkey) {
+/// Amends the transfer, and asserts that the amount difference moved between the vault and deposit,
+/// and whether the warm-up restarted.
+pub fn amend_transfer(svm: &mut LiteSVM, inst_data: &AmendTransferInstructionData, authority: &Keypair, booked_destination: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault = find_vault_pda(inst_data.vault_index(), authority.pubkey().as_array(), mint.as_array(), token_program.as_array());
+    let transfer = find_transfer_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.pubkey().as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
+    let deposit = find_deposit_pda(inst_data.vault_index(), inst_data.transfer_index(), authority.pubkey().as_array(), booked_destination.as_array(), mint.as_array(), token_program.as_array());
 
-    let vault = find_vault_pda(
-        inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
-        mint.as_array(),
-        TOKEN_PROGRAM.as_array()
-    );
-    let vault_data = find_vault_data_pda(
-        inst_data.vault_index(), 
-        authority.pubkey().as_array(), 
-        mint.as_array(),
-        TOKEN_PROGRAM.as_array()
-    );
-    let transfer = find_transfer_pda(
-        inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        &inst_data.destination,
-        mint.as_array(),
-        TOKEN_PROGRAM.as_array()
-    );
-    let deposit = find_deposit_pda(
-        inst_data.vault_index(), 
-        inst_data.transfer_index(),
-        authority.pubkey().as_array(),
-        &inst_data.destination,
-        mint.as_array(),
-        TOKEN_PROGRAM.as_array()
+    let t = svm.get_account(&transfer.0).unwrap();
+    let pre = from_bytes::<TransferData>(&t.data).unwrap();
+    let restarts_warmup = inst_data.amount() > pre.amount() 
+        || inst_data.destination != pre.destination 
+        || inst_data.validity() > pre.validity();
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let vault_pre_amount = token_amount(svm, &vault.0);
+    let deposit_pre_amount = token_amount(svm, &deposit.0);
+
+    let inst = amend_transfer_instruction(&authority.pubkey(), booked_destination, mint, token_program, inst_data);
+    let tx = Transaction::new(
+        &[authority], 
+        Message::new(&[inst], Some(&authority.pubkey())), 
+        svm.latest_blockhash()
     );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to amend transfer: {:#?}", e);
+    }
 
-    let inst_bytes = as_bytes(inst_data);
+    assert_eq!(token_amount(svm, &vault.0) + inst_data.amount(), vault_pre_amount + pre.amount());
+    assert_eq!(token_amount(svm, &deposit.0) + pre.amount(), deposit_pre_amount + inst_data.amount());
 
-    let inst = Instruction::new_with_bytes(
-        PIME_ID, 
-        inst_bytes, 
+    let a = svm.get_account(&transfer.0).unwrap();
+    let transfer_acc = from_bytes::<TransferData>(&a.data).unwrap();
+    assert_eq!(transfer_acc.amount(), inst_data.amount());
+    assert_eq!(transfer_acc.destination, inst_data.destination);
+    assert_eq!(transfer_acc.booked_destination, booked_destination.to_bytes());
+    assert_eq!(transfer_acc.validity(), inst_data.validity());
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.pubkey().as_array(), mint.as_array(), token_program.as_array());
+    if restarts_warmup {
+        // Released no earlier than the vault's min warm-up from now, and never earlier than booked.
+        let v = svm.get_account(&vault_data.0).unwrap();
+        let transfer_min_warmup = from_bytes::<VaultData>(&v.data[..VaultData::LEN]).unwrap().transfer_min_warmup();
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        assert!(transfer_acc.created() + transfer_acc.warmup() >= now + transfer_min_warmup);
+        assert!(transfer_acc.warmup() >= pre.warmup());
+    }
+    else {
+        assert_eq!(transfer_acc.warmup(), pre.warmup());
+    }
+    // The registered entry follows the amendment.
+    let entries = transfer_entries(svm, &vault_data.0);
+    if let Some(e) = entries.iter().find(|e| e.0 == inst_data.transfer_index()) {
+        assert_eq!(e.2.to_bytes(), inst_data.destination);
+        assert_eq!(e.3, inst_data.amount() - transfer_acc.drawn());
+    }
+}
+
+/// Both sides of the swap are found through their destination, as swaps can not be amended.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_swap_instruction(authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, counterpart_authority: &Pubkey, counterpart_destination: &Pubkey, counterpart_mint: &Pubkey, token_program: &Pubkey, inst_data: &SettleSwapInstructionData) -> Instruction {
+    let leg = |authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, vault_index: u64, transfer_index: u64| {
+        let vault_data = find_vault_data_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+        let transfer = find_transfer_pda(vault_index, transfer_index, authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+        let deposit = find_deposit_pda(vault_index, transfer_index, authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
         [
-            AccountMeta::new(authority.pubkey(), true),
-            AccountMeta::new(vault.0, false),
+            AccountMeta::new(*authority, false),
             AccountMeta::new(vault_data.0, false),
             AccountMeta::new(transfer.0, false),
             AccountMeta::new(deposit.0, false),
+            AccountMeta::new(*destination, false),
             AccountMeta::new_readonly(*mint, false),
-            AccountMeta::new(*token_program, false),
-        ].to_vec());
+            AccountMeta::new_readonly(*token_program, false),
+        ]
+    };
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            leg(authority, destination, mint, inst_data.vault_index(), inst_data.transfer_index()),
+            leg(counterpart_authority, counterpart_destination, counterpart_mint, inst_data.counterpart_vault_index(), inst_data.counterpart_transfer_index()),
+        ].concat())
+}
+
+/// Settles the swap, and asserts that both destinations were paid in full and both transfers closed.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_swap(svm: &mut LiteSVM, inst_data: &SettleSwapInstructionData, settler: &Keypair, authority: &Pubkey, destination: &Pubkey, mint: &Pubkey, counterpart_authority: &Pubkey, counterpart_destination: &Pubkey, counterpart_mint: &Pubkey, token_program: &Pubkey) {
+    let legs = [
+        (authority, destination, mint, inst_data.vault_index(), inst_data.transfer_index()),
+        (counterpart_authority, counterpart_destination, counterpart_mint, inst_data.counterpart_vault_index(), inst_data.counterpart_transfer_index()),
+    ];
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let pre = legs.map(|(authority, destination, mint, vault_index, transfer_index)| {
+        let vault_data = find_vault_data_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+        let transfer = find_transfer_pda(vault_index, transfer_index, authority.as_array(), destination.as_array(), mint.as_array(), token_program.as_array());
+        let t = svm.get_account(&transfer.0).unwrap();
+        let transfer_acc = from_bytes::<TransferData>(&t.data).unwrap();
+        assert!(transfer_acc.is_swap());
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data, transfer, transfer_acc.amount(), token_amount(svm, destination), vault_data_account.open_transfers(), vault_data_account.total_executed())
+    });
+
+    let inst = settle_swap_instruction(authority, destination, mint, counterpart_authority, counterpart_destination, counterpart_mint, token_program, inst_data);
+    let tx = Transaction::new(
+        &[settler], 
+        Message::new(&[inst], Some(&settler.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to settle swap: {:#?}", e);
+    }
+
+    for ((_, destination, _, _, transfer_index), (vault_data, transfer, amount, destination_pre_amount, pre_open_transfers, pre_executed)) in legs.into_iter().zip(pre) {
+        assert_eq!(token_amount(svm, destination), destination_pre_amount + amount);
+        assert!(svm.get_account(&transfer.0).is_none_or(|a| a.lamports == 0));
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - 1);
+        assert_eq!(vault_data_account.total_executed(), pre_executed + amount);
+        assert!(transfer_entries(svm, &vault_data.0).iter().all(|e| e.0 != transfer_index));
+    }
+}
+
+pub fn create_campaign_instruction(creator: &Pubkey, recipient: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &CreateCampaignInstructionData) -> Instruction {
+    let campaign = find_campaign_pda(inst_data.campaign_index(), creator.as_array(), mint.as_array(), token_program.as_array());
+    let escrow = find_escrow_pda(campaign.0.as_array());
+
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        inst_data.to_bytes(), 
+        [
+            AccountMeta::new(*creator, true),
+            AccountMeta::new(campaign.0, false),
+            AccountMeta::new(escrow.0, false),
+            AccountMeta::new_readonly(*recipient, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ].to_vec())
+}
+
+/// Creates the campaign, and asserts its state and empty escrow. Returns the campaign.
+pub fn create_campaign(svm: &mut LiteSVM, inst_data: &CreateCampaignInstructionData, creator: &Keypair, recipient: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    let campaign = find_campaign_pda(inst_data.campaign_index(), creator.pubkey().as_array(), mint.as_array(), token_program.as_array());
+    let escrow = find_escrow_pda(campaign.0.as_array());
+
+    let inst = create_campaign_instruction(&creator.pubkey(), recipient, mint, token_program, inst_data);
+    let tx = Transaction::new(
+        &[creator], 
+        Message::new(&[inst], Some(&creator.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to create campaign: {:#?}", e);
+    }
+
+    let c = svm.get_account(&campaign.0).unwrap();
+    let campaign_acc = from_bytes::<CampaignData>(&c.data).unwrap();
+    assert_eq!(campaign_acc.version(), 1);
+    assert_eq!(campaign_acc.creator, creator.pubkey().to_bytes());
+    assert_eq!(campaign_acc.recipient, recipient.to_bytes());
+    assert_eq!(campaign_acc.mint, mint.to_bytes());
+    assert_eq!(campaign_acc.campaign_index(), inst_data.campaign_index());
+    assert_eq!(campaign_acc.campaign_bump(), campaign.1);
+    assert_eq!(campaign_acc.escrow_bump(), escrow.1);
+    assert_eq!(campaign_acc.target(), inst_data.target());
+    assert_eq!(campaign_acc.deadline(), inst_data.deadline());
+    assert_eq!(campaign_acc.allows_withdraw(), inst_data.allows_withdraw());
+    assert_eq!(campaign_acc.pledged(), 0);
+    assert!(!campaign_acc.is_finalized());
+    assert_eq!(&c.data[CampaignData::CREATOR_OFFSET..CampaignData::CREATOR_OFFSET + PUBKEY_BYTES], creator.pubkey().as_array());
+
+    let escrow_acc = TokenAccount::unpack(&svm.get_account(&escrow.0).unwrap().data).unwrap();
+    assert_eq!(escrow_acc.amount, 0);
+    assert_eq!(escrow_acc.owner, escrow.0);
+    campaign.0
+}
+
+/// Accounts of the pledge instructions, up to the mint and token program.
+fn pledge_account_metas(authority: &Pubkey, authority_signs: bool, vault_index: u64, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Vec<AccountMeta> {
+    let vault_data = find_vault_data_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+    let escrow = find_escrow_pda(campaign.as_array());
+    let pledge = find_pledge_pda(campaign.as_array(), vault_data.0.as_array());
+    [
+        AccountMeta::new(*authority, authority_signs),
+        AccountMeta::new(vault_data.0, false),
+        AccountMeta::new(vault.0, false),
+        AccountMeta::new(*campaign, false),
+        AccountMeta::new(escrow.0, false),
+        AccountMeta::new(pledge.0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ].to_vec()
+}
+
+pub fn pledge_to_campaign_instruction(authority: &Pubkey, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &PledgeToCampaignInstructionData) -> Instruction {
+    let mut accounts = pledge_account_metas(authority, true, inst_data.vault_index(), campaign, mint, token_program);
+    accounts.push(AccountMeta::new_readonly(solana_system_interface::program::ID, false));
+    Instruction::new_with_bytes(PIME_ID, inst_data.to_bytes(), accounts)
+}
+
+/// Pledges from the authority's vault, and asserts that the amount moved into the campaign's escrow.
+pub fn pledge_to_campaign(svm: &mut LiteSVM, inst_data: &PledgeToCampaignInstructionData, authority: &Keypair, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let vault_data = find_vault_data_pda(inst_data.vault_index(), authority.pubkey().as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(inst_data.vault_index(), authority.pubkey().as_array(), mint.as_array(), token_program.as_array());
+    let escrow = find_escrow_pda(campaign.as_array());
+    let pledge = find_pledge_pda(campaign.as_array(), vault_data.0.as_array());
+
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let vault_pre_amount = token_amount(svm, &vault.0);
+    let escrow_pre_amount = token_amount(svm, &escrow.0);
+    let pre_pledged = from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().pledged();
+    let (pre_in_flight, pre_open_transfers) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.in_flight_amount(), vault_data_account.open_transfers())
+    };
 
+    let inst = pledge_to_campaign_instruction(&authority.pubkey(), campaign, mint, token_program, inst_data);
     let tx = Transaction::new(
         &[authority], 
         Message::new(&[inst], Some(&authority.pubkey())), 
         svm.latest_blockhash()
     );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to pledge to campaign: {:#?}", e);
+    }
+
+    assert_eq!(token_amount(svm, &vault.0), vault_pre_amount - inst_data.amount());
+    assert_eq!(token_amount(svm, &escrow.0), escrow_pre_amount + inst_data.amount());
+    let p = svm.get_account(&pledge.0).unwrap();
+    let pledge_acc = from_bytes::<PledgeData>(&p.data).unwrap();
+    assert_eq!(pledge_acc.amount(), inst_data.amount());
+    assert_eq!(pledge_acc.campaign, campaign.to_bytes());
+    assert_eq!(pledge_acc.vault_data, vault_data.0.to_bytes());
+    assert_eq!(pledge_acc.pledge_bump(), pledge.1);
+    assert_eq!(from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().pledged(), pre_pledged + inst_data.amount());
+    let vault_data_account_data = svm.get_account(&vault_data.0).unwrap().data;
+    let vault_data_account = from_bytes::<VaultData>(&vault_data_account_data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.in_flight_amount(), pre_in_flight + inst_data.amount());
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers + 1);
+}
+
+pub fn withdraw_pledge_instruction(authority: &Pubkey, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &WithdrawPledgeInstructionData) -> Instruction {
+    Instruction::new_with_bytes(PIME_ID, inst_data.to_bytes(), pledge_account_metas(authority, true, inst_data.vault_index(), campaign, mint, token_program))
+}
+
+/// Withdraws the authority's pledge, and asserts that it was refunded into the vault and closed.
+pub fn withdraw_pledge(svm: &mut LiteSVM, inst_data: &WithdrawPledgeInstructionData, authority: &Keypair, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let pre_pledged = from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().pledged();
+    let inst = withdraw_pledge_instruction(&authority.pubkey(), campaign, mint, token_program, inst_data);
+    let amount = assert_pledge_settled(svm, inst, authority, &authority.pubkey(), inst_data.vault_index(), campaign, mint, token_program);
+    assert_eq!(from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().pledged(), pre_pledged - amount);
+}
+
+pub fn finalize_campaign_instruction(campaign: &Pubkey, recipient: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let escrow = find_escrow_pda(campaign.as_array());
+    Instruction::new_with_bytes(
+        PIME_ID, 
+        FinalizeCampaignInstructionData::new().to_bytes(), 
+        [
+            AccountMeta::new(*campaign, false),
+            AccountMeta::new(escrow.0, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ].to_vec())
+}
+
+/// Finalizes the campaign, and asserts that every pledge was released to the recipient.
+pub fn finalize_campaign(svm: &mut LiteSVM, finalizer: &Keypair, campaign: &Pubkey, recipient: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let escrow = find_escrow_pda(campaign.as_array());
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let recipient_pre_amount = token_amount(svm, recipient);
+    let pledged = from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().pledged();
+
+    let inst = finalize_campaign_instruction(campaign, recipient, mint, token_program);
+    let tx = Transaction::new(
+        &[finalizer], 
+        Message::new(&[inst], Some(&finalizer.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to finalize campaign: {:#?}", e);
+    }
+
+    assert_eq!(token_amount(svm, recipient), recipient_pre_amount + pledged);
+    assert_eq!(token_amount(svm, &escrow.0), 0);
+    assert!(from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().is_finalized());
+}
+
+pub fn settle_pledge_instruction(authority: &Pubkey, campaign: &Pubkey, creator: &Pubkey, mint: &Pubkey, token_program: &Pubkey, inst_data: &SettlePledgeInstructionData) -> Instruction {
+    let mut accounts = pledge_account_metas(authority, false, inst_data.vault_index(), campaign, mint, token_program);
+    accounts.push(AccountMeta::new(*creator, false));
+    Instruction::new_with_bytes(PIME_ID, inst_data.to_bytes(), accounts)
+}
+
+/// Settles the authority's pledge, and asserts that it was recorded as executed if the campaign is
+/// finalized, or refunded into the vault otherwise. Once the last pledge is settled, asserts that the
+/// campaign and its escrow were closed, with their rent returned to the creator.
+pub fn settle_pledge(svm: &mut LiteSVM, inst_data: &SettlePledgeInstructionData, settler: &Keypair, authority: &Pubkey, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) {
+    let campaign_acc = svm.get_account(campaign).unwrap();
+    let campaign_data = from_bytes::<CampaignData>(&campaign_acc.data).unwrap();
+    let creator = Pubkey::new_from_array(campaign_data.creator);
+    let last_pledge = campaign_data.pledges() == 1;
+    let escrow = find_escrow_pda(campaign.as_array());
+    let rent = campaign_acc.lamports + svm.get_account(&escrow.0).unwrap().lamports;
+    let creator_pre_lamports = svm.get_account(&creator).unwrap().lamports;
+
+    let inst = settle_pledge_instruction(authority, campaign, &creator, mint, token_program, inst_data);
+    assert_pledge_settled(svm, inst, settler, authority, inst_data.vault_index(), campaign, mint, token_program);
+
+    if last_pledge {
+        assert!(svm.get_account(campaign).is_none_or(|a| a.lamports == 0));
+        assert!(svm.get_account(&escrow.0).is_none_or(|a| a.lamports == 0));
+    }
+    // The creator's balance is only exact when they neither pay the fee nor receive the pledge's rent.
+    if creator != settler.pubkey() && creator != *authority {
+        let returned = if last_pledge { rent } else { 0 };
+        assert_eq!(svm.get_account(&creator).unwrap().lamports, creator_pre_lamports + returned);
+    }
+}
+
+/// Sends the instruction settling the pledge, and asserts that it was refunded into the vault
+/// unless the campaign is finalized, and closed with its rent returned to the authority.
+/// Returns the pledged amount.
+#[allow(clippy::too_many_arguments)]
+fn assert_pledge_settled(svm: &mut LiteSVM, inst: Instruction, signer: &Keypair, authority: &Pubkey, vault_index: u64, campaign: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> u64 {
+    let vault_data = find_vault_data_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+    let vault = find_vault_pda(vault_index, authority.as_array(), mint.as_array(), token_program.as_array());
+    let pledge = find_pledge_pda(campaign.as_array(), vault_data.0.as_array());
+
+    let token_amount = |svm: &LiteSVM, acc: &Pubkey| TokenAccount::unpack(&svm.get_account(acc).unwrap().data).unwrap().amount;
+    let p = svm.get_account(&pledge.0).unwrap();
+    let amount = from_bytes::<PledgeData>(&p.data).unwrap().amount();
+    let refunded = !from_bytes::<CampaignData>(&svm.get_account(campaign).unwrap().data).unwrap().is_finalized();
+    let vault_pre_amount = token_amount(svm, &vault.0);
+    let authority_pre_lamports = svm.get_account(authority).unwrap().lamports;
+    let (pre_in_flight, pre_executed, pre_unbooked, pre_open_transfers) = {
+        let a = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+        (vault_data_account.in_flight_amount(), vault_data_account.total_executed(), vault_data_account.total_unbooked(), vault_data_account.open_transfers())
+    };
+    // The signer pays the fee when it is the authority.
+    let fee = if signer.pubkey() == *authority { 5_000 } else { 0 };
 
+    let tx = Transaction::new(
+        &[signer], 
+        Message::new(&[inst], Some(&signer.pubkey())), 
+        svm.latest_blockhash()
+    );
     if let Err(e) = svm.send_transaction(tx) {
-        panic!("Failed to unbook transfer: {:#?}", e);
+        panic!("Failed to settle pledge: {:#?}", e);
     }
 
-    if svm.get_account(&transfer.0).is_some() {
-        panic!("Failed to close transfer account");
+    assert!(svm.get_account(&pledge.0).is_none_or(|a| a.lamports == 0));
+    assert_eq!(svm.get_account(authority).unwrap().lamports, authority_pre_lamports + p.lamports - fee);
+    let a = svm.get_account(&vault_data.0).unwrap();
+    let vault_data_account = from_bytes::<VaultData>(&a.data[..VaultData::LEN]).unwrap();
+    assert_eq!(vault_data_account.in_flight_amount(), pre_in_flight - amount);
+    assert_eq!(vault_data_account.open_transfers(), pre_open_transfers - 1);
+    if refunded {
+        assert_eq!(token_amount(svm, &vault.0), vault_pre_amount + amount);
+        assert_eq!(vault_data_account.total_unbooked(), pre_unbooked + amount);
+    }
+    else {
+        assert_eq!(token_amount(svm, &vault.0), vault_pre_amount);
+        assert_eq!(vault_data_account.total_executed(), pre_executed + amount);
     }
+    amount
+}
 
-    if svm.get_account(&deposit.0).is_some() {
-        panic!("Failed to close deposit account");
+/// Asserts that a non-empty memo was attached through the memo program.
+pub fn assert_memo_logged(logs: &[String], memo: &Memo) {
+    if memo.is_empty() {
+        return;
+    }
+    let memo = std::str::from_utf8(memo.as_slice()).unwrap();
+    assert!(logs.iter().any(|l| l.contains("Memo") && l.contains(memo)), "Memo was not attached: {:#?}", logs);
+}
+
+/// Sends the instruction, signed by the signer, and asserts that it fails with the Pime error.
+pub fn assert_pime_error(svm: &mut LiteSVM, inst: Instruction, signer: &Keypair, error: PimeError) {
+    let tx = Transaction::new(
+        &[signer], 
+        Message::new(&[inst], Some(&signer.pubkey())), 
+        svm.latest_blockhash()
+    );
+    match svm.send_transaction(tx) {
+        Ok(_) => panic!("Transaction was expected to fail."),
+        Err(e) => assert_eq!(e.err, TransactionError::InstructionError(0, InstructionError::Custom(error as u32))),
+    }
+}
+
+/// Moves the clock sysvar forward in time.
+/// Sends lamports from `from` to a, possibly not yet created, account.
+pub fn prefund(svm: &mut LiteSVM, from: &Keypair, to: &Pubkey, lamports: u64) {
+    let inst = solana_system_interface::instruction::transfer(&from.pubkey(), to, lamports);
+    let tx = Transaction::new(
+        &[from], 
+        Message::new(&[inst], Some(&from.pubkey())), 
+        svm.latest_blockhash()
+    );
+    if let Err(e) = svm.send_transaction(tx) {
+        panic!("Failed to prefund account: {:#?}", e);
     }
+    let to_acc = svm.get_account(to).unwrap();
+    assert_eq!(to_acc.owner, solana_system_interface::program::ID);
+    assert!(to_acc.data.is_empty());
+}
+
+pub fn warp_clock(svm: &mut LiteSVM, seconds: i64) {
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += seconds;
+    svm.set_sysvar::<Clock>(&clock);
+    svm.expire_blockhash();
 }
 
 pub fn find_vault_data_pda(vault_index: u64, authority: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
@@ -576,6 +2059,49 @@ pub fn find_vault_pda(vault_index: u64, authority: &[u8; PUBKEY_BYTES], mint: &[
         &Pubkey::new_from_array(pime::ID))
 }
 
+pub fn find_lot_pda(vault_index: u64, lot_index: u64, authority: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        LotData::LOT_SEED,
+        &vault_index.to_le_bytes(),
+        &lot_index.to_le_bytes(),
+        authority,
+        mint,
+        token_program,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
+pub fn find_depositor_pda(vault_index: u64, authority: &[u8; PUBKEY_BYTES], depositor: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        DepositorData::DEPOSITOR_SEED,
+        &vault_index.to_le_bytes(),
+        authority,
+        depositor,
+        mint,
+        token_program,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
+pub fn find_metadata_pda(vault_index: u64, authority: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        MetadataData::METADATA_SEED,
+        &vault_index.to_le_bytes(),
+        authority,
+        mint,
+        token_program,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
+pub fn find_registry_pda(authority: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        RegistryData::REGISTRY_SEED,
+        authority,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
 pub fn find_transfer_pda(vault_index: u64, transfer_index: u64, authority: &[u8; PUBKEY_BYTES], destination: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES] ) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[
         TransferData::TRANSFER_SEED,
@@ -601,3 +2127,31 @@ pub fn find_deposit_pda(vault_index: u64, transfer_index: u64, authority: &[u8;
     ],
         &Pubkey::new_from_array(pime::ID))
 }
+
+pub fn find_campaign_pda(campaign_index: u64, creator: &[u8; PUBKEY_BYTES], mint: &[u8; PUBKEY_BYTES], token_program: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        CampaignData::CAMPAIGN_SEED,
+        &campaign_index.to_le_bytes(),
+        creator,
+        mint,
+        token_program,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
+pub fn find_escrow_pda(campaign: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        CampaignData::ESCROW_SEED,
+        campaign,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
+
+pub fn find_pledge_pda(campaign: &[u8; PUBKEY_BYTES], vault_data: &[u8; PUBKEY_BYTES]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[
+        PledgeData::PLEDGE_SEED,
+        campaign,
+        vault_data,
+    ],
+        &Pubkey::new_from_array(pime::ID))
+}
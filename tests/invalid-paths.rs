@@ -1,5 +1,113 @@
 #[cfg(test)]
 mod invalid_paths_tests {
+    use pime::errors::PimeError;
+    use pime::interface::instructions::close_vault_instruction::CloseVaultInstructionData;
+    use pime::interface::instructions::withdraw_from_vault::WithdrawFromVaultInstructionData;
+    use pime::interface::parser::{InstructionData, parse};
+    use pime::interface::pime_instruction::PimeInstruction;
+    use pime::states::hash_lock::{MAX_PREIMAGE_LEN, Preimage};
+    use pinocchio::program_error::ProgramError;
+
     #[test]
     fn test(){}
+
+    #[test]
+    fn every_discriminator_maps_to_its_instruction() {
+        let instructions = [
+            PimeInstruction::CreateVault,
+            PimeInstruction::DepositToVault,
+            PimeInstruction::WithdrawFromVault,
+            PimeInstruction::CloseVault,
+            PimeInstruction::ReleaseLot,
+            PimeInstruction::SetDepositPolicy,
+            PimeInstruction::SetDepositor,
+            PimeInstruction::ReturnDeposit,
+            PimeInstruction::SetVaultMetadata,
+            PimeInstruction::MigrateAccount,
+            PimeInstruction::BookTransfer,
+            PimeInstruction::ExecuteTransfer,
+            PimeInstruction::UnbookTransfer,
+            PimeInstruction::SetMaxTransactions,
+            PimeInstruction::CrankTransfer,
+            PimeInstruction::DrawTransfer,
+            PimeInstruction::AmendTransfer,
+            PimeInstruction::ClaimTransfer,
+            PimeInstruction::SettleSwap,
+            PimeInstruction::CreateCampaign,
+            PimeInstruction::PledgeToCampaign,
+            PimeInstruction::WithdrawPledge,
+            PimeInstruction::FinalizeCampaign,
+            PimeInstruction::SettlePledge,
+        ];
+        for instruction in instructions {
+            assert_eq!(PimeInstruction::try_from(instruction as u8), Ok(instruction));
+        }
+        for discriminator in 0..=u8::MAX {
+            if !instructions.iter().any(|i| *i as u8 == discriminator) {
+                assert_eq!(PimeInstruction::try_from(discriminator), Err(PimeError::UnknownInstruction.into()));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_rejects_short_and_trailing_data() {
+        let inst_data = WithdrawFromVaultInstructionData::new(10, 3);
+        let bytes = inst_data.to_bytes();
+
+        let parsed = parse::<WithdrawFromVaultInstructionData>(bytes).unwrap();
+        assert_eq!(parsed.amount(), 10);
+        assert_eq!(parsed.vault_index(), 3);
+
+        assert_eq!(parse::<WithdrawFromVaultInstructionData>(&bytes[..bytes.len() - 1]).err(), Some(PimeError::InvalidInstructionDataLength.into()));
+        let mut trailing = bytes.to_vec();
+        trailing.push(0);
+        assert_eq!(parse::<WithdrawFromVaultInstructionData>(&trailing).err(), Some(PimeError::InvalidInstructionDataLength.into()));
+    }
+
+    #[test]
+    fn parse_rejects_other_instructions_data() {
+        // Close vault data is as long as withdraw data without its amount, pad it to the same length.
+        let mut bytes = CloseVaultInstructionData::new(3).to_bytes().to_vec();
+        bytes.resize(size_of::<WithdrawFromVaultInstructionData>(), 0);
+        assert_eq!(parse::<WithdrawFromVaultInstructionData>(&bytes).err(), Some(PimeError::InstructionMismatch.into()));
+    }
+
+    #[test]
+    #[cfg(feature = "host-hash")]
+    fn hash_locks_hash_like_the_syscalls_off_chain() {
+        use pime::states::hash_lock::HashLock;
+        let preimage = [7u8; 45];
+        assert_eq!(HashLock::None.hash(&preimage), Ok(None));
+        assert_eq!(HashLock::Sha256.hash(&preimage), Ok(Some(solana_sdk::hash::hash(&preimage).to_bytes())));
+        assert_eq!(HashLock::Keccak256.hash(&preimage), Ok(Some(solana_sdk::keccak::hash(&preimage).to_bytes())));
+    }
+
+    #[test]
+    fn preimages_are_limited_in_length() {
+        assert_eq!(Preimage::new(&[7u8; MAX_PREIMAGE_LEN]).unwrap().as_slice(), Ok(&[7u8; MAX_PREIMAGE_LEN][..]));
+        assert_eq!(Preimage::new(&[7u8; MAX_PREIMAGE_LEN + 1]).err(), Some(PimeError::InvalidPreimage.into()));
+        assert_eq!(Preimage::empty().as_slice(), Ok(&[][..]));
+    }
+
+    #[test]
+    fn every_error_code_round_trips() {
+        let mut codes = 0;
+        for code in 0..u8::MAX {
+            let error = PimeError::from(code);
+            if error == PimeError::Unknown {
+                continue;
+            }
+            codes += 1;
+            assert_eq!(error as u8, code);
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(code as u32));
+            assert_eq!(PimeError::from(code as u32), error);
+            assert!(!error.to_string().is_empty());
+        }
+        // Every code up to the newest error is used, except the removed 8, 50 and 67.
+        assert_eq!(codes, PimeError::TransfersNotAllowed as u8 - 2);
+        for removed in [8u8, 50, 67] {
+            assert_eq!(PimeError::from(removed), PimeError::Unknown);
+        }
+        assert_eq!(PimeError::from(u32::MAX), PimeError::Unknown);
+    }
 }
@@ -1,3 +1,28 @@
+mod common;
+use common::*;
+
+use pime::errors::PimeError;
+use pime::interface::instructions::book_transfer::BookTransferInstructionData;
+use pime::interface::instructions::claim_transfer_instruction::ClaimTransferInstructionData;
+use pime::interface::instructions::crank_transfer_instruction::CrankTransferInstructionData;
+use pime::interface::instructions::create_campaign_instruction::CreateCampaignInstructionData;
+use pime::interface::instructions::create_vault_instruction::CreateVaultInstructionData;
+use pime::interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData;
+use pime::interface::instructions::draw_transfer_instruction::DrawTransferInstructionData;
+use pime::interface::instructions::pledge_to_campaign_instruction::PledgeToCampaignInstructionData;
+use pime::interface::instructions::settle_pledge_instruction::SettlePledgeInstructionData;
+use pime::interface::instructions::settle_swap_instruction::SettleSwapInstructionData;
+use pime::interface::instructions::withdraw_pledge_instruction::WithdrawPledgeInstructionData;
+use pime::states::hash_lock::{HASH_LEN, HashLock};
+use pime::states::transfer_data::TransferData;
+use pime::states::{Transmutable, VaultData};
+use solana_sdk::{clock::Clock, message::AccountMeta, native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_associated_token_account_interface::address::get_associated_token_address;
+use spl_token_interface::state::Account as TokenAccount;
+
+const PIME_ID: Pubkey = Pubkey::new_from_array(pime::ID);
+const TOKEN_PROGRAM: Pubkey = spl_token_interface::ID;
+
 //      AUTHORITY TESTS
 #[test]
 fn bob_cant_create_alice_vault() {
@@ -34,8 +59,416 @@ fn bob_cant_unbook_alice_transfer_to_own_account() {
 //      PRE-INIT ATTACKS
 #[test]
 fn bob_cant_pre_init_alice_vault() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let mint = Keypair::new();
+    initialize_mint(&mut svm, &alice.pubkey(), &alice, &mint, &TOKEN_PROGRAM);
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let vault_data = find_vault_data_pda(
+        create_vault_inst_data.vault_index(), 
+        alice.pubkey().as_array(), 
+        mint.pubkey().as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let vault = find_vault_pda(
+        create_vault_inst_data.vault_index(), 
+        alice.pubkey().as_array(), 
+        mint.pubkey().as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let registry = find_registry_pda(alice.pubkey().as_array());
+
+    // Bob sends lamports to every account of Alice's future vault, one below and one above rent.
+    prefund(&mut svm, &bob, &vault_data.0, 1_000);
+    prefund(&mut svm, &bob, &vault.0, LAMPORTS_PER_SOL / 10);
+    prefund(&mut svm, &bob, &registry.0, 1_000);
+
+    create_new_vault(&mut svm, &alice, &create_vault_inst_data, &mint.pubkey());
+
+    let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+    assert_eq!(vault_data_acc.owner, PIME_ID);
+    assert_eq!(vault_data_acc.lamports, svm.minimum_balance_for_rent_exemption(VaultData::SPACE));
+    let vault_acc = svm.get_account(&vault.0).unwrap();
+    assert_eq!(vault_acc.owner, TOKEN_PROGRAM);
+    assert_eq!(vault_acc.lamports, LAMPORTS_PER_SOL / 10);
+
+    // The vault is usable as any other.
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 100, &alice, &alice.pubkey(), &alice_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), 
+        &DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 100));
 }
 
 #[test]
 fn bob_cant_pre_init_alice_transfer() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 5, 
+        /* validity*/ 6);
+    // The vault assigns the booking its next transfer index, which Bob can read as well.
+    let vault_data = find_vault_data_pda(create_vault_inst_data.vault_index(), alice.pubkey().as_array(), mint.pubkey().as_array(), TOKEN_PROGRAM.as_array());
+    let transfer_index = next_transfer_index(&svm, &vault_data.0);
+    let transfer = find_transfer_pda(
+        book_transfer_inst_data.vault_index(), 
+        transfer_index,
+        alice.pubkey().as_array(),
+        &book_transfer_inst_data.destination,
+        mint.pubkey().as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    let deposit = find_deposit_pda(
+        book_transfer_inst_data.vault_index(), 
+        transfer_index,
+        alice.pubkey().as_array(),
+        &book_transfer_inst_data.destination,
+        mint.pubkey().as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+
+    // Bob sends lamports to the transfer and deposit of Alice's next transfer.
+    prefund(&mut svm, &bob, &transfer.0, 1_000);
+    prefund(&mut svm, &bob, &deposit.0, 1_000);
+
+    book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let transfer_acc = svm.get_account(&transfer.0).unwrap();
+    assert_eq!(transfer_acc.owner, PIME_ID);
+    assert_eq!(transfer_acc.lamports, svm.minimum_balance_for_rent_exemption(TransferData::LEN));
+    let deposit_acc = svm.get_account(&deposit.0).unwrap();
+    assert_eq!(deposit_acc.owner, TOKEN_PROGRAM);
+    assert_eq!(deposit_acc.lamports, svm.minimum_balance_for_rent_exemption(TokenAccount::LEN));
+}
+
+#[test]
+fn bob_cant_crank_alice_transfer_without_her_opt_in() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100);
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Even though Bob is the destination, only Alice may execute her transfer.
+    let crank_inst_data = CrankTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index);
+    assert_pime_error(&mut svm, 
+        crank_transfer_instruction(&bob.pubkey(), &alice.pubkey(), &bob_ata, &mint.pubkey(), &TOKEN_PROGRAM, &crank_inst_data), 
+        &bob, 
+        PimeError::CrankNotAllowed);
+}
+
+#[test]
+fn bob_cant_draw_alice_transfer_to_carol() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_partial_draws();
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Only Carol, the owner of the destination, may draw.
+    let draw_inst_data = DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 100);
+    assert_pime_error(&mut svm, 
+        draw_transfer_instruction(&bob.pubkey(), &alice.pubkey(), &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM, &draw_inst_data), 
+        &bob, 
+        PimeError::AuthorityError);
+}
+
+#[test]
+fn bob_cant_claim_alice_link_without_its_claim_key() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    // The destination is an ephemeral claim key, shared with the recipient as a link.
+    let claim_key = Keypair::new();
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ claim_key.pubkey().to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .as_claim_link();
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Bob signs in place of the claim key, to claim into his own token account.
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+    let claim_inst_data = ClaimTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index);
+    let mut inst = claim_transfer_instruction(&claim_key.pubkey(), &bob.pubkey(), &alice.pubkey(), &bob_ata, &bob.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &claim_inst_data);
+    inst.accounts[0] = AccountMeta::new_readonly(bob.pubkey(), true);
+    assert_pime_error(&mut svm, inst, &bob, PimeError::AuthorityError);
+}
+
+#[test]
+fn bob_cant_crank_alices_hash_lock_without_its_preimage() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let preimage = [7u8; HASH_LEN];
+    let book_transfer_inst_data = BookTransferInstructionData::new_crankable(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100,
+        /* allows_crank */ true,
+        /* crank_tip */ 0)
+        .with_hash_lock(HashLock::Sha256, solana_sdk::hash::hash(&preimage).to_bytes());
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Allowing cranks does not lift the hash lock.
+    let crank_inst_data = CrankTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index);
+    assert_pime_error(&mut svm, 
+        crank_transfer_instruction(&bob.pubkey(), &alice.pubkey(), &bob_ata, &mint.pubkey(), &TOKEN_PROGRAM, &crank_inst_data), 
+        &bob, 
+        PimeError::HashLocked);
+}
+
+#[test]
+fn bob_cant_settle_alices_swap_with_carol_against_his_booking() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (alice_mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let (bob_mint, _bob_ata) = create_funded_vault(&mut svm, &bob, &create_vault_inst_data, 1_000, 500);
+
+    // Each side is paid into a token account of the other's mint.
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &alice_mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &bob_mint.pubkey());
+    mint_to(&mut svm, 0, &bob, &alice.pubkey(), &alice_ata, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    let alice_transfer = find_transfer_pda(0, 0, alice.pubkey().as_array(), bob_ata.as_array(), alice_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    // Alice agreed to swap with Carol, and Bob books the same terms towards Alice.
+    let carol_transfer = find_transfer_pda(0, 0, carol.pubkey().as_array(), alice_ata.as_array(), bob_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    let alice_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(carol_transfer.to_bytes(), 100);
+    book_transfer(&mut svm, &alice_book_inst_data, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let bob_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 100, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(alice_transfer.to_bytes(), 250);
+    book_transfer(&mut svm, &bob_book_inst_data, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    let settle_inst = settle_swap_instruction(
+        &alice.pubkey(), &bob_ata, &alice_mint.pubkey(), 
+        &bob.pubkey(), &alice_ata, &bob_mint.pubkey(), 
+        &TOKEN_PROGRAM, 
+        &SettleSwapInstructionData::new(0, 0, 0, 0));
+    assert_pime_error(&mut svm, settle_inst, &bob, PimeError::SwapMismatch);
+}
+
+#[test]
+fn bob_cant_refund_a_successful_campaign_before_it_is_finalized() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 450), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Settling Alice's pledge first would pull it back out of Carol's successful campaign.
+    warp_clock(&mut svm, 101);
+    assert_pime_error(&mut svm, 
+        settle_pledge_instruction(&alice.pubkey(), &campaign, &carol.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &SettlePledgeInstructionData::new(0)), 
+        &bob, 
+        PimeError::CampaignNotFinalized);
+}
+
+#[test]
+fn bob_cant_withdraw_alices_pledge() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Bob signs in place of Alice, which derives a different vault.
+    let mut withdraw_inst = withdraw_pledge_instruction(&alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM, &WithdrawPledgeInstructionData::new(0));
+    withdraw_inst.accounts[0] = AccountMeta::new(bob.pubkey(), true);
+    assert_pime_error(&mut svm, withdraw_inst, &bob, PimeError::IncorrectPDA);
 }
@@ -3,19 +3,41 @@ mod common;
 mod happy_paths_tests {
 
     use super::common::*;
+    use pime::errors::PimeError;
 
+    use pime::interface::instructions::amend_transfer_instruction::AmendTransferInstructionData;
     use pime::interface::instructions::book_transfer::BookTransferInstructionData;
+    use pime::interface::instructions::claim_transfer_instruction::ClaimTransferInstructionData;
     use pime::interface::instructions::close_vault_instruction::CloseVaultInstructionData;
+    use pime::interface::instructions::crank_transfer_instruction::CrankTransferInstructionData;
+    use pime::interface::instructions::create_campaign_instruction::CreateCampaignInstructionData;
     use pime::interface::instructions::create_vault_instruction::CreateVaultInstructionData;
     use pime::interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData;
+    use pime::interface::instructions::draw_transfer_instruction::DrawTransferInstructionData;
     use pime::interface::instructions::execute_transfer::ExecuteTransferInstructionData;
+    use pime::interface::instructions::migrate_account_instruction::MigrateAccountInstructionData;
+    use pime::interface::instructions::pledge_to_campaign_instruction::PledgeToCampaignInstructionData;
+    use pime::interface::instructions::release_lot_instruction::ReleaseLotInstructionData;
+    use pime::interface::instructions::return_deposit_instruction::ReturnDepositInstructionData;
+    use pime::interface::instructions::set_deposit_policy_instruction::SetDepositPolicyInstructionData;
+    use pime::interface::instructions::set_depositor_instruction::SetDepositorInstructionData;
+    use pime::interface::instructions::set_max_transactions_instruction::SetMaxTransactionsInstructionData;
+    use pime::interface::instructions::set_vault_metadata_instruction::SetVaultMetadataInstructionData;
+    use pime::interface::instructions::settle_pledge_instruction::SettlePledgeInstructionData;
+    use pime::interface::instructions::settle_swap_instruction::SettleSwapInstructionData;
     use pime::interface::instructions::unbook_transfer_instruction::UnbookTransferInstructionData;
     use pime::interface::instructions::withdraw_from_vault::WithdrawFromVaultInstructionData;
-    use solana_sdk::{native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use pime::interface::instructions::withdraw_pledge_instruction::WithdrawPledgeInstructionData;
+    use pime::states::VaultHistory;
+    use pime::states::hash_lock::{HASH_LEN, HashLock, Preimage};
+    use pime::states::memo::Memo;
+    use pime::states::registry_data::RegistryData;
+    use pime::states::withdraw_history::WithdrawHistory;
+    use pime::states::{DepositPolicy, Transmutable, VaultData, from_bytes};
+    use solana_sdk::{clock::Clock, message::{AccountMeta, Message}, native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
     use spl_associated_token_account_interface::address::{get_associated_token_address, get_associated_token_address_with_program_id};
     use spl_token_interface::state::Account as TokenAccount;
 
-    const PIME_ID: Pubkey = Pubkey::new_from_array(pime::ID);
     const TOKEN_PROGRAM: Pubkey = spl_token_interface::ID;
 
     #[test]
@@ -32,6 +54,8 @@ mod happy_paths_tests {
             /* allows transfers */ true,
             /* transfer min window */ 4u64,
             /* transfer max_window */ 5u64,
+            /* withdraw cooldown */ 0i64,
+            /* withdraw warmup */ 0i64,
         );
 
         // Create new mint
@@ -57,6 +81,8 @@ mod happy_paths_tests {
             /* allows transfers */ true,
             /* transfer min window */ 4u64,
             /* transfer max_window */ 5u64,
+            /* withdraw cooldown */ 0i64,
+            /* withdraw warmup */ 0i64,
         );
 
         // Create new mint
@@ -85,6 +111,8 @@ mod happy_paths_tests {
             /* allows transfers */ true,
             /* transfer min window */ 4u64,
             /* transfer max_window */ 5u64,
+            /* withdraw cooldown */ 0i64,
+            /* withdraw warmup */ 0i64,
         );
 
         // Create new mint
@@ -143,6 +171,8 @@ mod happy_paths_tests {
             /* allows transfers */ true,
             /* transfer min window */ 5u64,
             /* transfer max_window */ 6u64,
+            /* withdraw cooldown */ 0i64,
+            /* withdraw warmup */ 0i64,
         );
 
         // Create new mint
@@ -234,7 +264,9 @@ mod happy_paths_tests {
             /* max_amount */ 4, 
             /* allows transfers */ true,
             /* transfer_min_warmup */ 5, 
-            /* transfer_max_window */ 6);
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
         create_new_vault(&mut svm, 
             /* authority */ &alice, 
             /* instuction_data */ &create_vault_inst_data,  
@@ -259,11 +291,10 @@ mod happy_paths_tests {
             /* amount */ transfer_amount, 
             /* destination */ receiver_ata.to_bytes(),
             /* vault_index */ create_vault_inst_data.vault_index(), 
-            /* transfer_index */ 1, 
             /* warmup */ 1, 
             /* validity*/ 1);
 
-        book_transfer(&mut svm, 
+        let transfer_index = book_transfer(&mut svm, 
             /* inst data */ &book_transfer_inst_data, 
             /* authority */ &alice, 
             /* mint */ &mint.pubkey(), 
@@ -278,7 +309,7 @@ mod happy_paths_tests {
         );
         let deposit = find_deposit_pda(
             book_transfer_inst_data.vault_index(), 
-            book_transfer_inst_data.transfer_index(),
+            transfer_index,
             alice.pubkey().as_array(),
             &book_transfer_inst_data.destination,
             mint.pubkey().as_array(),
@@ -326,7 +357,9 @@ mod happy_paths_tests {
             /* max_amount */ 4, 
             /* allows transfers */ true,
             /* transfer_min_warmup */ 5, 
-            /* transfer_max_window */ 6);
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
         create_new_vault(&mut svm, 
             /* authority */ &alice, 
             /* instuction_data */ &create_vault_inst_data,  
@@ -351,11 +384,10 @@ mod happy_paths_tests {
             /* amount */ transfer_amount, 
             /* destination */ destination_ata.to_bytes(),
             /* vault_index */ create_vault_inst_data.vault_index(), 
-            /* transfer_index */ 1, 
             /* warmup */ 0, 
             /* validity*/ 100);
 
-        book_transfer(&mut svm, 
+        let transfer_index = book_transfer(&mut svm, 
             /* inst data */ &book_transfer_inst_data, 
             /* authority */ &alice, 
             /* mint */ &mint.pubkey(), 
@@ -363,7 +395,7 @@ mod happy_paths_tests {
 
         let execute_transfer_inst_data = ExecuteTransferInstructionData::new(
             book_transfer_inst_data.vault_index(), 
-            book_transfer_inst_data.transfer_index());
+            transfer_index);
 
         execute_transfer(&mut svm, 
             /* inst_data */ &execute_transfer_inst_data, 
@@ -408,7 +440,9 @@ mod happy_paths_tests {
             /* max_amount */ 4, 
             /* allows transfers */ true,
             /* transfer_min_warmup */ 5, 
-            /* transfer_max_window */ 6);
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
         create_new_vault(&mut svm, 
             /* authority */ &alice, 
             /* instuction_data */ &create_vault_inst_data,  
@@ -433,11 +467,10 @@ mod happy_paths_tests {
             /* amount */ transfer_amount, 
             /* destination */ destination_ata.to_bytes(),
             /* vault_index */ create_vault_inst_data.vault_index(), 
-            /* transfer_index */ 1, 
             /* warmup */ 0, 
             /* validity*/ 100);
 
-        book_transfer(&mut svm, 
+        let transfer_index = book_transfer(&mut svm, 
             /* inst data */ &book_transfer_inst_data, 
             /* authority */ &alice, 
             /* mint */ &mint.pubkey(), 
@@ -445,7 +478,7 @@ mod happy_paths_tests {
 
         let unbook_transfer_inst_data = UnbookTransferInstructionData::new(
             book_transfer_inst_data.vault_index(), 
-            book_transfer_inst_data.transfer_index(),
+            transfer_index,
             destination_ata.to_bytes(),
         );
         unbook_transfer(&mut svm, 
@@ -454,4 +487,1246 @@ mod happy_paths_tests {
             &mint.pubkey(), 
             &TOKEN_PROGRAM);
     }
+
+    #[test]
+    fn alice_withdraws_matured_lot_and_releases_it() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 10, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 100);
+
+        // Lock 400 for a year.
+        let maturity = svm.get_sysvar::<Clock>().unix_timestamp + 365 * 24 * 60 * 60;
+        let lot_inst_data = DepositToVaultInstructionData::new_lot(
+            /* vault owner */ alice.pubkey().to_bytes(), 
+            /* vault index */ create_vault_inst_data.vault_index(), 
+            /* amount */ 400, 
+            /* lot index */ 0, 
+            /* maturity */ maturity);
+        deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), &lot_inst_data);
+
+        // The whole vault can be withdrawn once the lot has matured, without releasing it.
+        let until_maturity = maturity - svm.get_sysvar::<Clock>().unix_timestamp;
+        warp_clock(&mut svm, until_maturity);
+        withdraw_from_vault(&mut svm, 
+            /* authority */ &alice, 
+            /* to */ &alice_ata, 
+            /* mint */ &mint.pubkey(), 
+            /* token program */ &TOKEN_PROGRAM, 
+            /* inst data */ &WithdrawFromVaultInstructionData::new(500, create_vault_inst_data.vault_index()));
+
+        // Releasing the lot only returns its rent.
+        release_lot(&mut svm, 
+            /* inst data */ &ReleaseLotInstructionData::new(create_vault_inst_data.vault_index(), lot_inst_data.lot_index()), 
+            /* authority */ &alice, 
+            /* payer */ &alice.pubkey(), 
+            /* mint */ &mint.pubkey(), 
+            /* token program */ &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn bob_deposits_to_alices_vault_and_alice_returns_it() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 10, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 100);
+
+        let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 500, &alice, &bob.pubkey(), &bob_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Anyone may deposit by default, which creates Bob's depositor record.
+        deposit_to_vault(&mut svm, &bob_ata, &bob, &mint.pubkey(), 
+            &DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 200));
+
+        return_deposit(&mut svm, 
+            /* inst data */ &ReturnDepositInstructionData::new(create_vault_inst_data.vault_index(), bob.pubkey().to_bytes(), 150), 
+            /* authority */ &alice, 
+            /* to */ &bob_ata, 
+            /* mint */ &mint.pubkey(), 
+            /* token program */ &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn bob_deposits_to_alices_allowlisted_vault() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 10, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 100);
+
+        let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 500, &alice, &bob.pubkey(), &bob_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        set_deposit_policy(&mut svm, 
+            &SetDepositPolicyInstructionData::new(create_vault_inst_data.vault_index(), DepositPolicy::Allowlist, 50), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        set_depositor(&mut svm, 
+            &SetDepositorInstructionData::new(create_vault_inst_data.vault_index(), bob.pubkey().to_bytes(), true), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        deposit_to_vault(&mut svm, &bob_ata, &bob, &mint.pubkey(), 
+            &DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 50));
+    }
+
+    #[test]
+    fn alice_labels_vault_and_closes_it() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_instruction_data = CreateVaultInstructionData::new(
+            /* index */ 0u64, 
+            /* timeframe */ 1i64, 
+            /* max_withdraws */ 2u64, 
+            /* max_lamports */ 3u64,
+            /* allows transfers */ true,
+            /* transfer min window */ 4u64,
+            /* transfer max_window */ 5u64,
+            /* withdraw cooldown */ 0i64,
+            /* withdraw warmup */ 0i64,
+        );
+
+        let mint = Keypair::new();
+        initialize_mint(&mut svm, &alice.pubkey(), &alice, &mint, &TOKEN_PROGRAM);
+        create_new_vault(&mut svm, &alice, &create_vault_instruction_data, &mint.pubkey());
+
+        set_vault_metadata(&mut svm, 
+            &SetVaultMetadataInstructionData::new(create_vault_instruction_data.vault_index(), 1, "Savings", "Rainy day funds."), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        // Growing, and shrinking, the metadata resizes the account.
+        set_vault_metadata(&mut svm, 
+            &SetVaultMetadataInstructionData::new(create_vault_instruction_data.vault_index(), 2, "Holiday savings", "Funds for the summer holiday, not to be touched before June."), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        set_vault_metadata(&mut svm, 
+            &SetVaultMetadataInstructionData::new(create_vault_instruction_data.vault_index(), 2, "Holiday", ""), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        close_vault(&mut svm, 
+            &CloseVaultInstructionData::new(create_vault_instruction_data.vault_index()), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_enumerates_vaults_through_registry() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let mint = Keypair::new();
+        initialize_mint(&mut svm, &alice.pubkey(), &alice, &mint, &TOKEN_PROGRAM);
+
+        // Each vault is created at the registry's next free index.
+        let registry = find_registry_pda(alice.pubkey().as_array());
+        for _ in 0..3 {
+            let next_index = svm.get_account(&registry.0)
+                .map(|a| from_bytes::<RegistryData>(&a.data[..RegistryData::LEN]).unwrap().next_index())
+                .unwrap_or(0);
+            let create_vault_instruction_data = CreateVaultInstructionData::new(
+                /* index */ next_index, 
+                /* timeframe */ 1i64, 
+                /* max_withdraws */ 2u64, 
+                /* max_lamports */ 3u64,
+                /* allows transfers */ true,
+                /* transfer min window */ 4u64,
+                /* transfer max_window */ 5u64,
+                /* withdraw cooldown */ 0i64,
+                /* withdraw warmup */ 0i64,
+            );
+            create_new_vault(&mut svm, &alice, &create_vault_instruction_data, &mint.pubkey());
+        }
+        assert_eq!(registry_entries(&svm, &registry.0), vec![(0, mint.pubkey()), (1, mint.pubkey()), (2, mint.pubkey())]);
+
+        // Closing a vault removes it from the registry, without reusing its index.
+        close_vault(&mut svm, &CloseVaultInstructionData::new(0), &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        assert_eq!(registry_entries(&svm, &registry.0), vec![(2, mint.pubkey()), (1, mint.pubkey())]);
+        let registry_acc = svm.get_account(&registry.0).unwrap();
+        assert_eq!(from_bytes::<RegistryData>(&registry_acc.data[..RegistryData::LEN]).unwrap().next_index(), 3);
+    }
+
+    #[test]
+    fn alice_raises_max_transactions_after_a_timeframe() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 100, 
+            /* max_transactions */ 1, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 5_000, 4_000);
+
+        // The raised limit is pending, so only one withdraw is allowed for now.
+        set_max_transactions(&mut svm, 
+            &SetMaxTransactionsInstructionData::new(create_vault_inst_data.vault_index(), 3), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+            &WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index()));
+
+        // Once the timeframe has passed, the raised limit applies.
+        warp_clock(&mut svm, create_vault_inst_data.timeframe());
+        for _ in 0..2 {
+            withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+                &WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index()));
+            svm.expire_blockhash();
+        }
+
+        let vault_data = find_vault_data_pda(
+            create_vault_inst_data.vault_index(), 
+            alice.pubkey().as_array(), 
+            mint.pubkey().as_array(),
+            TOKEN_PROGRAM.as_array()
+        );
+        let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+        assert_eq!(vault_data_account.max_transactions(), 3);
+        assert_eq!(vault_data_account.pending_max_transactions(), 0);
+
+        // Lowering it again applies right away.
+        set_max_transactions(&mut svm, 
+            &SetMaxTransactionsInstructionData::new(create_vault_inst_data.vault_index(), 2), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_migrates_version_1_vault_data_and_keeps_her_limits() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        warp_clock(&mut svm, 10_000);
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 3, 
+            /* timeframe */ 100, 
+            /* max_transactions */ 2, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 5_000, 4_000);
+
+        // A vault as first deployed, with one withdraw inside its timeframe and one before it.
+        let vault_data = find_vault_data_pda(
+            create_vault_inst_data.vault_index(), 
+            alice.pubkey().as_array(), 
+            mint.pubkey().as_array(),
+            TOKEN_PROGRAM.as_array()
+        );
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        set_version_1_vault_data(&mut svm, &vault_data.0, 0, &[
+            VaultHistory::new(now - 500, 900),
+            VaultHistory::new(now - 10, 700),
+        ]);
+
+        migrate_account(&mut svm, 
+            &MigrateAccountInstructionData::new(create_vault_inst_data.vault_index()), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        // Only the withdraw within the timeframe is folded into the history.
+        let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+        let history = from_bytes::<WithdrawHistory>(&vault_data_acc.data[VaultData::LEN..VaultData::LEN + WithdrawHistory::LEN]).unwrap();
+        assert_eq!(history.window_amount(), 700);
+        assert_eq!(history.window_count(), 1);
+        assert_eq!(history.last_withdraw(), now - 10);
+
+        // The carried over withdraw still counts towards the limit.
+        assert_pime_error(&mut svm, 
+            withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+                &WithdrawFromVaultInstructionData::new(301, create_vault_inst_data.vault_index())), 
+            &alice, 
+            PimeError::WithdrawLimitReachedAmount);
+        withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+            &WithdrawFromVaultInstructionData::new(300, create_vault_inst_data.vault_index()));
+    }
+
+    #[test]
+    fn alice_settles_transfers_booked_with_version_0() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Two transfers to Carol as first deployed, one to execute and one to unbook.
+        let executed_index = book_transfer(&mut svm, 
+            /* inst data */ &BookTransferInstructionData::new(250, carol_ata.to_bytes(), create_vault_inst_data.vault_index(), 0, 100), 
+            /* authority */ &alice, 
+            /* mint */ &mint.pubkey(), 
+            /* token_program */ &TOKEN_PROGRAM);
+        let unbooked_index = book_transfer(&mut svm, 
+            /* inst data */ &BookTransferInstructionData::new(100, carol_ata.to_bytes(), create_vault_inst_data.vault_index(), 0, 100), 
+            /* authority */ &alice, 
+            /* mint */ &mint.pubkey(), 
+            /* token_program */ &TOKEN_PROGRAM);
+        for transfer_index in [executed_index, unbooked_index] {
+            let transfer = find_transfer_pda(
+                create_vault_inst_data.vault_index(), 
+                transfer_index, 
+                alice.pubkey().as_array(), 
+                carol_ata.as_array(), 
+                mint.pubkey().as_array(), 
+                TOKEN_PROGRAM.as_array());
+            set_version_0_transfer(&mut svm, &transfer.0);
+        }
+
+        let execute_inst = execute_transfer_instruction(&alice.pubkey(), &carol_ata, &carol_ata, &carol.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, 
+            &ExecuteTransferInstructionData::new(create_vault_inst_data.vault_index(), executed_index));
+        let tx = Transaction::new(
+            &[&alice], 
+            Message::new(&[execute_inst], Some(&alice.pubkey())), 
+            svm.latest_blockhash()
+        );
+        svm.send_transaction(tx).unwrap();
+        let carol_acc = TokenAccount::unpack(&svm.get_account(&carol_ata).unwrap().data).unwrap();
+        assert_eq!(carol_acc.amount, 250);
+
+        unbook_transfer(&mut svm, 
+            &UnbookTransferInstructionData::new(create_vault_inst_data.vault_index(), unbooked_index, carol_ata.to_bytes()), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        let vault_data = find_vault_data_pda(create_vault_inst_data.vault_index(), alice.pubkey().as_array(), mint.pubkey().as_array(), TOKEN_PROGRAM.as_array());
+        let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+        assert_eq!(from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap().open_transfers(), 0);
+    }
+
+    #[test]
+    fn bob_cranks_alices_matured_transfer_and_earns_the_tip() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        // The crank can not create the destination, so Carol's ATA has to exist.
+        let carol = Keypair::new();
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        let book_transfer_inst_data = BookTransferInstructionData::new_crankable(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100,
+            /* allows_crank */ true,
+            /* crank_tip */ 10_000)
+            .with_memo(Memo::new(b"INV-1043").unwrap());
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // The memo is attached to the crank's payout, which is checked by the helper.
+        crank_transfer(&mut svm, 
+            &CrankTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            /* crank */ &bob, 
+            /* authority */ &alice.pubkey(), 
+            /* destination */ &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn bob_cranks_alices_expired_transfer_back_into_her_vault() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        // Carol's ATA exists, but never receives the expired transfer.
+        let carol = Keypair::new();
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        let book_transfer_inst_data = BookTransferInstructionData::new_crankable(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100,
+            /* allows_crank */ true,
+            /* crank_tip */ 10_000);
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        warp_clock(&mut svm, book_transfer_inst_data.validity() + 1);
+        crank_transfer(&mut svm, 
+            &CrankTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            /* crank */ &bob, 
+            /* authority */ &alice.pubkey(), 
+            /* destination */ &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn carol_draws_alices_streamed_transfer_as_it_releases() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // 300 released linearly over 100 seconds.
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 300, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 200)
+            .with_stream(100)
+            .with_memo(Memo::new(b"INV-1044").unwrap());
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Half way through, Carol draws some of what has been released. Each draw carries the memo.
+        warp_clock(&mut svm, 50);
+        draw_transfer(&mut svm, 
+            &DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 100), 
+            &carol, 
+            &alice.pubkey(), 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        // Alice executes the rest of what has been released so far.
+        let execute_transfer_inst_data = ExecuteTransferInstructionData::new(
+            book_transfer_inst_data.vault_index(), 
+            transfer_index);
+        execute_transfer(&mut svm, &execute_transfer_inst_data, &alice, &carol_ata, &carol.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Once the stream has ended, Carol drains the transfer, closing it.
+        warp_clock(&mut svm, 50);
+        draw_transfer(&mut svm, 
+            &DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 150), 
+            &carol, 
+            &alice.pubkey(), 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_unbooks_the_undrawn_remainder_of_a_partial_transfer() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_partial_draws();
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        draw_transfer(&mut svm, 
+            &DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 100), 
+            &carol, 
+            &alice.pubkey(), 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        // The remaining 150 go back to the vault.
+        let unbook_transfer_inst_data = UnbookTransferInstructionData::new(
+            book_transfer_inst_data.vault_index(), 
+            transfer_index,
+            book_transfer_inst_data.destination);
+        unbook_transfer(&mut svm, &unbook_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_lowers_a_booked_transfer_and_executes_it_right_away() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100);
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Lowering the amount and shortening the validity keeps the warm-up.
+        amend_transfer(&mut svm, 
+            &AmendTransferInstructionData::new(
+                book_transfer_inst_data.vault_index(), 
+                transfer_index, 
+                /* amount */ 100, 
+                /* destination */ carol_ata.to_bytes(), 
+                /* validity */ 50), 
+            &alice, 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        execute_transfer(&mut svm, 
+            &ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            &alice, 
+            &carol_ata, 
+            &carol.pubkey(), 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_amends_a_transfer_to_dave_and_waits_for_the_warmup() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        let dave = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        let dave_ata = get_associated_token_address(&dave.pubkey(), &mint.pubkey());
+
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100);
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Changing the destination and raising the amount restarts the warm-up.
+        amend_transfer(&mut svm, 
+            &AmendTransferInstructionData::new(
+                book_transfer_inst_data.vault_index(), 
+                transfer_index, 
+                /* amount */ 300, 
+                /* destination */ dave_ata.to_bytes(), 
+                /* validity */ 100), 
+            &alice, 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+
+        // The transfer is still found through Carol, but pays Dave.
+        let execute_transfer_inst_data = ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index);
+        let execute_inst = execute_transfer_instruction(&alice.pubkey(), &carol_ata, &dave_ata, &dave.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &execute_transfer_inst_data);
+        assert_pime_error(&mut svm, execute_inst.clone(), &alice, PimeError::TransferWarmingUp);
+
+        warp_clock(&mut svm, create_vault_inst_data.transfer_min_warmup());
+        let tx = Transaction::new(
+            &[&alice], 
+            Message::new(&[execute_inst], Some(&alice.pubkey())), 
+            svm.latest_blockhash()
+        );
+        svm.send_transaction(tx).unwrap();
+
+        let dave_acc = TokenAccount::unpack(&svm.get_account(&dave_ata).unwrap().data).unwrap();
+        assert_eq!(dave_acc.amount, 300);
+    }
+
+    #[test]
+    fn alice_lists_her_pending_transfers() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+
+        // Transfer indices are handed out in order.
+        let first_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 100, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100);
+        let first_transfer_index = book_transfer(&mut svm, &first_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        let second_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 200, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100);
+        book_transfer(&mut svm, &second_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        let vault_data = find_vault_data_pda(
+            create_vault_inst_data.vault_index(), 
+            alice.pubkey().as_array(), 
+            mint.pubkey().as_array(),
+            TOKEN_PROGRAM.as_array()
+        );
+        assert_eq!(transfer_entries(&svm, &vault_data.0), vec![(0, carol_ata, carol_ata, 100), (1, carol_ata, carol_ata, 200)]);
+
+        // Executed transfers leave the registry.
+        execute_transfer(&mut svm, 
+            &ExecuteTransferInstructionData::new(first_transfer_inst_data.vault_index(), first_transfer_index), 
+            &alice, 
+            &carol_ata, 
+            &carol.pubkey(), 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+        assert_eq!(transfer_entries(&svm, &vault_data.0), vec![(1, carol_ata, carol_ata, 200)]);
+
+        let vault_data_acc = svm.get_account(&vault_data.0).unwrap();
+        let vault_data_account = from_bytes::<VaultData>(&vault_data_acc.data[..VaultData::LEN]).unwrap();
+        assert_eq!(vault_data_account.next_transfer_index(), 2);
+    }
+
+    #[test]
+    fn alice_pays_an_invoice_with_a_memo_and_reference() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_memo(Memo::new(b"INV-1042").unwrap());
+
+        // The reference is a read-only account of the booking, by which the payment can be found.
+        let reference = Pubkey::new_unique();
+        let vault_data = find_vault_data_pda(create_vault_inst_data.vault_index(), alice.pubkey().as_array(), mint.pubkey().as_array(), TOKEN_PROGRAM.as_array());
+        let transfer_index = next_transfer_index(&svm, &vault_data.0);
+        let mut inst = book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, transfer_index, &book_transfer_inst_data);
+        inst.accounts.push(AccountMeta::new_readonly(reference, false));
+        let tx = Transaction::new(
+            &[&alice], 
+            Message::new(&[inst], Some(&alice.pubkey())), 
+            svm.latest_blockhash()
+        );
+        let meta = svm.send_transaction(tx).unwrap();
+        assert!(meta.logs.iter().all(|l| !l.contains("INV-1042")));
+
+        // The memo is attached once the transfer executes, which is checked by the helper.
+        execute_transfer(&mut svm, 
+            &ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            &alice, 
+            &carol_ata, 
+            &carol.pubkey(), 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_withdraws_with_a_memo() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 100, 
+            /* max_transactions */ 2, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 5_000, 4_000);
+
+        // The memo is attached to the withdraw, which is checked by the helper.
+        withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+            &WithdrawFromVaultInstructionData::new(700, create_vault_inst_data.vault_index())
+                .with_memo(Memo::new(b"Payroll October").unwrap()));
+    }
+
+    #[test]
+    fn dave_claims_alices_link_into_a_new_token_account() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let dave = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&dave.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        // The destination is an ephemeral claim key, shared with the recipient as a link.
+        let claim_key = Keypair::new();
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ claim_key.pubkey().to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .as_claim_link();
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Dave holds the claim key, and claims to his own, not yet created, token account.
+        let dave_ata = get_associated_token_address(&dave.pubkey(), &mint.pubkey());
+        assert!(svm.get_account(&dave_ata).is_none());
+        claim_transfer(&mut svm, 
+            &ClaimTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            &claim_key, 
+            &dave, 
+            &alice.pubkey(), 
+            &dave_ata, 
+            &dave.pubkey(), 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_unbooks_an_unclaimed_link_after_it_expires() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+        // The destination is an ephemeral claim key, shared with the recipient as a link.
+        let claim_key = Keypair::new();
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ claim_key.pubkey().to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .as_claim_link();
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Nobody claimed the link in time, so its assets return to the vault.
+        warp_clock(&mut svm, 101);
+        unbook_transfer(&mut svm, 
+            &UnbookTransferInstructionData::new(
+                book_transfer_inst_data.vault_index(), 
+                transfer_index, 
+                claim_key.pubkey().to_bytes()), 
+            &alice, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn carol_settles_alices_sha256_hash_lock_with_the_preimage() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Carol only learns the preimage once she settles her side of the swap. It need not be as long as the hash.
+        let preimage = b"settled with carol";
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_hash_lock(HashLock::Sha256, solana_sdk::hash::hash(preimage).to_bytes());
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Revealing the preimage settles the transfer without Alice's signature.
+        execute_hash_locked_transfer(&mut svm, 
+            &ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index)
+                .with_preimage(Preimage::new(preimage).unwrap()), 
+            &carol, 
+            &alice.pubkey(), 
+            &carol_ata, 
+            &carol.pubkey(), 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn bob_refunds_alices_expired_keccak_hash_lock_into_her_vault() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Carol only learns the preimage once she settles her side of the swap.
+        let preimage = [7u8; HASH_LEN];
+        let book_transfer_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ carol_ata.to_bytes(),
+            /* vault_index */ create_vault_inst_data.vault_index(), 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_hash_lock(HashLock::Keccak256, solana_sdk::keccak::hash(&preimage).to_bytes());
+        let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Once timed out, anyone refunds the hash lock, even though Alice did not allow cranking.
+        warp_clock(&mut svm, 101);
+        crank_transfer(&mut svm, 
+            &CrankTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index), 
+            &bob, 
+            &alice.pubkey(), 
+            &carol_ata, 
+            &mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_and_bob_swap_between_their_vaults() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (alice_mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let (bob_mint, _bob_ata) = create_funded_vault(&mut svm, &bob, &create_vault_inst_data, 1_000, 500);
+
+        // Each side is paid into a token account of the other's mint.
+        let bob_ata = get_associated_token_address(&bob.pubkey(), &alice_mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+        let alice_ata = get_associated_token_address(&alice.pubkey(), &bob_mint.pubkey());
+        mint_to(&mut svm, 0, &bob, &alice.pubkey(), &alice_ata, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+        let alice_transfer = find_transfer_pda(0, 0, alice.pubkey().as_array(), bob_ata.as_array(), alice_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+        let bob_transfer = find_transfer_pda(0, 0, bob.pubkey().as_array(), alice_ata.as_array(), bob_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+        // Alice swaps 250 of her mint for 100 of Bob's.
+        let alice_book_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ bob_ata.to_bytes(),
+            /* vault_index */ 0, 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_swap(bob_transfer.to_bytes(), 100);
+        book_transfer(&mut svm, &alice_book_inst_data, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+        let bob_book_inst_data = BookTransferInstructionData::new(
+            /* amount */ 100, 
+            /* destination */ alice_ata.to_bytes(),
+            /* vault_index */ 0, 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_swap(alice_transfer.to_bytes(), 250);
+        book_transfer(&mut svm, &bob_book_inst_data, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Either side, or anyone else, settles both at once.
+        settle_swap(&mut svm, 
+            &SettleSwapInstructionData::new(0, 0, 0, 0), 
+            &bob, 
+            &alice.pubkey(), &bob_ata, &alice_mint.pubkey(), 
+            &bob.pubkey(), &alice_ata, &bob_mint.pubkey(), 
+            &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn bob_cancels_his_side_of_the_swap_before_settlement() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 4, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (alice_mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        let (bob_mint, _bob_ata) = create_funded_vault(&mut svm, &bob, &create_vault_inst_data, 1_000, 500);
+
+        // Each side is paid into a token account of the other's mint.
+        let bob_ata = get_associated_token_address(&bob.pubkey(), &alice_mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+        let alice_ata = get_associated_token_address(&alice.pubkey(), &bob_mint.pubkey());
+        mint_to(&mut svm, 0, &bob, &alice.pubkey(), &alice_ata, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+        let alice_transfer = find_transfer_pda(0, 0, alice.pubkey().as_array(), bob_ata.as_array(), alice_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+        let bob_transfer = find_transfer_pda(0, 0, bob.pubkey().as_array(), alice_ata.as_array(), bob_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+        // Alice swaps 250 of her mint for 100 of Bob's.
+        let alice_book_inst_data = BookTransferInstructionData::new(
+            /* amount */ 250, 
+            /* destination */ bob_ata.to_bytes(),
+            /* vault_index */ 0, 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_swap(bob_transfer.to_bytes(), 100);
+        book_transfer(&mut svm, &alice_book_inst_data, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+        let bob_book_inst_data = BookTransferInstructionData::new(
+            /* amount */ 100, 
+            /* destination */ alice_ata.to_bytes(),
+            /* vault_index */ 0, 
+            /* warmup */ 0, 
+            /* validity*/ 100)
+            .with_swap(alice_transfer.to_bytes(), 250);
+        book_transfer(&mut svm, &bob_book_inst_data, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Bob backs out before settlement, which leaves Alice's side unsettled.
+        unbook_transfer(&mut svm, &UnbookTransferInstructionData::new(0, 0, alice_ata.to_bytes()), &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+        let settle_inst = settle_swap_instruction(
+            &alice.pubkey(), &bob_ata, &alice_mint.pubkey(), 
+            &bob.pubkey(), &alice_ata, &bob_mint.pubkey(), 
+            &TOKEN_PROGRAM, 
+            &SettleSwapInstructionData::new(0, 0, 0, 0));
+        assert_pime_error(&mut svm, settle_inst, &alice, PimeError::AccountNotInitialized);
+
+        // Alice gets her side back into her vault.
+        unbook_transfer(&mut svm, &UnbookTransferInstructionData::new(0, 0, bob_ata.to_bytes()), &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_and_bob_fund_carols_campaign() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        // Bob has a vault of the same mint.
+        let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 1_000, &bob, &bob.pubkey(), &bob_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        create_new_vault(&mut svm, &bob, &create_vault_inst_data, &mint.pubkey());
+        deposit_to_vault(&mut svm, &bob_ata, &bob, &mint.pubkey(), &DepositToVaultInstructionData::new(bob.pubkey().to_bytes(), 0, 500));
+        // Carol raises into her own token account.
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let create_campaign_inst_data = CreateCampaignInstructionData::new(
+            /* campaign_index */ 0, 
+            /* target */ 400, 
+            /* deadline */ now + 100, 
+            /* allows withdraw */ true);
+        let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+        pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 200), &bob, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // The pledges exceed the target, and anyone releases them to Carol after the deadline.
+        warp_clock(&mut svm, 101);
+        finalize_campaign(&mut svm, &bob, &campaign, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Settling the pledges records them as executed in each vault.
+        settle_pledge(&mut svm, &SettlePledgeInstructionData::new(0), &carol, &alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+        // Settling the last pledge closes the campaign and its escrow, returning their rent to Carol.
+        settle_pledge(&mut svm, &SettlePledgeInstructionData::new(0), &alice, &bob.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn carols_campaign_misses_its_target_and_refunds_alice() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        // Carol raises into her own token account.
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let create_campaign_inst_data = CreateCampaignInstructionData::new(
+            /* campaign_index */ 0, 
+            /* target */ 400, 
+            /* deadline */ now + 100, 
+            /* allows withdraw */ false);
+        let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Anyone refunds the pledge into Alice's vault once the deadline has passed, which returns
+        // the campaign's rent to Carol.
+        warp_clock(&mut svm, 101);
+        settle_pledge(&mut svm, &SettlePledgeInstructionData::new(0), &bob, &alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+    }
+
+    #[test]
+    fn alice_withdraws_her_pledge_before_the_deadline() {
+        let mut svm = create_svm();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let create_vault_inst_data = CreateVaultInstructionData::new(
+            /* index */ 0, 
+            /* timeframe */ 2, 
+            /* max_transactions */ 3, 
+            /* max_amount */ 1_000, 
+            /* allows transfers */ true,
+            /* transfer_min_warmup */ 5, 
+            /* transfer_max_window */ 6,
+            /* withdraw_cooldown */ 0,
+            /* withdraw_warmup */ 0);
+        let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+        // Carol raises into her own token account.
+        let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+        mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let create_campaign_inst_data = CreateCampaignInstructionData::new(
+            /* campaign_index */ 0, 
+            /* target */ 400, 
+            /* deadline */ now + 100, 
+            /* allows withdraw */ true);
+        let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+        withdraw_pledge(&mut svm, &WithdrawPledgeInstructionData::new(0), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+        // Alice may pledge again afterwards.
+        pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 100), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+    }
 }
@@ -1,3 +1,35 @@
+mod common;
+use common::*;
+
+use pime::errors::PimeError;
+use pime::interface::instructions::amend_transfer_instruction::AmendTransferInstructionData;
+use pime::interface::instructions::book_transfer::BookTransferInstructionData;
+use pime::interface::instructions::close_vault_instruction::CloseVaultInstructionData;
+use pime::interface::instructions::create_campaign_instruction::CreateCampaignInstructionData;
+use pime::interface::instructions::create_vault_instruction::CreateVaultInstructionData;
+use pime::interface::instructions::deposit_to_vault_instruction::DepositToVaultInstructionData;
+use pime::interface::instructions::execute_transfer::ExecuteTransferInstructionData;
+use pime::interface::instructions::draw_transfer_instruction::DrawTransferInstructionData;
+use pime::interface::instructions::migrate_account_instruction::MigrateAccountInstructionData;
+use pime::interface::instructions::pledge_to_campaign_instruction::PledgeToCampaignInstructionData;
+use pime::interface::instructions::release_lot_instruction::ReleaseLotInstructionData;
+use pime::interface::instructions::return_deposit_instruction::ReturnDepositInstructionData;
+use pime::interface::instructions::set_deposit_policy_instruction::SetDepositPolicyInstructionData;
+use pime::interface::instructions::set_max_transactions_instruction::SetMaxTransactionsInstructionData;
+use pime::interface::instructions::settle_pledge_instruction::SettlePledgeInstructionData;
+use pime::interface::instructions::settle_swap_instruction::SettleSwapInstructionData;
+use pime::interface::instructions::unbook_transfer_instruction::UnbookTransferInstructionData;
+use pime::interface::instructions::withdraw_from_vault::WithdrawFromVaultInstructionData;
+use pime::interface::instructions::withdraw_pledge_instruction::WithdrawPledgeInstructionData;
+use pime::states::DepositPolicy;
+use pime::states::hash_lock::{HASH_LEN, HashLock, Preimage};
+use pime::states::lot_schedule::MAX_OPEN_LOTS;
+use pime::states::memo::Memo;
+use solana_sdk::{clock::Clock, instruction::AccountMeta, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_associated_token_account_interface::address::get_associated_token_address;
+
+const TOKEN_PROGRAM: Pubkey = spl_token_interface::ID;
+
 #[test]
 fn alice_create_vault_with_too_low_warm_up() {
 }
@@ -13,3 +45,1261 @@ fn alice_create_vault_with_warmup_below_zero() {
 #[test]
 fn alice_create_vault_with_validity_below_zero() {
 }
+
+#[test]
+fn alice_withdraws_during_vault_warmup() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 100, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 100);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::VaultWarmingUp);
+
+    // Once the warm-up has passed, the withdraw succeeds.
+    warp_clock(&mut svm, create_vault_inst_data.withdraw_warmup() + 1);
+    withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data);
+}
+
+#[test]
+fn alice_withdraws_during_cooldown() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 100, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 100,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index());
+    withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data);
+
+    let second_withdraw_inst_data = WithdrawFromVaultInstructionData::new(11, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &second_withdraw_inst_data), 
+        &alice, 
+        PimeError::WithdrawCoolingDown);
+
+    // Once the cool-down has passed, the withdraw succeeds.
+    warp_clock(&mut svm, create_vault_inst_data.withdraw_cooldown() + 1);
+    withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &second_withdraw_inst_data);
+}
+
+#[test]
+fn alice_withdraws_and_releases_locked_lot() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 100);
+
+    let lot_inst_data = DepositToVaultInstructionData::new_lot(
+        /* vault owner */ alice.pubkey().to_bytes(), 
+        /* vault index */ create_vault_inst_data.vault_index(), 
+        /* amount */ 400, 
+        /* lot index */ 0, 
+        /* maturity */ svm.get_sysvar::<Clock>().unix_timestamp + 100);
+    deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), &lot_inst_data);
+
+    // Only the unlocked 100 can be withdrawn.
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(101, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::FundsLocked);
+
+    let release_inst_data = ReleaseLotInstructionData::new(create_vault_inst_data.vault_index(), lot_inst_data.lot_index());
+    assert_pime_error(&mut svm, 
+        release_lot_instruction(&alice.pubkey(), &alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &release_inst_data), 
+        &alice, 
+        PimeError::LotNotMatured);
+}
+
+#[test]
+fn alice_locks_more_lots_than_the_vault_schedules() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 100);
+
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let lot = |lot_index: u64, maturity: i64| DepositToVaultInstructionData::new_lot(
+        /* vault owner */ alice.pubkey().to_bytes(), 
+        /* vault index */ create_vault_inst_data.vault_index(), 
+        /* amount */ 10, 
+        /* lot index */ lot_index, 
+        /* maturity */ maturity);
+    // The first lot matures early, the others a day later.
+    deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), &lot(0, now + 10));
+    for lot_index in 1..MAX_OPEN_LOTS as u64 {
+        deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), &lot(lot_index, now + 24 * 60 * 60));
+    }
+
+    // The schedule is full of unmatured lots.
+    assert_pime_error(&mut svm, 
+        deposit_to_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &lot(MAX_OPEN_LOTS as u64, now + 24 * 60 * 60)), 
+        &alice, 
+        PimeError::LotScheduleFull);
+
+    // Once the first lot has matured, its entry makes room, even though it was not released.
+    warp_clock(&mut svm, 10);
+    deposit_to_vault(&mut svm, &alice_ata, &alice, &mint.pubkey(), &lot(MAX_OPEN_LOTS as u64, now + 24 * 60 * 60));
+    let vault_data = find_vault_data_pda(create_vault_inst_data.vault_index(), alice.pubkey().as_array(), mint.pubkey().as_array(), TOKEN_PROGRAM.as_array());
+    assert_eq!(lot_entries(&svm, &vault_data.0).len(), MAX_OPEN_LOTS);
+}
+
+/// Creates Alice's funded vault, and gives Bob 500 of the vault's mint.
+fn alice_vault_and_funded_bob(svm: &mut litesvm::LiteSVM, alice: &Keypair, bob: &Keypair) -> (CreateVaultInstructionData, Keypair, Pubkey) {
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(svm, alice, &create_vault_inst_data, 1_000, 100);
+
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+    mint_to(svm, 500, alice, &bob.pubkey(), &bob_ata, alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    (create_vault_inst_data, mint, bob_ata)
+}
+
+#[test]
+fn bob_deposits_to_authority_only_vault() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+
+    set_deposit_policy(&mut svm, 
+        &SetDepositPolicyInstructionData::new(create_vault_inst_data.vault_index(), DepositPolicy::AuthorityOnly, 0), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    let deposit_inst_data = DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 50);
+    assert_pime_error(&mut svm, 
+        deposit_to_vault_instruction(&bob.pubkey(), &bob_ata, &mint.pubkey(), &deposit_inst_data), 
+        &bob, 
+        PimeError::DepositorNotAllowed);
+}
+
+#[test]
+fn bob_deposits_to_allowlist_vault_without_being_allowed() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+
+    set_deposit_policy(&mut svm, 
+        &SetDepositPolicyInstructionData::new(create_vault_inst_data.vault_index(), DepositPolicy::Allowlist, 0), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    let deposit_inst_data = DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 50);
+    assert_pime_error(&mut svm, 
+        deposit_to_vault_instruction(&bob.pubkey(), &bob_ata, &mint.pubkey(), &deposit_inst_data), 
+        &bob, 
+        PimeError::DepositorNotAllowed);
+}
+
+#[test]
+fn bob_deposits_below_minimum() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+
+    set_deposit_policy(&mut svm, 
+        &SetDepositPolicyInstructionData::new(create_vault_inst_data.vault_index(), DepositPolicy::Anyone, 100), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    let deposit_inst_data = DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 99);
+    assert_pime_error(&mut svm, 
+        deposit_to_vault_instruction(&bob.pubkey(), &bob_ata, &mint.pubkey(), &deposit_inst_data), 
+        &bob, 
+        PimeError::DepositBelowMinimum);
+}
+
+#[test]
+fn alice_returns_more_than_bob_deposited() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+
+    deposit_to_vault(&mut svm, &bob_ata, &bob, &mint.pubkey(), 
+        &DepositToVaultInstructionData::new(alice.pubkey().to_bytes(), create_vault_inst_data.vault_index(), 50));
+
+    // The vault holds 150, but only Bob's 50 can be returned to him.
+    let return_inst_data = ReturnDepositInstructionData::new(create_vault_inst_data.vault_index(), bob.pubkey().to_bytes(), 51);
+    assert_pime_error(&mut svm, 
+        return_deposit_instruction(&alice.pubkey(), &bob_ata, &mint.pubkey(), &TOKEN_PROGRAM, &return_inst_data), 
+        &alice, 
+        PimeError::ReturnExceedsDeposits);
+}
+
+#[test]
+fn alice_exceeds_withdraw_limit_within_timeframe() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, _bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+
+    // The vault allows 3 withdraws within its timeframe.
+    for _ in 0..create_vault_inst_data.max_transactions() {
+        withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+            &WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index()));
+        svm.expire_blockhash();
+    }
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::WithdrawLimitReachedTransactions);
+}
+
+#[test]
+fn alice_exceeds_withdraw_limit_before_raised_limit_applies() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, _bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+
+    // Raising the limit only applies once the timeframe has passed.
+    set_max_transactions(&mut svm, 
+        &SetMaxTransactionsInstructionData::new(create_vault_inst_data.vault_index(), 5), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    for _ in 0..create_vault_inst_data.max_transactions() {
+        withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+            &WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index()));
+        svm.expire_blockhash();
+    }
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::WithdrawLimitReachedTransactions);
+}
+
+#[test]
+fn alice_exceeds_lowered_withdraw_limit() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, _bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+
+    // Lowering the limit applies right away.
+    set_max_transactions(&mut svm, 
+        &SetMaxTransactionsInstructionData::new(create_vault_inst_data.vault_index(), 1), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    withdraw_from_vault(&mut svm, &alice, &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, 
+        &WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index()));
+    svm.expire_blockhash();
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(1, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::WithdrawLimitReachedTransactions);
+}
+
+#[test]
+fn alice_withdraws_with_wrong_account_as_vault_data() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, _bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index());
+
+    // An account of this program, but not a vault data account.
+    let mut inst = withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data);
+    inst.accounts[1].pubkey = find_registry_pda(alice.pubkey().as_array()).0;
+    assert_pime_error(&mut svm, inst, &alice, PimeError::InvalidDiscriminator);
+
+    // An account not owned by this program.
+    let mut inst = withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data);
+    inst.accounts[1].pubkey = alice_ata;
+    assert_pime_error(&mut svm, inst, &alice, PimeError::InvalidAccountOwner);
+}
+
+#[test]
+fn alice_withdraws_with_wrong_account_as_vault() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (create_vault_inst_data, mint, _bob_ata) = alice_vault_and_funded_bob(&mut svm, &alice, &bob);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &mint.pubkey());
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index());
+
+    // A token account of the same mint, but not the vault.
+    let mut inst = withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data);
+    inst.accounts[2].pubkey = alice_ata;
+    assert_pime_error(&mut svm, inst, &alice, PimeError::IncorrectPDA);
+}
+
+#[test]
+fn alice_tips_a_transfer_that_cant_be_cranked() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    // A tip is only ever paid to a crank.
+    let book_transfer_inst_data = BookTransferInstructionData::new_crankable(
+        /* amount */ 250, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100,
+        /* allows_crank */ false,
+        /* crank_tip */ 10_000);
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::InvalidCrankTip);
+}
+
+#[test]
+fn carol_draws_more_than_alices_stream_has_released() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 300, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 200)
+        .with_stream(100);
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Only 150 has been released half way through the stream.
+    warp_clock(&mut svm, 50);
+    let draw_inst_data = DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 151);
+    assert_pime_error(&mut svm, 
+        draw_transfer_instruction(&carol.pubkey(), &alice.pubkey(), &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM, &draw_inst_data), 
+        &carol, 
+        PimeError::DrawExceedsReleased);
+}
+
+#[test]
+fn alice_books_a_stream_ending_after_its_validity() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 300, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_stream(101);
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::InvalidTransferStream);
+}
+
+#[test]
+fn alice_amends_a_transfer_below_what_has_been_drawn() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_partial_draws();
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    draw_transfer(&mut svm, 
+        &DrawTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index, 100), 
+        &carol, 
+        &alice.pubkey(), 
+        &carol_ata, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+
+    let amend_inst_data = AmendTransferInstructionData::new(
+        book_transfer_inst_data.vault_index(), 
+        transfer_index, 
+        /* amount */ 100, 
+        /* destination */ carol_ata.to_bytes(), 
+        /* validity */ 100);
+    assert_pime_error(&mut svm, 
+        amend_transfer_instruction(&alice.pubkey(), &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM, &amend_inst_data), 
+        &alice, 
+        PimeError::InvalidAmendment);
+}
+
+#[test]
+fn alice_books_a_transfer_into_the_accounts_of_an_unbooked_one() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 100, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100);
+    book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Once unbooked, the transfer's accounts are free again, but the vault has moved on to its next index.
+    unbook_transfer(&mut svm, 
+        &UnbookTransferInstructionData::new(create_vault_inst_data.vault_index(), 0, alice_ata.to_bytes()), 
+        &alice, 
+        &mint.pubkey(), 
+        &TOKEN_PROGRAM);
+    svm.expire_blockhash();
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::IncorrectPDA);
+}
+
+#[test]
+fn alice_books_a_transfer_into_the_accounts_of_a_later_index() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 100, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100);
+    // The vault's first transfer index is 0.
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 2, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::IncorrectPDA);
+}
+
+#[test]
+fn alice_books_a_transfer_with_a_memo_which_is_not_utf8() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    // The memo program only accepts UTF-8, so the memo could never be attached.
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 100, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_memo(Memo::new(&[0xff, 0xfe]).unwrap());
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::InvalidMemo);
+}
+
+#[test]
+fn alice_executes_her_claim_link_herself() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    // The destination is an ephemeral claim key, shared with the recipient as a link.
+    let claim_key = Keypair::new();
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ claim_key.pubkey().to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .as_claim_link();
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Only the holder of the claim key chooses where the assets go.
+    let execute_inst_data = ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index);
+    assert_pime_error(&mut svm, 
+        execute_transfer_instruction(&alice.pubkey(), &claim_key.pubkey(), &claim_key.pubkey(), &alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &execute_inst_data), 
+        &alice, 
+        PimeError::TransferIsClaimLink);
+}
+
+#[test]
+fn carol_settles_a_hash_lock_with_the_wrong_preimage() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Carol only learns the preimage once she settles her side of the swap.
+    let preimage = [7u8; HASH_LEN];
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_hash_lock(HashLock::Sha256, solana_sdk::hash::hash(&preimage).to_bytes());
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    let execute_inst_data = ExecuteTransferInstructionData::new(book_transfer_inst_data.vault_index(), transfer_index)
+        .with_preimage(Preimage::new(&[8u8; HASH_LEN]).unwrap());
+    let mut inst = execute_transfer_instruction(&alice.pubkey(), &carol_ata, &carol_ata, &carol.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &execute_inst_data);
+    inst.accounts[0] = AccountMeta::new(alice.pubkey(), false);
+    assert_pime_error(&mut svm, inst, &carol, PimeError::InvalidPreimage);
+}
+
+#[test]
+fn alice_books_a_partially_drawable_hash_lock() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Partial draws are not checked against the preimage.
+    let preimage = [7u8; HASH_LEN];
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_hash_lock(HashLock::Sha256, solana_sdk::hash::hash(&preimage).to_bytes())
+        .with_partial_draws();
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::InvalidHashLock);
+}
+
+#[test]
+fn alice_unbooks_her_hash_lock_before_it_times_out() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Carol only learns the preimage once she settles her side of the swap.
+    let preimage = [7u8; HASH_LEN];
+    let book_transfer_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ carol_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_hash_lock(HashLock::Sha256, solana_sdk::hash::hash(&preimage).to_bytes());
+    let transfer_index = book_transfer(&mut svm, &book_transfer_inst_data, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Carol relies on the transfer until it times out.
+    let unbook_inst_data = UnbookTransferInstructionData::new(
+        book_transfer_inst_data.vault_index(), 
+        transfer_index, 
+        carol_ata.to_bytes());
+    assert_pime_error(&mut svm, 
+        unbook_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &unbook_inst_data), 
+        &alice, 
+        PimeError::HashLocked);
+}
+
+#[test]
+fn alice_settles_a_swap_bob_booked_for_less() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (alice_mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let (bob_mint, _bob_ata) = create_funded_vault(&mut svm, &bob, &create_vault_inst_data, 1_000, 500);
+
+    // Each side is paid into a token account of the other's mint.
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &alice_mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &bob_mint.pubkey());
+    mint_to(&mut svm, 0, &bob, &alice.pubkey(), &alice_ata, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    let alice_transfer = find_transfer_pda(0, 0, alice.pubkey().as_array(), bob_ata.as_array(), alice_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    let bob_transfer = find_transfer_pda(0, 0, bob.pubkey().as_array(), alice_ata.as_array(), bob_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    // Bob books 90 of his mint, while Alice asks for 100.
+    let alice_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(bob_transfer.to_bytes(), 100);
+    book_transfer(&mut svm, &alice_book_inst_data, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let bob_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 90, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(alice_transfer.to_bytes(), 250);
+    book_transfer(&mut svm, &bob_book_inst_data, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    let settle_inst = settle_swap_instruction(
+        &alice.pubkey(), &bob_ata, &alice_mint.pubkey(), 
+        &bob.pubkey(), &alice_ata, &bob_mint.pubkey(), 
+        &TOKEN_PROGRAM, 
+        &SettleSwapInstructionData::new(0, 0, 0, 0));
+    assert_pime_error(&mut svm, settle_inst, &alice, PimeError::SwapMismatch);
+}
+
+#[test]
+fn alice_executes_her_side_of_a_swap() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (alice_mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let (bob_mint, _bob_ata) = create_funded_vault(&mut svm, &bob, &create_vault_inst_data, 1_000, 500);
+
+    // Each side is paid into a token account of the other's mint.
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &alice_mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &bob.pubkey(), &bob_ata, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &bob_mint.pubkey());
+    mint_to(&mut svm, 0, &bob, &alice.pubkey(), &alice_ata, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    let alice_transfer = find_transfer_pda(0, 0, alice.pubkey().as_array(), bob_ata.as_array(), alice_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    let bob_transfer = find_transfer_pda(0, 0, bob.pubkey().as_array(), alice_ata.as_array(), bob_mint.pubkey().as_array(), TOKEN_PROGRAM.as_array()).0;
+    // Alice swaps 250 of her mint for 100 of Bob's.
+    let alice_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(bob_transfer.to_bytes(), 100);
+    book_transfer(&mut svm, &alice_book_inst_data, &alice, &alice_mint.pubkey(), &TOKEN_PROGRAM);
+    let bob_book_inst_data = BookTransferInstructionData::new(
+        /* amount */ 100, 
+        /* destination */ alice_ata.to_bytes(),
+        /* vault_index */ 0, 
+        /* warmup */ 0, 
+        /* validity*/ 100)
+        .with_swap(alice_transfer.to_bytes(), 250);
+    book_transfer(&mut svm, &bob_book_inst_data, &bob, &bob_mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Executing her side alone would pay Bob without him paying her.
+    let execute_inst_data = ExecuteTransferInstructionData::new(0, 0);
+    assert_pime_error(&mut svm, 
+        execute_transfer_instruction(&alice.pubkey(), &bob_ata, &bob_ata, &bob.pubkey(), &alice_mint.pubkey(), &TOKEN_PROGRAM, &execute_inst_data), 
+        &alice, 
+        PimeError::TransferIsSwap);
+}
+
+#[test]
+fn alice_books_a_crankable_swap() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 4, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let bob_ata = get_associated_token_address(&bob.pubkey(), &mint.pubkey());
+
+    // A crank could execute Alice's side without Bob's.
+    let book_transfer_inst_data = BookTransferInstructionData::new_crankable(
+        /* amount */ 250, 
+        /* destination */ bob_ata.to_bytes(),
+        /* vault_index */ create_vault_inst_data.vault_index(), 
+        /* warmup */ 0, 
+        /* validity*/ 100,
+        /* allows_crank */ true,
+        /* crank_tip */ 0)
+        .with_swap(Pubkey::new_unique().to_bytes(), 100);
+    assert_pime_error(&mut svm, 
+        book_transfer_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, /* transfer_index */ 0, &book_transfer_inst_data), 
+        &alice, 
+        PimeError::InvalidSwap);
+}
+
+#[test]
+fn carol_finalizes_her_campaign_before_the_deadline() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 450), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // The target is reached, but pledges can still be withdrawn until the deadline.
+    assert_pime_error(&mut svm, 
+        finalize_campaign_instruction(&campaign, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM), 
+        &carol, 
+        PimeError::CampaignActive);
+}
+
+#[test]
+fn alice_withdraws_from_a_campaign_which_does_not_allow_it() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ false);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    assert_pime_error(&mut svm, 
+        withdraw_pledge_instruction(&alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM, &WithdrawPledgeInstructionData::new(0)), 
+        &alice, 
+        PimeError::PledgeWithdrawNotAllowed);
+}
+
+#[test]
+fn alice_pledges_around_her_vaults_protections() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 300, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 50, 
+        /* transfer_max_window */ 60,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+    // A campaign ending before a booked transfer could be executed would skip the warm-up.
+    let soon_campaign = create_campaign(&mut svm, 
+        &CreateCampaignInstructionData::new(/* campaign_index */ 0, /* target */ 400, /* deadline */ now + 10, /* allows withdraw */ true), 
+        &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+    assert_pime_error(&mut svm, 
+        pledge_to_campaign_instruction(&alice.pubkey(), &soon_campaign, &mint.pubkey(), &TOKEN_PROGRAM, &PledgeToCampaignInstructionData::new(0, 250)), 
+        &alice, 
+        PimeError::VaultWarmupViolation);
+
+    // The pledge counts against the vault's withdraw amount limit.
+    let campaign = create_campaign(&mut svm, 
+        &CreateCampaignInstructionData::new(/* campaign_index */ 1, /* target */ 400, /* deadline */ now + 100, /* allows withdraw */ true), 
+        &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+    assert_pime_error(&mut svm, 
+        pledge_to_campaign_instruction(&alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM, &PledgeToCampaignInstructionData::new(0, 301)), 
+        &alice, 
+        PimeError::WithdrawLimitReachedAmount);
+}
+
+#[test]
+fn alice_pledges_from_a_vault_which_does_not_allow_transfers() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ false,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    assert_pime_error(&mut svm, 
+        pledge_to_campaign_instruction(&alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM, &PledgeToCampaignInstructionData::new(0, 250)), 
+        &alice, 
+        PimeError::TransfersNotAllowed);
+}
+
+#[test]
+fn alice_pledges_after_the_deadline() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    warp_clock(&mut svm, 101);
+    assert_pime_error(&mut svm, 
+        pledge_to_campaign_instruction(&alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM, &PledgeToCampaignInstructionData::new(0, 250)), 
+        &alice, 
+        PimeError::CampaignEnded);
+}
+
+#[test]
+fn carol_finalizes_a_campaign_which_missed_its_target() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    // Carol raises into her own token account.
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 400, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 250), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    warp_clock(&mut svm, 101);
+    assert_pime_error(&mut svm, 
+        finalize_campaign_instruction(&campaign, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM), 
+        &carol, 
+        PimeError::CampaignTargetNotReached);
+
+    // The pledge is refunded instead.
+    settle_pledge(&mut svm, &SettlePledgeInstructionData::new(0), &carol, &alice.pubkey(), &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+}
+
+#[test]
+fn alice_closes_her_vault_with_an_open_pledge() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    let carol = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&carol.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 2, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 1_000, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+    let carol_ata = get_associated_token_address(&carol.pubkey(), &mint.pubkey());
+    mint_to(&mut svm, 0, &alice, &carol.pubkey(), &carol_ata, &alice, &mint.pubkey(), &TOKEN_PROGRAM);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let create_campaign_inst_data = CreateCampaignInstructionData::new(
+        /* campaign_index */ 0, 
+        /* target */ 1_000, 
+        /* deadline */ now + 100, 
+        /* allows withdraw */ true);
+    let campaign = create_campaign(&mut svm, &create_campaign_inst_data, &carol, &carol_ata, &mint.pubkey(), &TOKEN_PROGRAM);
+
+    // Alice pledges everything, leaving her vault empty, but the pledge may still be refunded into it.
+    pledge_to_campaign(&mut svm, &PledgeToCampaignInstructionData::new(0, 500), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+    assert_pime_error(&mut svm, 
+        close_vault_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &CloseVaultInstructionData::new(0)), 
+        &alice, 
+        PimeError::VaultHasOpenTransfers);
+
+    // The pledge has to be withdrawn back into the vault first.
+    withdraw_pledge(&mut svm, &WithdrawPledgeInstructionData::new(0), &alice, &campaign, &mint.pubkey(), &TOKEN_PROGRAM);
+}
+
+#[test]
+fn alice_withdraws_from_version_1_vault_data() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 100, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let vault_data = find_vault_data_pda(
+        create_vault_inst_data.vault_index(), 
+        alice.pubkey().as_array(), 
+        mint.pubkey().as_array(),
+        TOKEN_PROGRAM.as_array()
+    );
+    set_version_1_vault_data(&mut svm, &vault_data.0, 0, &[]);
+
+    let withdraw_inst_data = WithdrawFromVaultInstructionData::new(10, create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        withdraw_from_vault_instruction(&alice.pubkey(), &alice_ata, &mint.pubkey(), &TOKEN_PROGRAM, &withdraw_inst_data), 
+        &alice, 
+        PimeError::OutdatedAccountVersion);
+}
+
+#[test]
+fn alice_migrates_current_vault_data() {
+    let mut svm = create_svm();
+    let alice = Keypair::new();
+    svm.airdrop(&alice.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let create_vault_inst_data = CreateVaultInstructionData::new(
+        /* index */ 0, 
+        /* timeframe */ 10, 
+        /* max_transactions */ 3, 
+        /* max_amount */ 100, 
+        /* allows transfers */ true,
+        /* transfer_min_warmup */ 5, 
+        /* transfer_max_window */ 6,
+        /* withdraw_cooldown */ 0,
+        /* withdraw_warmup */ 0);
+    let (mint, _alice_ata) = create_funded_vault(&mut svm, &alice, &create_vault_inst_data, 1_000, 500);
+
+    let migrate_inst_data = MigrateAccountInstructionData::new(create_vault_inst_data.vault_index());
+    assert_pime_error(&mut svm, 
+        migrate_account_instruction(&alice.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM, &migrate_inst_data), 
+        &alice, 
+        PimeError::AccountNotMigratable);
+}